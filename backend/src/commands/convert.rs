@@ -0,0 +1,197 @@
+use crate::commands::query::ensure_read_only;
+use crate::models::{
+    IngestFolder, IngestResult, ParquetWriteResult, PartitionResult, RewriteResult, SortSpec,
+    SplitResult, WriteOptions,
+};
+use crate::services::convert;
+use crate::services::ingest::IngestWatcher;
+use crate::services::parquet::{self, ParquetCache};
+use crate::services::tasks::TaskManager;
+
+#[tauri::command]
+pub async fn rewrite_parquet(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    source_path: String,
+    dest_path: String,
+    options: Option<WriteOptions>,
+) -> Result<RewriteResult, String> {
+    cache.check_path(&source_path)?;
+    cache.check_path(&dest_path)?;
+    let label = format!("Rewrite {}", source_path);
+    tasks
+        .run("convert", &label, |task| async move {
+            tokio::task::spawn_blocking(move || {
+                convert::rewrite_parquet(
+                    &source_path,
+                    &dest_path,
+                    &options.unwrap_or_default(),
+                    Some(&task),
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn split_parquet(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    dest_dir: String,
+    rows_per_file: Option<usize>,
+    max_bytes: Option<u64>,
+) -> Result<SplitResult, String> {
+    cache.check_path(&path)?;
+    cache.check_path(&dest_dir)?;
+    let label = format!("Split {}", path);
+    tasks
+        .run("convert", &label, |task| async move {
+            tokio::task::spawn_blocking(move || {
+                convert::split_parquet(&path, &dest_dir, rows_per_file, max_bytes, Some(&task))
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+/// Materialize a query result as a Parquet file. `session_id` is a workspace
+/// id or a file path, as for `execute_sql`.
+#[tauri::command]
+pub async fn create_parquet_from_sql(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    session_id: String,
+    query: String,
+    dest: String,
+    write_options: Option<WriteOptions>,
+) -> Result<ParquetWriteResult, String> {
+    cache.check_path(&dest)?;
+    ensure_read_only(&cache, &query)?;
+    let label = format!("Write query results to {}", dest);
+    tasks
+        .run("convert", &label, |_| async {
+            let stream = parquet::execute_sql_stream(&cache, &session_id, &query).await?;
+            convert::write_stream_to_parquet(stream, &dest, &write_options.unwrap_or_default())
+                .await
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn extract_columns(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    columns: Vec<String>,
+    dest: String,
+    options: Option<WriteOptions>,
+) -> Result<RewriteResult, String> {
+    cache.check_path(&path)?;
+    cache.check_path(&dest)?;
+    let label = format!("Extract columns from {}", path);
+    tasks
+        .run("convert", &label, |task| async move {
+            tokio::task::spawn_blocking(move || {
+                convert::extract_columns(
+                    &path,
+                    &columns,
+                    &dest,
+                    &options.unwrap_or_default(),
+                    Some(&task),
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn sort_parquet(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    dest: String,
+    sort: Vec<SortSpec>,
+    options: Option<WriteOptions>,
+) -> Result<RewriteResult, String> {
+    cache.check_path(&path)?;
+    cache.check_path(&dest)?;
+    let label = format!("Sort {}", path);
+    tasks
+        .run("convert", &label, |_| async {
+            convert::sort_parquet(&path, &dest, sort, &options.unwrap_or_default()).await
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn partition_parquet(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    dest_dir: String,
+    partition_columns: Vec<String>,
+    options: Option<WriteOptions>,
+) -> Result<PartitionResult, String> {
+    cache.check_path(&path)?;
+    cache.check_path(&dest_dir)?;
+    let label = format!("Partition {}", path);
+    tasks
+        .run("convert", &label, |task| async move {
+            tokio::task::spawn_blocking(move || {
+                convert::partition_parquet(
+                    &path,
+                    &dest_dir,
+                    &partition_columns,
+                    &options.unwrap_or_default(),
+                    Some(&task),
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+/// Convert new CSV and JSON Lines files in `config.folder` to Parquet as they
+/// appear, until `stop_ingest`. Results arrive as `ingest-finished` events.
+#[tauri::command]
+pub async fn start_ingest(
+    cache: tauri::State<'_, ParquetCache>,
+    watcher: tauri::State<'_, IngestWatcher>,
+    config: IngestFolder,
+) -> Result<(), String> {
+    cache.check_path(&config.folder)?;
+    if let Some(dest) = &config.destination {
+        cache.check_path(dest)?;
+    }
+    watcher.watch(config)
+}
+
+#[tauri::command]
+pub async fn stop_ingest(
+    watcher: tauri::State<'_, IngestWatcher>,
+    folder: String,
+) -> Result<(), String> {
+    watcher.unwatch(&folder)
+}
+
+#[tauri::command]
+pub async fn list_ingest_folders(
+    watcher: tauri::State<'_, IngestWatcher>,
+) -> Result<Vec<IngestFolder>, String> {
+    watcher.folders()
+}
+
+/// Conversions finished this session, most recent first.
+#[tauri::command]
+pub async fn list_ingest_results(
+    watcher: tauri::State<'_, IngestWatcher>,
+) -> Result<Vec<IngestResult>, String> {
+    watcher.results()
+}
@@ -1,42 +1,778 @@
-use crate::services::{export, parquet};
-use crate::services::parquet::ParquetCache;
+use crate::commands::data_dir;
+use crate::models::{
+    CacheStats, CellBytes, CellValue, ColumnSummaries, CountEstimate, DataDiff, DataPage,
+    DataProfile, DataWindow, DisplaySettings, EngineSettings, FilterExpr, FilterInput, Histogram,
+    LenientData, NullProfile, PivotTable, PruningReport, RowGroupPage, SearchResults, SelectedRows,
+    SensitiveColumns, SettingsUpdate, SortSpec, SqlValidation, TimeSeries, ValueCounts,
+};
+use crate::services::parquet::{CacheLimits, ParquetCache};
+use crate::services::reader::ReadMode;
+use crate::services::settings::SettingsStore;
+use crate::services::tasks::TaskManager;
+use crate::services::watcher::FileWatcher;
+use crate::services::{diff, export, parquet, pii, sample, sql};
+use crate::utils::{
+    BinaryDisplay, DisplayOptions, DisplayTimezone, NonFiniteDisplay, TimestampFormat,
+};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn read_parquet_data(
     cache: tauri::State<'_, ParquetCache>,
     path: String,
     offset: usize,
     limit: usize,
-    filter: Option<String>,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    timeout_ms: Option<u64>,
+    flatten: Option<bool>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    parquet::read_data(&cache, &path, offset, limit, filter).await
+    cache.check_path(&path)?;
+    parquet::read_data(
+        &cache,
+        &path,
+        offset,
+        limit,
+        filter,
+        sort,
+        timeout_ms,
+        flatten.unwrap_or(false),
+    )
+    .await
+}
+
+/// `read_parquet_data` as Arrow IPC stream bytes, for decoding with arrow-js.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn read_parquet_data_arrow(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    timeout_ms: Option<u64>,
+    flatten: Option<bool>,
+) -> Result<tauri::ipc::Response, String> {
+    cache.check_path(&path)?;
+    let bytes = parquet::read_data_ipc(
+        &cache,
+        &path,
+        offset,
+        limit,
+        filter,
+        sort,
+        timeout_ms,
+        flatten.unwrap_or(false),
+    )
+    .await?;
+    Ok(tauri::ipc::Response::new(bytes))
+}
+
+#[tauri::command]
+pub async fn read_parquet_data_lenient(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+) -> Result<LenientData, String> {
+    cache.check_path(&path)?;
+    parquet::read_data_lenient(&cache, &path, offset, limit, filter, sort).await
+}
+
+/// A page of rows with the total and filtered row counts, in one round trip.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_data_window(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    timeout_ms: Option<u64>,
+    flatten: Option<bool>,
+) -> Result<DataWindow, String> {
+    cache.check_path(&path)?;
+    parquet::read_window(
+        &cache,
+        &path,
+        offset,
+        limit,
+        filter,
+        sort,
+        timeout_ms,
+        flatten.unwrap_or(false),
+    )
+    .await
+}
+
+/// Rows of one row group, read directly instead of through a query.
+#[tauri::command]
+pub async fn read_row_group(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    row_group_index: usize,
+    offset: usize,
+    limit: usize,
+) -> Result<RowGroupPage, String> {
+    cache.check_path(&path)?;
+    let display = cache.display_options();
+    let mode = cache.read_mode();
+    tokio::task::spawn_blocking(move || {
+        parquet::read_row_group(&path, row_group_index, offset, limit, display, mode)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn read_parquet_page(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Vec<SortSpec>,
+    cursor: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<DataPage, String> {
+    cache.check_path(&path)?;
+    parquet::read_data_page(&cache, &path, limit, filter, sort, cursor, timeout_ms).await
+}
+
+/// Check a filter (a filter tree or the text of a `WHERE` condition) against
+/// the file's schema before it is applied.
+#[tauri::command]
+pub async fn validate_filter(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    filter: FilterInput,
+) -> Result<SqlValidation, String> {
+    cache.check_path(&path)?;
+    sql::validate_filter(&cache, &path, &filter).await
 }
 
 #[tauri::command]
 pub async fn count_parquet_data(
     cache: tauri::State<'_, ParquetCache>,
     path: String,
-    filter: Option<String>,
+    filter: Option<FilterExpr>,
 ) -> Result<usize, String> {
+    cache.check_path(&path)?;
     parquet::count_data(&cache, &path, filter).await
 }
 
+/// Fast row count from row-group statistics, flagged when approximate.
+#[tauri::command]
+pub async fn estimate_parquet_count(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    filter: Option<FilterExpr>,
+) -> Result<CountEstimate, String> {
+    cache.check_path(&path)?;
+    parquet::estimate_count(&cache, &path, filter.as_ref())
+}
+
+/// Which row groups statistics prune for `filter` and which must be scanned.
+#[tauri::command]
+pub async fn get_pruning_report(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    filter: FilterExpr,
+) -> Result<PruningReport, String> {
+    cache.check_path(&path)?;
+    parquet::pruning_report(&cache, &path, &filter)
+}
+
+#[tauri::command]
+pub async fn describe_parquet(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    columns: Option<Vec<String>>,
+) -> Result<DataProfile, String> {
+    cache.check_path(&path)?;
+    let label = format!("Profile {}", path);
+    tasks
+        .run("profile", &label, |_| {
+            parquet::describe(&cache, &path, columns)
+        })
+        .await
+}
+
+/// Column stats already computed for `path`, or `None` if they haven't been.
+#[tauri::command]
+pub async fn get_column_stats(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<Option<ColumnSummaries>, String> {
+    cache.check_path(&path)?;
+    Ok(cache.cached_column_stats(&path))
+}
+
+#[tauri::command]
+pub async fn compute_column_stats(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+) -> Result<ColumnSummaries, String> {
+    cache.check_path(&path)?;
+    let label = format!("Column stats {}", path);
+    tasks
+        .run("profile", &label, |_| parquet::column_stats(&cache, &path))
+        .await
+}
+
+/// Whether opening a file starts computing its column stats in the background.
+#[tauri::command]
+pub fn set_background_stats(cache: tauri::State<'_, ParquetCache>, enabled: bool) {
+    cache.set_background_stats(enabled);
+}
+
+#[tauri::command]
+pub async fn column_value_counts(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    column: String,
+    top_n: usize,
+    filter: Option<FilterExpr>,
+) -> Result<ValueCounts, String> {
+    cache.check_path(&path)?;
+    parquet::value_counts(&cache, &path, &column, top_n, filter).await
+}
+
+#[tauri::command]
+pub async fn column_histogram(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    column: String,
+    num_buckets: usize,
+    filter: Option<FilterExpr>,
+) -> Result<Histogram, String> {
+    cache.check_path(&path)?;
+    parquet::histogram(&cache, &path, &column, num_buckets, filter).await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn time_series(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    time_column: String,
+    interval: String,
+    value_column: Option<String>,
+    aggregation: String,
+    filter: Option<FilterExpr>,
+) -> Result<TimeSeries, String> {
+    cache.check_path(&path)?;
+    parquet::time_series(
+        &cache,
+        &path,
+        &time_column,
+        &interval,
+        value_column,
+        &aggregation,
+        filter,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn null_profile(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+) -> Result<NullProfile, String> {
+    cache.check_path(&path)?;
+    let label = format!("Null profile {}", path);
+    tasks
+        .run("profile", &label, |_| parquet::null_profile(&cache, &path))
+        .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_rows(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    text: String,
+    case_sensitive: bool,
+    columns: Option<Vec<String>>,
+    offset: usize,
+    limit: usize,
+    timeout_ms: Option<u64>,
+) -> Result<SearchResults, String> {
+    cache.check_path(&path)?;
+    parquet::search_rows(
+        &cache,
+        &path,
+        &text,
+        case_sensitive,
+        columns,
+        offset,
+        limit,
+        timeout_ms,
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn regex_search(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    column: String,
+    pattern: String,
+    case_sensitive: bool,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<serde_json::Value>, String> {
+    cache.check_path(&path)?;
+    parquet::regex_search(
+        &cache,
+        &path,
+        &column,
+        &pattern,
+        case_sensitive,
+        offset,
+        limit,
+        filter,
+        timeout_ms,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn find_row_positions(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    filter: FilterExpr,
+    sort: Option<Vec<SortSpec>>,
+    limit: Option<usize>,
+) -> Result<Vec<u64>, String> {
+    cache.check_path(&path)?;
+    let limit = limit.unwrap_or(parquet::DEFAULT_MAX_ROW_POSITIONS);
+    parquet::find_row_positions(&cache, &path, filter, sort, limit).await
+}
+
+#[tauri::command]
+pub async fn sample_rows(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    n: usize,
+    method: String,
+    seed: Option<u64>,
+) -> Result<Vec<serde_json::Value>, String> {
+    cache.check_path(&path)?;
+    sample::sample_rows(&path, n, &method, seed).await
+}
+
+#[tauri::command]
+pub async fn detect_sensitive_columns(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<SensitiveColumns, String> {
+    cache.check_path(&path)?;
+    pii::detect_sensitive_columns(&path).await
+}
+
+#[tauri::command]
+pub async fn distinct_values(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    column: String,
+    limit: usize,
+    search_prefix: Option<String>,
+) -> Result<Vec<serde_json::Value>, String> {
+    cache.check_path(&path)?;
+    parquet::distinct_values(&cache, &path, &column, limit, search_prefix).await
+}
+
+#[tauri::command]
+pub async fn pivot(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    group_by_columns: Vec<String>,
+    agg_column: String,
+    agg_func: String,
+    pivot_column: Option<String>,
+    filter: Option<FilterExpr>,
+) -> Result<PivotTable, String> {
+    cache.check_path(&path)?;
+    parquet::pivot(
+        &cache,
+        &path,
+        group_by_columns,
+        &agg_column,
+        &agg_func,
+        pivot_column,
+        filter,
+    )
+    .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn diff_data(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path_a: String,
+    path_b: String,
+    key_columns: Vec<String>,
+    compare_columns: Option<Vec<String>>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    timeout_ms: Option<u64>,
+) -> Result<DataDiff, String> {
+    cache.check_path(&path_a)?;
+    cache.check_path(&path_b)?;
+    let label = format!("Diff {} and {}", path_a, path_b);
+    tasks
+        .run("diff", &label, |_| {
+            diff::diff_data(
+                &path_a,
+                &path_b,
+                key_columns,
+                compare_columns,
+                offset.unwrap_or(0),
+                limit.unwrap_or(100),
+                timeout_ms,
+            )
+        })
+        .await
+}
+
+/// Forget a file that was closed: its cached entries and its change watch.
 #[tauri::command]
 pub async fn evict_cache(
     cache: tauri::State<'_, ParquetCache>,
+    watcher: tauri::State<'_, FileWatcher>,
     path: String,
 ) -> Result<(), String> {
     cache.evict(&path);
+    // Files that failed to be watched when opened have nothing to release
+    let _ = watcher.unwatch_file(&path);
     Ok(())
 }
 
+/// Drop everything persisted across restarts; returns the number of entries removed.
+#[tauri::command]
+pub async fn clear_disk_cache(cache: tauri::State<'_, ParquetCache>) -> Result<usize, String> {
+    cache.clear_disk_cache()
+}
+
+#[tauri::command]
+pub async fn get_cache_stats(cache: tauri::State<'_, ParquetCache>) -> Result<CacheStats, String> {
+    cache.stats()
+}
+
+/// Update cache limits; omitted values keep their current setting.
+#[tauri::command]
+pub async fn set_cache_limits(
+    cache: tauri::State<'_, ParquetCache>,
+    max_entries: Option<usize>,
+    memory_budget: Option<usize>,
+) -> Result<CacheStats, String> {
+    let current = cache.limits();
+    cache.set_limits(CacheLimits {
+        max_entries: max_entries.unwrap_or(current.max_entries),
+        memory_budget: memory_budget.unwrap_or(current.memory_budget),
+    })?;
+    cache.stats()
+}
+
+#[tauri::command]
+pub async fn get_engine_config(
+    cache: tauri::State<'_, ParquetCache>,
+) -> Result<EngineSettings, String> {
+    Ok(engine_settings(&cache))
+}
+
+/// Limit the memory queries may use before spilling to disk, and how many
+/// partitions (cores) each query uses. `memory_limit` or `target_partitions`
+/// of 0 restores the default, as does an empty `spill_dir`; omitted values
+/// keep their current setting. The blocking thread cap is read-only here; it
+/// is a setting applied at startup.
+#[tauri::command]
+pub async fn set_engine_config(
+    cache: tauri::State<'_, ParquetCache>,
+    memory_limit: Option<usize>,
+    spill_dir: Option<String>,
+    target_partitions: Option<usize>,
+) -> Result<EngineSettings, String> {
+    let mut config = cache.engine_config();
+    if let Some(limit) = memory_limit {
+        config.memory_limit = (limit > 0).then_some(limit);
+    }
+    if let Some(dir) = spill_dir {
+        if !dir.trim().is_empty() {
+            cache.check_path(&dir)?;
+        }
+        config.spill_dir = (!dir.trim().is_empty()).then_some(dir);
+    }
+    if let Some(partitions) = target_partitions {
+        config.target_partitions = (partitions > 0).then_some(partitions);
+    }
+    cache.set_engine_config(config)?;
+    Ok(engine_settings(&cache))
+}
+
+fn engine_settings(cache: &ParquetCache) -> EngineSettings {
+    let config = cache.engine_config();
+    EngineSettings {
+        memory_limit: config.memory_limit,
+        spill_dir: config.spill_dir,
+        target_partitions: config.target_partitions,
+        blocking_threads: parquet::blocking_threads(),
+        memory_used: cache.engine_memory_used(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_display_settings(
+    cache: tauri::State<'_, ParquetCache>,
+) -> Result<DisplaySettings, String> {
+    Ok(display_settings(cache.display_options()))
+}
+
+/// Choose how values are shown in the grid, query results and exports.
+/// `timezone` applies to UTC-adjusted timestamps: `"utc"`, `"local"` or an
+/// IANA timezone name. `timestamp_format` is a chrono `strftime` pattern for
+/// the date and time of every timestamp. Both are saved with the settings.
+/// `binary` is `"hex"`, `"base64"` or `"size"`, and `non_finite`
+/// (NaN/±Infinity) is `"string"` or `"null"`. Omitted values keep their
+/// current setting.
+#[tauri::command]
+pub async fn set_display_settings(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    settings: tauri::State<'_, SettingsStore>,
+    timezone: Option<String>,
+    timestamp_format: Option<String>,
+    binary: Option<String>,
+    non_finite: Option<String>,
+) -> Result<DisplaySettings, String> {
+    let mut display = cache.display_options();
+    if let Some(timezone) = &timezone {
+        display.timezone = DisplayTimezone::parse(timezone)?;
+    }
+    if let Some(format) = &timestamp_format {
+        display.timestamp_format = TimestampFormat::parse(format)?;
+    }
+    if let Some(binary) = binary {
+        display.binary = BinaryDisplay::parse(&binary)?;
+    }
+    if let Some(non_finite) = non_finite {
+        display.non_finite = NonFiniteDisplay::parse(&non_finite)?;
+    }
+    if timezone.is_some() || timestamp_format.is_some() {
+        let update = SettingsUpdate {
+            display_timezone: timezone.map(|_| display.timezone.name()),
+            timestamp_format: timestamp_format
+                .map(|_| display.timestamp_format.pattern().to_string()),
+            ..Default::default()
+        };
+        settings.update(&data_dir(&app)?, update)?;
+    }
+    cache.set_display_options(display)?;
+    Ok(display_settings(display))
+}
+
+fn display_settings(display: DisplayOptions) -> DisplaySettings {
+    DisplaySettings {
+        timezone: display.timezone.name(),
+        timestamp_format: display.timestamp_format.pattern().to_string(),
+        binary: display.binary.name().to_string(),
+        non_finite: display.non_finite.name().to_string(),
+    }
+}
+
+#[tauri::command]
+pub async fn get_read_mode(cache: tauri::State<'_, ParquetCache>) -> Result<String, String> {
+    Ok(cache.read_mode().name().to_string())
+}
+
+/// Choose how files are read for metadata and direct page reads: `"direct"`,
+/// `"buffered"` (large read buffer, for spinning disks and network mounts)
+/// or `"mmap"` (memory-mapped).
+#[tauri::command]
+pub async fn set_read_mode(
+    cache: tauri::State<'_, ParquetCache>,
+    mode: String,
+) -> Result<String, String> {
+    let mode = ReadMode::parse(&mode)?;
+    cache.set_read_mode(mode)?;
+    Ok(mode.name().to_string())
+}
+
+/// Untruncated value of one cell, addressed by its position in the view
+/// produced by `filter` and `sort`, for the detail pane.
+#[tauri::command]
+pub async fn get_cell_value(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    row_index: usize,
+    column: String,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+) -> Result<CellValue, String> {
+    cache.check_path(&path)?;
+    parquet::cell_value(&cache, &path, row_index, &column, filter, sort).await
+}
+
+/// Full payload of a binary or string cell, for inspecting values that the
+/// grid only shows truncated. Returns `None` for a null cell.
+#[tauri::command]
+pub async fn get_cell_bytes(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    row: usize,
+    column: String,
+) -> Result<Option<CellBytes>, String> {
+    cache.check_path(&path)?;
+    parquet::cell_bytes(&cache, &path, row, &column).await
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn export_data(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
     source_path: String,
     export_path: String,
     format: String,
     offset: Option<usize>,
     limit: Option<usize>,
+    compression: Option<String>,
+    flatten: Option<bool>,
+    password: Option<String>,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+) -> Result<String, String> {
+    cache.check_path(&source_path)?;
+    cache.check_path(&export_path)?;
+    let label = format!("Export to {}", export_path);
+    let display = cache.display_options();
+    let flatten = flatten.unwrap_or(false);
+
+    // A filtered or sorted view has to go through a query; otherwise rows are
+    // read straight from the file
+    if filter.is_some() || sort.as_ref().is_some_and(|sort| !sort.is_empty()) {
+        return tasks
+            .run("export", &label, |task| async move {
+                export::export_view(
+                    &cache,
+                    &source_path,
+                    &export_path,
+                    &format,
+                    filter,
+                    sort,
+                    offset,
+                    limit,
+                    compression,
+                    password,
+                    flatten,
+                    Some(&task),
+                )
+                .await
+            })
+            .await;
+    }
+
+    tasks
+        .run("export", &label, |task| async move {
+            tokio::task::spawn_blocking(move || {
+                export::export_data(
+                    source_path,
+                    export_path,
+                    format,
+                    offset,
+                    limit,
+                    compression,
+                    password,
+                    display,
+                    flatten,
+                    Some(&task),
+                )
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+/// Export the rows picked out of the current view, by position or key.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_rows(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    source_path: String,
+    selection: SelectedRows,
+    format: String,
+    export_path: String,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    compression: Option<String>,
+    password: Option<String>,
+    flatten: Option<bool>,
 ) -> Result<String, String> {
-    export::export_data(source_path, export_path, format, offset, limit)
+    cache.check_path(&source_path)?;
+    cache.check_path(&export_path)?;
+    let label = format!("Export to {}", export_path);
+    tasks
+        .run("export", &label, |_| {
+            export::export_rows(
+                &cache,
+                &source_path,
+                &selection,
+                filter,
+                sort,
+                &export_path,
+                &format,
+                compression,
+                password,
+                flatten.unwrap_or(false),
+            )
+        })
+        .await
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn export_to_clipboard(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    offset: usize,
+    limit: usize,
+    columns: Option<Vec<String>>,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    format: String,
+) -> Result<String, String> {
+    cache.check_path(&path)?;
+    let columns = match columns {
+        Some(columns) => columns,
+        None => cache
+            .get_or_create_metadata(&path)?
+            .columns
+            .into_iter()
+            .map(|c| c.name)
+            .collect(),
+    };
+
+    let rows = parquet::read_data(&cache, &path, offset, limit, filter, sort, None, false).await?;
+    let text = export::render_rows(&rows, &columns, &format)?;
+
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
+
+    Ok(format!("Copied {} rows to clipboard", rows.len()))
 }
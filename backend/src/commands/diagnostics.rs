@@ -0,0 +1,39 @@
+use std::path::PathBuf;
+
+use crate::commands::data_dir;
+use crate::models::{DiagnosticsBundle, DiagnosticsReport};
+use crate::services::diagnostics;
+use crate::services::parquet::ParquetCache;
+use crate::services::settings::SettingsStore;
+
+/// Write a diagnostics bundle (versions, settings, cache state and recent
+/// logs) to `dest`, or to the app data directory when omitted.
+#[tauri::command]
+pub async fn collect_diagnostics(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    settings: tauri::State<'_, SettingsStore>,
+    dest: Option<String>,
+) -> Result<DiagnosticsBundle, String> {
+    let data_dir = data_dir(&app)?;
+    let report = DiagnosticsReport {
+        app_version: app.package_info().version.to_string(),
+        datafusion_version: datafusion::DATAFUSION_VERSION.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        read_mode: cache.read_mode().name().to_string(),
+        settings: settings.get(),
+        cache: cache.stats()?,
+    };
+    if let Some(dest) = &dest {
+        cache.check_path(dest)?;
+    }
+    let dest = dest
+        .map(PathBuf::from)
+        .unwrap_or_else(|| diagnostics::default_bundle_path(&data_dir));
+
+    tokio::task::spawn_blocking(move || diagnostics::collect_diagnostics(&data_dir, &dest, &report))
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -1,18 +1,109 @@
-use crate::models::{FileEntry, FileInfo, ParquetMetadata};
-use crate::services::parquet::ParquetCache;
+use crate::models::{
+    ColumnSummaries, DirectorySummary, FileEntry, FileInfo, MetadataPrefetch, OpenFileRequest,
+    ParquetMetadata, SchemaDriftReport,
+};
+use crate::services::directory;
+use crate::services::launch::LaunchQueue;
+use crate::services::parquet::{self, ParquetCache, OPEN_STAGES, STAGE_SESSION_REGISTERED};
+use crate::services::tasks::TaskManager;
+use crate::services::watcher::{DirectoryWatcher, FileWatcher};
+use serde::Serialize;
 use std::fs::{metadata, read_dir};
 use std::path::Path;
+use tauri::{Emitter, Manager};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenProgressEvent {
+    pub path: String,
+    pub stage: String,
+    pub step: usize,
+    pub total_steps: usize,
+}
+
+fn emit_open_progress(app: &tauri::AppHandle, path: &str, stage: &str) {
+    let step = OPEN_STAGES.iter().position(|s| *s == stage).unwrap_or(0) + 1;
+    // Progress is cosmetic, so a failed emit must not fail the open itself
+    let _ = app.emit(
+        "open-progress",
+        OpenProgressEvent {
+            path: path.to_string(),
+            stage: stage.to_string(),
+            step,
+            total_steps: OPEN_STAGES.len(),
+        },
+    );
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetadataLoadedEvent {
+    pub directory: String,
+    pub path: String,
+    pub metadata: Option<ParquetMetadata>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnStatsEvent {
+    pub path: String,
+    pub stats: Option<ColumnSummaries>,
+    pub error: Option<String>,
+}
+
+/// Compute column stats for `path` as a background task, emitting
+/// `column-stats-ready` when done.
+fn spawn_column_stats(app: tauri::AppHandle, path: String) {
+    tauri::async_runtime::spawn(async move {
+        let cache = app.state::<ParquetCache>();
+        let tasks = app.state::<TaskManager>();
+        let label = format!("Column stats {}", path);
+        let result = tasks
+            .run("profile", &label, |_| parquet::column_stats(&cache, &path))
+            .await;
+        let _ = app.emit(
+            "column-stats-ready",
+            ColumnStatsEvent {
+                path: path.clone(),
+                error: result.as_ref().err().cloned(),
+                stats: result.ok(),
+            },
+        );
+    });
+}
 
 #[tauri::command]
 pub async fn open_parquet_file(
+    app: tauri::AppHandle,
     cache: tauri::State<'_, ParquetCache>,
+    watcher: tauri::State<'_, FileWatcher>,
     path: String,
 ) -> Result<ParquetMetadata, String> {
-    cache.get_or_create_metadata(&path)
+    cache.check_path(&path)?;
+    let metadata = cache.get_or_create_metadata_with_progress(&path, &|stage| {
+        emit_open_progress(&app, &path, stage)
+    })?;
+
+    // Register the session up front so the first data request doesn't pay for it
+    cache.get_or_create_session(&path).await?;
+    emit_open_progress(&app, &path, STAGE_SESSION_REGISTERED);
+
+    // Some network filesystems can't be watched; the file is still usable without it
+    if let Err(e) = watcher.watch_file(&path) {
+        tracing::warn!("Not watching {}: {}", path, e);
+    }
+
+    if cache.background_stats() && cache.cached_column_stats(&path).is_none() {
+        spawn_column_stats(app.clone(), path.clone());
+    }
+
+    Ok(metadata)
 }
 
 #[tauri::command]
-pub async fn get_file_info(path: String) -> Result<FileInfo, String> {
+pub async fn get_file_info(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<FileInfo, String> {
+    cache.check_path(&path)?;
     let file_path = Path::new(&path);
     let file_metadata = metadata(&path).map_err(|e| e.to_string())?;
 
@@ -30,12 +121,29 @@ pub async fn get_file_info(path: String) -> Result<FileInfo, String> {
 }
 
 #[tauri::command]
-pub async fn check_file_exists(path: String) -> Result<bool, String> {
+pub async fn check_file_exists(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<bool, String> {
+    cache.check_path(&path)?;
     Ok(Path::new(&path).exists())
 }
 
+/// Files the app was asked to open before the window was listening, e.g.
+/// launch arguments. Later requests arrive as `open-file` events.
 #[tauri::command]
-pub async fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
+pub async fn take_pending_open_files(
+    queue: tauri::State<'_, LaunchQueue>,
+) -> Result<Vec<OpenFileRequest>, String> {
+    Ok(queue.take_pending())
+}
+
+#[tauri::command]
+pub async fn list_directory(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<Vec<FileEntry>, String> {
+    cache.check_path(&path)?;
     let dir_path = Path::new(&path);
 
     if !dir_path.exists() {
@@ -77,11 +185,123 @@ pub async fn list_directory(path: String) -> Result<Vec<FileEntry>, String> {
     }
 
     // Sort: directories first, then files, alphabetically
-    entries.sort_by(|a, b| match (a.is_directory, b.is_directory) {
-        (true, false) => std::cmp::Ordering::Less,
-        (false, true) => std::cmp::Ordering::Greater,
-        _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
-    });
+    directory::sort_entries(&mut entries);
 
     Ok(entries)
 }
+
+#[tauri::command]
+pub async fn scan_directory(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    glob: Option<String>,
+    max_depth: Option<usize>,
+    parquet_only: Option<bool>,
+) -> Result<Vec<FileEntry>, String> {
+    cache.check_path(&path)?;
+    tokio::task::spawn_blocking(move || {
+        directory::scan_directory(
+            &path,
+            glob.as_deref(),
+            max_depth,
+            parquet_only.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Read and cache the footers of every Parquet file under `path`, emitting a
+/// `metadata-loaded` event per file so the UI can fill in as they arrive.
+#[tauri::command]
+pub async fn prefetch_directory_metadata(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+) -> Result<MetadataPrefetch, String> {
+    cache.check_path(&path)?;
+    let root = path.clone();
+    let files = tokio::task::spawn_blocking(move || directory::parquet_files(Path::new(&root)))
+        .await
+        .map_err(|e| e.to_string())??
+        .into_iter()
+        .map(|file| file.to_string_lossy().into_owned())
+        .collect();
+
+    let label = format!("Load metadata in {}", path);
+    let on_loaded = |file: &str, result: Result<&ParquetMetadata, &str>| {
+        let _ = app.emit(
+            "metadata-loaded",
+            MetadataLoadedEvent {
+                directory: path.clone(),
+                path: file.to_string(),
+                metadata: result.ok().cloned(),
+                error: result.err().map(str::to_string),
+            },
+        );
+    };
+    tasks
+        .run("scan", &label, |task| async move {
+            parquet::prefetch_metadata(&cache, files, &on_loaded, Some(&task)).await
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn summarize_directory(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+) -> Result<DirectorySummary, String> {
+    cache.check_path(&path)?;
+    let label = format!("Summarize {}", path);
+    tasks
+        .run("scan", &label, |_| async move {
+            tokio::task::spawn_blocking(move || directory::summarize_directory(&path))
+                .await
+                .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn check_schema_drift(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    reference_path: Option<String>,
+) -> Result<SchemaDriftReport, String> {
+    cache.check_path(&path)?;
+    if let Some(reference) = &reference_path {
+        cache.check_path(reference)?;
+    }
+    let label = format!("Check schemas in {}", path);
+    tasks
+        .run("scan", &label, |_| async move {
+            tokio::task::spawn_blocking(move || {
+                directory::check_schema_drift(&path, reference_path.as_deref())
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn watch_directory(
+    cache: tauri::State<'_, ParquetCache>,
+    watcher: tauri::State<'_, DirectoryWatcher>,
+    path: String,
+) -> Result<(), String> {
+    cache.check_path(&path)?;
+    watcher.watch_directory(&path)
+}
+
+#[tauri::command]
+pub async fn unwatch_directory(
+    watcher: tauri::State<'_, DirectoryWatcher>,
+    path: String,
+) -> Result<(), String> {
+    watcher.unwatch_directory(&path)
+}
@@ -0,0 +1,150 @@
+use crate::models::{
+    BloomCheck, ColumnIndexInfo, ColumnSizes, ExtendedMetadata, FileHash, FileMetadataInfo,
+    FileVerification, ParquetDiagnosis, RowGroupInfo, SchemaDiff, SchemaDocument, TableDdl,
+};
+use crate::services::bloom;
+use crate::services::inspect;
+use crate::services::lineage;
+use crate::services::parquet::ParquetCache;
+use crate::services::schema;
+use crate::services::tasks::TaskManager;
+
+#[tauri::command]
+pub async fn get_row_group_info(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<Vec<RowGroupInfo>, String> {
+    cache.check_path(&path)?;
+    inspect::get_row_group_info(&path)
+}
+
+#[tauri::command]
+pub async fn column_sizes(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<ColumnSizes, String> {
+    cache.check_path(&path)?;
+    inspect::column_sizes(&path)
+}
+
+#[tauri::command]
+pub async fn get_file_metadata(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<FileMetadataInfo, String> {
+    cache.check_path(&path)?;
+    inspect::get_file_metadata(&path)
+}
+
+#[tauri::command]
+pub async fn get_extended_metadata(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<ExtendedMetadata, String> {
+    cache.check_path(&path)?;
+    lineage::get_extended_metadata(&path)
+}
+
+#[tauri::command]
+pub async fn get_index_info(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<Vec<ColumnIndexInfo>, String> {
+    cache.check_path(&path)?;
+    inspect::get_index_info(&path)
+}
+
+/// Whether `value` might occur in `column`, from statistics and bloom filters.
+#[tauri::command]
+pub async fn bloom_check(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    column: String,
+    value: serde_json::Value,
+) -> Result<BloomCheck, String> {
+    cache.check_path(&path)?;
+    bloom::bloom_check(&cache, &path, &column, &value)
+}
+
+#[tauri::command]
+pub async fn diagnose_parquet(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<ParquetDiagnosis, String> {
+    cache.check_path(&path)?;
+    inspect::diagnose_parquet(&path)
+}
+
+#[tauri::command]
+pub async fn diff_schemas(
+    cache: tauri::State<'_, ParquetCache>,
+    path_a: String,
+    path_b: String,
+) -> Result<SchemaDiff, String> {
+    cache.check_path(&path_a)?;
+    cache.check_path(&path_b)?;
+    inspect::diff_schemas(&path_a, &path_b)
+}
+
+#[tauri::command]
+pub async fn verify_file(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    verify_checksums: Option<bool>,
+) -> Result<FileVerification, String> {
+    cache.check_path(&path)?;
+    let label = format!("Verify {}", path);
+    tasks
+        .run("verify", &label, |task| async move {
+            tokio::task::spawn_blocking(move || {
+                inspect::verify_file(&path, verify_checksums.unwrap_or(false), Some(&task))
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+/// Hash a file with "md5" or "sha256", reporting progress as a task.
+#[tauri::command]
+pub async fn hash_file(
+    cache: tauri::State<'_, ParquetCache>,
+    tasks: tauri::State<'_, TaskManager>,
+    path: String,
+    algorithm: String,
+) -> Result<FileHash, String> {
+    cache.check_path(&path)?;
+    let label = format!("Hash {}", path);
+    tasks
+        .run("hash", &label, |task| async move {
+            tokio::task::spawn_blocking(move || inspect::hash_file(&path, &algorithm, Some(&task)))
+                .await
+                .map_err(|e| e.to_string())?
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn generate_ddl(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    dialect: String,
+) -> Result<TableDdl, String> {
+    cache.check_path(&path)?;
+    schema::generate_ddl(&path, &dialect)
+}
+
+#[tauri::command]
+pub async fn export_schema(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+    format: String,
+    dest: Option<String>,
+) -> Result<SchemaDocument, String> {
+    cache.check_path(&path)?;
+    if let Some(dest) = &dest {
+        cache.check_path(dest)?;
+    }
+    schema::export_schema(&path, &format, dest.as_deref())
+}
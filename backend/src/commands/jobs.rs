@@ -0,0 +1,100 @@
+use tauri::{AppHandle, Manager};
+
+use crate::commands::data_dir;
+use crate::commands::query::ensure_read_only;
+use crate::models::{Job, JobRun, JobSpec, JobTrigger};
+use crate::services::jobs::{self, JobScheduler};
+use crate::services::logging;
+use crate::services::parquet::ParquetCache;
+use crate::services::tasks::TaskManager;
+
+/// Hold a job to the same path scope and write setting as interactive use.
+/// Checked again on every run, since either may have changed since it was saved.
+fn check_job(cache: &ParquetCache, spec: &JobSpec) -> Result<(), String> {
+    cache.check_path(&spec.source)?;
+    cache.check_path(&spec.destination)?;
+    if let Some(query) = &spec.query {
+        ensure_read_only(cache, query)?;
+    }
+    Ok(())
+}
+
+/// Run a job already marked as running in the scheduler, clearing the mark
+/// once it ends.
+async fn run_started(app: &AppHandle, job: Job, trigger: JobTrigger) -> Result<JobRun, String> {
+    let result = async {
+        let cache = app.state::<ParquetCache>();
+        check_job(&cache, &job.spec)?;
+        let tasks = app.state::<TaskManager>();
+        jobs::run_job(&cache, &tasks, &data_dir(app)?, &job, trigger).await
+    }
+    .await;
+    app.state::<JobScheduler>().finished(job.id);
+    result
+}
+
+/// Start every repeating job that is due, each in the background.
+pub(crate) fn run_due_jobs(app: &AppHandle) {
+    let jobs = match data_dir(app).and_then(|dir| jobs::list_jobs(&dir)) {
+        Ok(jobs) => jobs,
+        Err(e) => {
+            tracing::warn!("Failed to load jobs: {}", e);
+            return;
+        }
+    };
+    for job in app.state::<JobScheduler>().take_due(jobs) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let name = job.spec.name.clone();
+            match run_started(&app, job, JobTrigger::Schedule).await {
+                Ok(run) if run.error.is_none() => {}
+                Ok(run) => tracing::warn!(
+                    "Job {} failed: {}",
+                    name,
+                    logging::redact(&run.error.unwrap_or_default())
+                ),
+                Err(e) => {
+                    tracing::warn!("Job {} could not run: {}", name, logging::redact(&e))
+                }
+            }
+        });
+    }
+}
+
+/// Save a new job, or replace the definition of job `id`.
+#[tauri::command]
+pub async fn save_job(
+    app: AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    id: Option<u64>,
+    spec: JobSpec,
+) -> Result<Job, String> {
+    check_job(&cache, &spec)?;
+    jobs::save_job(&data_dir(&app)?, id, spec)
+}
+
+#[tauri::command]
+pub async fn list_jobs(app: AppHandle) -> Result<Vec<Job>, String> {
+    jobs::list_jobs(&data_dir(&app)?)
+}
+
+#[tauri::command]
+pub async fn delete_job(app: AppHandle, id: u64) -> Result<(), String> {
+    jobs::delete_job(&data_dir(&app)?, id)
+}
+
+/// Run job `id` now, waiting for it to finish.
+#[tauri::command]
+pub async fn run_job(app: AppHandle, id: u64) -> Result<JobRun, String> {
+    let job = jobs::get_job(&data_dir(&app)?, id)?;
+    if !app.state::<JobScheduler>().try_start(id) {
+        return Err(format!("Job {} is already running", job.spec.name));
+    }
+    run_started(&app, job, JobTrigger::Manual).await
+}
+
+/// Past runs, most recent first, optionally only those of job `id`.
+#[tauri::command]
+pub async fn list_job_runs(app: AppHandle, id: Option<u64>) -> Result<Vec<JobRun>, String> {
+    jobs::job_runs(&data_dir(&app)?, id)
+}
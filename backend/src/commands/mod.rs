@@ -1,3 +1,22 @@
+pub mod convert;
 pub mod data;
+pub mod diagnostics;
 pub mod file;
+pub mod inspect;
+pub mod jobs;
 pub mod query;
+pub mod recent_files;
+pub mod saved_queries;
+pub mod session;
+pub mod settings;
+pub mod tasks;
+pub mod view_state;
+pub mod workspace;
+
+use std::path::PathBuf;
+use tauri::Manager;
+
+/// The app data directory, where the JSON stores and caches live.
+pub(crate) fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| e.to_string())
+}
@@ -1,9 +1,22 @@
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use datafusion::sql::parser::{DFParser, Statement as DFStatement};
+use datafusion::sql::sqlparser::ast::{ObjectType, Statement};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, Emitter};
 
-use crate::services::parquet::{batches_to_json_bytes, ParquetCache};
+use crate::models::{QueryPlan, SqlValidation};
+use crate::services::logging;
+use crate::services::notifications::Notifier;
+use crate::services::parquet::{batches_to_rows, with_timeout, ParquetCache};
+use crate::services::settings::SettingsStore;
+use crate::utils::{render_for_display, restore_non_finite, DisplayOptions};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Rows per `query-batch` event when the caller gives no chunk size.
+const DEFAULT_STREAM_CHUNK_ROWS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryColumn {
     pub name: String,
     pub data_type: String,
@@ -14,41 +27,306 @@ pub struct QueryResult {
     pub columns: Vec<QueryColumn>,
     pub rows: Vec<serde_json::Map<String, serde_json::Value>>,
     pub execution_time_ms: u128,
+    /// Whether collection stopped at the row cap.
+    pub truncated: bool,
+    pub total_rows_estimate: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryBatchEvent {
+    pub query_id: String,
+    pub batch_index: usize,
+    pub rows: Vec<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryCompleteEvent {
+    pub query_id: String,
+    pub columns: Vec<QueryColumn>,
+    pub total_rows: usize,
+    pub batch_count: usize,
+    pub execution_time_ms: u128,
+}
+
+fn schema_columns(schema: &SchemaRef) -> Vec<QueryColumn> {
+    schema
+        .fields()
+        .iter()
+        .map(|f| QueryColumn {
+            name: f.name().clone(),
+            data_type: f.data_type().to_string(),
+        })
+        .collect()
+}
+
+fn batches_to_objects(
+    batches: &[RecordBatch],
+    display: DisplayOptions,
+) -> Result<Vec<serde_json::Map<String, serde_json::Value>>, String> {
+    let rendered = batches
+        .iter()
+        .map(|batch| render_for_display(batch, display))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut rows = batches_to_rows(&rendered)?;
+    restore_non_finite(&rendered, &mut rows, display.non_finite);
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| match row {
+            serde_json::Value::Object(map) => Some(map),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Whether a statement only reads data or changes in-memory session state
+/// (views, settings) without touching files.
+fn is_read_only(statement: &DFStatement) -> bool {
+    match statement {
+        DFStatement::Statement(statement) => match statement.as_ref() {
+            Statement::Query(_)
+            | Statement::CreateView { .. }
+            | Statement::SetVariable { .. }
+            | Statement::ShowVariable { .. }
+            | Statement::ShowTables { .. }
+            | Statement::ShowColumns { .. }
+            | Statement::ExplainTable { .. } => true,
+            Statement::Drop { object_type, .. } => *object_type == ObjectType::View,
+            // EXPLAIN ANALYZE executes the inner statement
+            Statement::Explain { statement, .. } => {
+                is_read_only(&DFStatement::Statement(statement.clone()))
+            }
+            _ => false,
+        },
+        DFStatement::Explain(explain) => is_read_only(&explain.statement),
+        DFStatement::CreateExternalTable(_) | DFStatement::CopyTo(_) => false,
+    }
+}
+
+/// Reject DDL/DML such as INSERT, UPDATE, DELETE, CREATE EXTERNAL TABLE and
+/// COPY unless writes have been enabled with `set_allow_writes`.
+pub(crate) fn ensure_read_only(cache: &ParquetCache, query: &str) -> Result<(), String> {
+    if cache.allow_writes() {
+        return Ok(());
+    }
+    check_read_only(query)
+}
+
+/// Reject any statement that writes, regardless of `set_allow_writes`.
+pub(crate) fn check_read_only(query: &str) -> Result<(), String> {
+    let statements = DFParser::parse_sql(query).map_err(|e| format!("SQL parse error: {}", e))?;
+    match statements.iter().find(|statement| !is_read_only(statement)) {
+        Some(statement) => Err(format!(
+            "Statement not allowed in read-only mode: {}",
+            statement
+        )),
+        None => Ok(()),
+    }
 }
 
 #[command]
+pub fn set_allow_writes(cache: tauri::State<'_, ParquetCache>, enabled: bool) {
+    cache.set_allow_writes(enabled);
+}
+
+#[command]
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_sql(
     cache: tauri::State<'_, ParquetCache>,
+    settings: tauri::State<'_, SettingsStore>,
+    notifier: tauri::State<'_, Notifier>,
     file_path: String,
     query: String,
+    session_id: Option<String>,
+    timeout_ms: Option<u64>,
+    max_rows: Option<usize>,
 ) -> Result<QueryResult, String> {
+    ensure_read_only(&cache, &query)?;
+
     let start = std::time::Instant::now();
 
-    let (batches, schema) =
-        crate::services::parquet::execute_sql_with_cache(&cache, &file_path, &query).await?;
+    // Workspaces are isolated from the shared per-file session
+    let session_key = session_id.unwrap_or(file_path);
+    let max_rows = max_rows.unwrap_or_else(|| settings.get().max_result_rows);
 
-    // Get column info from schema
-    let columns: Vec<QueryColumn> = schema
-        .fields()
-        .iter()
-        .map(|f| QueryColumn {
-            name: f.name().clone(),
-            data_type: f.data_type().to_string(),
+    let result: Result<QueryResult, String> = async {
+        let output = crate::services::parquet::execute_sql_with_cache(
+            &cache,
+            &session_key,
+            &query,
+            timeout_ms,
+            Some(max_rows),
+        )
+        .await?;
+
+        // Get column info from schema
+        let columns = schema_columns(&output.schema);
+        let rows = batches_to_objects(&output.batches, cache.display_options())?;
+
+        let duration = start.elapsed().as_millis();
+
+        cache.record_query(&session_key, &query);
+
+        Ok(QueryResult {
+            columns,
+            rows,
+            execution_time_ms: duration,
+            truncated: output.truncated,
+            total_rows_estimate: output.total_rows_estimate,
         })
-        .collect();
+    }
+    .await;
+
+    if let Err(e) = &result {
+        tracing::warn!(
+            "Query failed after {:?}: {}",
+            start.elapsed(),
+            logging::redact(e)
+        );
+    }
+    notifier.query_finished(
+        start.elapsed(),
+        result
+            .as_ref()
+            .map(|r| r.rows.len())
+            .map_err(String::as_str),
+    );
+    result
+}
+
+/// Run a query and emit its rows as `query-batch` events of at most
+/// `chunk_size` rows, followed by a `query-complete` event, instead of returning
+/// the whole result in one payload. `query_id` tags every event. Streaming
+/// stops with an error once `timeout_ms` (or the default) elapses.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn execute_sql_streaming(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    notifier: tauri::State<'_, Notifier>,
+    file_path: String,
+    query: String,
+    query_id: String,
+    chunk_size: Option<usize>,
+    session_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<QueryCompleteEvent, String> {
+    ensure_read_only(&cache, &query)?;
 
-    let buf = batches_to_json_bytes(&batches)?;
-    let rows: Result<Vec<serde_json::Map<String, serde_json::Value>>, _> =
-        serde_json::Deserializer::from_slice(&buf)
-            .into_iter::<serde_json::Map<String, serde_json::Value>>()
-            .collect();
-    let rows = rows.map_err(|e| format!("Failed to parse JSON results: {}", e))?;
+    let start = std::time::Instant::now();
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_STREAM_CHUNK_ROWS).max(1);
+    let session_key = session_id.unwrap_or(file_path);
 
-    let duration = start.elapsed().as_millis();
+    let result = stream_query(
+        &app,
+        &cache,
+        &session_key,
+        &query,
+        query_id,
+        chunk_size,
+        timeout_ms,
+        start,
+    )
+    .await;
+    if let Err(e) = &result {
+        tracing::warn!(
+            "Query failed after {:?}: {}",
+            start.elapsed(),
+            logging::redact(e)
+        );
+    }
+    notifier.query_finished(
+        start.elapsed(),
+        result
+            .as_ref()
+            .map(|c| c.total_rows)
+            .map_err(String::as_str),
+    );
+    result
+}
 
-    Ok(QueryResult {
-        columns,
-        rows,
-        execution_time_ms: duration,
+#[allow(clippy::too_many_arguments)]
+async fn stream_query(
+    app: &tauri::AppHandle,
+    cache: &ParquetCache,
+    session_key: &str,
+    query: &str,
+    query_id: String,
+    chunk_size: usize,
+    timeout_ms: Option<u64>,
+    start: std::time::Instant,
+) -> Result<QueryCompleteEvent, String> {
+    let display = cache.display_options();
+    // Dropping the stream on timeout cancels the query
+    let (columns, total_rows, batch_count) = with_timeout(timeout_ms, async {
+        let mut stream =
+            crate::services::parquet::execute_sql_stream(cache, session_key, query).await?;
+        let columns = schema_columns(&stream.schema());
+
+        let mut total_rows = 0;
+        let mut batch_count = 0;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.map_err(|e| format!("Failed to read results: {}", e))?;
+
+            let mut offset = 0;
+            while offset < batch.num_rows() {
+                let length = chunk_size.min(batch.num_rows() - offset);
+                let rows = batches_to_objects(&[batch.slice(offset, length)], display)?;
+                app.emit(
+                    "query-batch",
+                    QueryBatchEvent {
+                        query_id: query_id.clone(),
+                        batch_index: batch_count,
+                        rows,
+                    },
+                )
+                .map_err(|e| e.to_string())?;
+
+                offset += length;
+                total_rows += length;
+                batch_count += 1;
+            }
+        }
+        Ok((columns, total_rows, batch_count))
     })
+    .await?;
+
+    cache.record_query(session_key, query);
+
+    let complete = QueryCompleteEvent {
+        query_id,
+        columns,
+        total_rows,
+        batch_count,
+        execution_time_ms: start.elapsed().as_millis(),
+    };
+    app.emit("query-complete", complete.clone())
+        .map_err(|e| e.to_string())?;
+
+    Ok(complete)
+}
+
+#[command]
+pub async fn explain_sql(
+    cache: tauri::State<'_, ParquetCache>,
+    file_path: String,
+    query: String,
+    analyze: Option<bool>,
+    session_id: Option<String>,
+) -> Result<QueryPlan, String> {
+    ensure_read_only(&cache, &query)?;
+
+    let session_key = session_id.unwrap_or(file_path);
+    crate::services::parquet::explain_sql(&cache, &session_key, &query, analyze.unwrap_or(false))
+        .await
+}
+
+#[command]
+pub async fn validate_sql(
+    cache: tauri::State<'_, ParquetCache>,
+    file_path: String,
+    query: String,
+    session_id: Option<String>,
+) -> Result<SqlValidation, String> {
+    let session_key = session_id.unwrap_or(file_path);
+    crate::services::parquet::validate_sql(&cache, &session_key, &query).await
 }
@@ -0,0 +1,34 @@
+use crate::commands::data_dir;
+use crate::models::RecentFile;
+use crate::services::parquet::ParquetCache;
+use crate::services::recent_files;
+
+/// Record an opened file, with its row count when the footer can be read.
+#[tauri::command]
+pub async fn add_recent_file(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<RecentFile, String> {
+    let row_count = cache.get_or_create_metadata(&path).ok().map(|m| m.num_rows);
+    recent_files::add_recent_file(&data_dir(&app)?, &path, row_count)
+}
+
+#[tauri::command]
+pub async fn list_recent_files(app: tauri::AppHandle) -> Result<Vec<RecentFile>, String> {
+    recent_files::list_recent_files(&data_dir(&app)?)
+}
+
+#[tauri::command]
+pub async fn pin_file(
+    app: tauri::AppHandle,
+    path: String,
+    pinned: Option<bool>,
+) -> Result<RecentFile, String> {
+    recent_files::pin_file(&data_dir(&app)?, &path, pinned.unwrap_or(true))
+}
+
+#[tauri::command]
+pub async fn remove_recent(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    recent_files::remove_recent(&data_dir(&app)?, &path)
+}
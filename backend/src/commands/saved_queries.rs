@@ -0,0 +1,43 @@
+use crate::commands::data_dir;
+use crate::models::SavedQuery;
+use crate::services::saved_queries;
+
+#[tauri::command]
+pub async fn save_query(
+    app: tauri::AppHandle,
+    name: String,
+    query: String,
+    file_path: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<SavedQuery, String> {
+    saved_queries::save_query(
+        &data_dir(&app)?,
+        &name,
+        &query,
+        file_path,
+        tags.unwrap_or_default(),
+    )
+}
+
+#[tauri::command]
+pub async fn list_saved_queries(
+    app: tauri::AppHandle,
+    file_path: Option<String>,
+    tag: Option<String>,
+) -> Result<Vec<SavedQuery>, String> {
+    saved_queries::list_queries(&data_dir(&app)?, file_path.as_deref(), tag.as_deref())
+}
+
+#[tauri::command]
+pub async fn rename_saved_query(
+    app: tauri::AppHandle,
+    id: u64,
+    name: String,
+) -> Result<SavedQuery, String> {
+    saved_queries::rename_query(&data_dir(&app)?, id, &name)
+}
+
+#[tauri::command]
+pub async fn delete_saved_query(app: tauri::AppHandle, id: u64) -> Result<(), String> {
+    saved_queries::delete_query(&data_dir(&app)?, id)
+}
@@ -0,0 +1,94 @@
+use crate::models::{CompletionInfo, SessionSpec, SessionView};
+use crate::services::parquet::ParquetCache;
+use crate::services::session;
+
+#[tauri::command]
+pub async fn export_session(
+    cache: tauri::State<'_, ParquetCache>,
+    file_path: String,
+    export_path: String,
+    format: String,
+    max_queries: Option<usize>,
+) -> Result<String, String> {
+    cache.check_path(&export_path)?;
+    session::export_session(
+        &cache,
+        &file_path,
+        &export_path,
+        &format,
+        max_queries.unwrap_or(usize::MAX),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn import_session(
+    cache: tauri::State<'_, ParquetCache>,
+    import_path: String,
+) -> Result<SessionSpec, String> {
+    cache.check_path(&import_path)?;
+    session::import_session(&cache, &import_path).await
+}
+
+#[tauri::command]
+pub async fn register_table(
+    cache: tauri::State<'_, ParquetCache>,
+    session_id: String,
+    alias: String,
+    path: String,
+) -> Result<(), String> {
+    cache.check_path(&path)?;
+    session::register_table(&cache, &session_id, &alias, &path).await
+}
+
+#[tauri::command]
+pub async fn unregister_table(
+    cache: tauri::State<'_, ParquetCache>,
+    session_id: String,
+    alias: String,
+) -> Result<(), String> {
+    session::unregister_table(&cache, &session_id, &alias).await
+}
+
+#[tauri::command]
+pub async fn rename_table(
+    cache: tauri::State<'_, ParquetCache>,
+    session_id: String,
+    alias: String,
+    new_alias: String,
+) -> Result<(), String> {
+    session::rename_table(&cache, &session_id, &alias, &new_alias).await
+}
+
+#[tauri::command]
+pub async fn create_session(
+    cache: tauri::State<'_, ParquetCache>,
+    path: String,
+) -> Result<String, String> {
+    cache.check_path(&path)?;
+    cache.create_workspace(&path).await
+}
+
+#[tauri::command]
+pub async fn destroy_session(
+    cache: tauri::State<'_, ParquetCache>,
+    session_id: String,
+) -> Result<(), String> {
+    cache.destroy_workspace(&session_id)
+}
+
+#[tauri::command]
+pub async fn list_views(
+    cache: tauri::State<'_, ParquetCache>,
+    session_id: String,
+) -> Result<Vec<SessionView>, String> {
+    session::list_views(&cache, &session_id).await
+}
+
+#[tauri::command]
+pub async fn get_sql_completion_info(
+    cache: tauri::State<'_, ParquetCache>,
+    session_id: String,
+) -> Result<CompletionInfo, String> {
+    session::get_sql_completion_info(&cache, &session_id).await
+}
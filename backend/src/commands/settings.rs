@@ -0,0 +1,28 @@
+use tauri::Emitter;
+
+use crate::commands::data_dir;
+use crate::models::{Settings, SettingsUpdate};
+use crate::services::parquet::ParquetCache;
+use crate::services::scope::PathScope;
+use crate::services::settings::{self, SettingsStore};
+
+#[tauri::command]
+pub async fn get_settings(settings: tauri::State<'_, SettingsStore>) -> Result<Settings, String> {
+    Ok(settings.get())
+}
+
+/// Change the given settings and save them. Every window is sent a
+/// `settings-changed` event with the new settings.
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    settings: tauri::State<'_, SettingsStore>,
+    update: SettingsUpdate,
+) -> Result<Settings, String> {
+    let updated = settings.update(&data_dir(&app)?, update)?;
+    cache.set_path_scope(PathScope::new(&updated.allowed_directories));
+    settings::apply_display_settings(&cache, &updated)?;
+    let _ = app.emit("settings-changed", updated.clone());
+    Ok(updated)
+}
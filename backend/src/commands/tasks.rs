@@ -0,0 +1,12 @@
+use crate::models::TaskInfo;
+use crate::services::tasks::TaskManager;
+
+#[tauri::command]
+pub async fn list_tasks(tasks: tauri::State<'_, TaskManager>) -> Result<Vec<TaskInfo>, String> {
+    tasks.list()
+}
+
+#[tauri::command]
+pub async fn cancel_task(tasks: tauri::State<'_, TaskManager>, id: String) -> Result<(), String> {
+    tasks.cancel(&id)
+}
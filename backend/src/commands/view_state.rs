@@ -0,0 +1,21 @@
+use crate::commands::data_dir;
+use crate::models::ViewState;
+use crate::services::view_state;
+
+#[tauri::command]
+pub async fn get_view_state(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<Option<ViewState>, String> {
+    view_state::get_view_state(&data_dir(&app)?, &path)
+}
+
+#[tauri::command]
+pub async fn save_view_state(app: tauri::AppHandle, state: ViewState) -> Result<ViewState, String> {
+    view_state::save_view_state(&data_dir(&app)?, state)
+}
+
+#[tauri::command]
+pub async fn clear_view_state(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    view_state::clear_view_state(&data_dir(&app)?, &path)
+}
@@ -0,0 +1,33 @@
+use crate::commands::data_dir;
+use crate::models::{Workspace, WorkspaceInfo};
+use crate::services::parquet::ParquetCache;
+use crate::services::workspace::{self, WorkspaceSessions};
+
+#[tauri::command]
+pub async fn save_workspace(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    workspace: Workspace,
+) -> Result<Workspace, String> {
+    workspace::save_workspace(&cache, &data_dir(&app)?, workspace).await
+}
+
+#[tauri::command]
+pub async fn load_workspace(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, ParquetCache>,
+    sessions: tauri::State<'_, WorkspaceSessions>,
+    name: String,
+) -> Result<Workspace, String> {
+    workspace::load_workspace(&cache, &sessions, &data_dir(&app)?, &name).await
+}
+
+#[tauri::command]
+pub async fn list_workspaces(app: tauri::AppHandle) -> Result<Vec<WorkspaceInfo>, String> {
+    workspace::list_workspaces(&data_dir(&app)?)
+}
+
+#[tauri::command]
+pub async fn delete_workspace(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    workspace::delete_workspace(&data_dir(&app)?, &name)
+}
@@ -3,38 +3,375 @@ pub mod models;
 pub mod services;
 pub mod utils;
 
+use models::OpenFileRequest;
+use services::disk_cache::DiskCache;
+use services::ingest::IngestWatcher;
+use services::jobs::JobScheduler;
+use services::launch::{self, LaunchQueue};
+use services::notifications::Notifier;
 use services::parquet::ParquetCache;
-use tauri::{DragDropEvent, Emitter};
+use services::scope::PathScope;
+use services::settings::SettingsStore;
+use services::tasks::TaskManager;
+use services::watcher::{DirectoryWatcher, FileWatcher};
+use services::workspace::WorkspaceSessions;
+use tauri::{AppHandle, DragDropEvent, Emitter, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_notification::NotificationExt;
+
+/// Queue the `parqsee://` links among `urls` to be opened. Any web page can
+/// open such a link, so its path must be in scope and its query may only read,
+/// even when writes are enabled.
+fn open_deep_links(app: &AppHandle, urls: Vec<url::Url>) {
+    let queue = app.state::<LaunchQueue>();
+    let cache = app.state::<ParquetCache>();
+    for url in urls {
+        let request = launch::parse_deep_link(&url).and_then(|request| {
+            cache.check_path(&request.path)?;
+            if let Some(query) = &request.query {
+                commands::query::check_read_only(query)?;
+            }
+            Ok(request)
+        });
+        match request {
+            Ok(request) => queue.dispatch(request),
+            Err(e) => tracing::warn!("Ignoring link {}: {}", url, e),
+        }
+    }
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    let context = tauri::generate_context!();
+
+    // Cap blocking threads so file reads can't saturate every core. The
+    // runtime is built before the app, so the settings are read directly from
+    // where the app keeps them
+    let saved = dirs::data_dir()
+        .map(|dir| services::settings::saved_settings(&dir.join(&context.config().identifier)))
+        .unwrap_or_default();
+    if let Some(threads) = services::parquet::init_blocking_threads(saved.blocking_threads) {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .max_blocking_threads(threads)
+            .build()
+            .expect("failed to build async runtime");
+        tauri::async_runtime::set(runtime.handle().clone());
+        // The app runs until exit, so the runtime does too
+        std::mem::forget(runtime);
+    }
+
+    let builder = tauri::Builder::default();
+
+    // A second launch (e.g. double-clicking another file) hands its arguments
+    // to the running app instead of opening another window
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+        let queue = app.state::<LaunchQueue>();
+        for path in launch::paths_from_args(&args, std::path::Path::new(&cwd)) {
+            queue.dispatch(OpenFileRequest { path, query: None });
+        }
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+    }));
+
+    builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_deep_link::init())
         .manage(ParquetCache::new())
+        .manage(TaskManager::new())
+        .manage(FileWatcher::new())
+        .manage(DirectoryWatcher::new())
+        .manage(IngestWatcher::new())
+        .manage(SettingsStore::new())
+        .manage(LaunchQueue::new())
+        .manage(Notifier::new())
+        .manage(JobScheduler::new())
+        .manage(WorkspaceSessions::new())
+        .setup(|app| {
+            // Logging comes first, so nothing logged while setting up is lost
+            let data_dir = app.path().app_data_dir();
+            if let Ok(dir) = &data_dir {
+                if let Err(e) = services::logging::init(dir) {
+                    eprintln!("Logging to file unavailable: {}", e);
+                }
+            }
+
+            // Forward task lifecycle events to the frontend
+            let handle = app.handle().clone();
+            app.state::<TaskManager>().set_listener(move |event, info| {
+                let _ = handle.emit(event, info);
+                if event == services::tasks::TASK_FINISHED {
+                    tracing::info!(
+                        "Task {} ({}) {}: {}",
+                        info.id,
+                        info.kind,
+                        info.state,
+                        info.error
+                            .as_deref()
+                            .map(services::logging::redact)
+                            .unwrap_or_else(|| info.label.clone())
+                    );
+                    handle.state::<Notifier>().task_finished(info);
+                }
+            });
+
+            // Finished long operations are only worth an OS notification when
+            // the user has switched away from the app
+            let handle = app.handle().clone();
+            app.state::<Notifier>().set_listener(move |notification| {
+                let focused = handle
+                    .get_webview_window("main")
+                    .and_then(|window| window.is_focused().ok())
+                    .unwrap_or(false);
+                if focused {
+                    return;
+                }
+                if let Err(e) = handle
+                    .notification()
+                    .builder()
+                    .title(&notification.title)
+                    .body(&notification.body)
+                    .show()
+                {
+                    tracing::warn!("Failed to show notification: {}", e);
+                }
+            });
+
+            // Drop stale sessions as soon as an open file is rewritten
+            let handle = app.handle().clone();
+            app.state::<FileWatcher>().set_listener(move |change| {
+                handle.state::<ParquetCache>().evict(&change.path);
+                let _ = handle.emit("file-changed", change);
+            });
+
+            let handle = app.handle().clone();
+            app.state::<LaunchQueue>().set_listener(move |request| {
+                let _ = handle.emit("open-file", request);
+            });
+
+            // Files named on the command line wait for the frontend to collect them
+            if let Ok(cwd) = std::env::current_dir() {
+                let args: Vec<String> = std::env::args().collect();
+                let queue = app.state::<LaunchQueue>();
+                for path in launch::paths_from_args(&args, &cwd) {
+                    queue.dispatch(OpenFileRequest { path, query: None });
+                }
+            }
+
+            let handle = app.handle().clone();
+            app.state::<DirectoryWatcher>().set_listener(move |change| {
+                let _ = handle.emit("directory-changed", change);
+            });
+
+            // New files in ingest folders are converted in the background
+            let handle = app.handle().clone();
+            app.state::<IngestWatcher>()
+                .set_listener(move |folder, source| {
+                    let handle = handle.clone();
+                    let folder = folder.clone();
+                    let source = source.to_path_buf();
+                    tauri::async_runtime::spawn(async move {
+                        let tasks = handle.state::<TaskManager>();
+                        let result = services::ingest::ingest_file(&tasks, &folder, &source).await;
+                        handle
+                            .state::<IngestWatcher>()
+                            .finished(&source, result.clone());
+                        let _ = handle.emit("ingest-finished", result);
+                    });
+                });
+
+            match data_dir {
+                Ok(dir) => {
+                    app.state::<SettingsStore>().load(&dir);
+                    let saved = app.state::<SettingsStore>().get();
+                    let cache = app.state::<ParquetCache>();
+                    cache.set_path_scope(PathScope::new(&saved.allowed_directories));
+                    if let Err(e) = services::settings::apply_display_settings(&cache, &saved) {
+                        tracing::warn!("Display settings not applied: {}", e);
+                    }
+                    // Reopening large files after a restart reuses their footers and stats
+                    match DiskCache::open(&dir) {
+                        Ok(disk) => cache.attach_disk_cache(disk),
+                        Err(e) => tracing::warn!("Disk cache unavailable: {}", e),
+                    }
+                }
+                Err(e) => tracing::error!("App data directory unavailable: {}", e),
+            }
+
+            // parqsee:// links, whether they launched the app or arrived later.
+            // Handled once the path scope is loaded, since links are checked against it
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                tracing::warn!("Failed to register link scheme: {}", e);
+            }
+            let handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                open_deep_links(&handle, event.urls());
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                open_deep_links(app.handle(), urls);
+            }
+
+            // Repeating jobs only run while the app is open
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(services::jobs::SCHEDULE_TICK).await;
+                    commands::jobs::run_due_jobs(&handle);
+                }
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             commands::file::open_parquet_file,
             commands::file::get_file_info,
             commands::file::check_file_exists,
+            commands::file::take_pending_open_files,
             commands::file::list_directory,
+            commands::file::scan_directory,
+            commands::file::prefetch_directory_metadata,
+            commands::file::summarize_directory,
+            commands::file::check_schema_drift,
+            commands::file::watch_directory,
+            commands::file::unwatch_directory,
+            commands::inspect::get_row_group_info,
+            commands::inspect::column_sizes,
+            commands::inspect::get_file_metadata,
+            commands::inspect::get_extended_metadata,
+            commands::inspect::get_index_info,
+            commands::inspect::bloom_check,
+            commands::inspect::diagnose_parquet,
+            commands::inspect::diff_schemas,
+            commands::inspect::generate_ddl,
+            commands::inspect::export_schema,
+            commands::inspect::verify_file,
+            commands::inspect::hash_file,
             commands::data::read_parquet_data,
+            commands::data::read_parquet_data_arrow,
+            commands::data::read_parquet_data_lenient,
+            commands::data::get_data_window,
+            commands::data::read_parquet_page,
+            commands::data::read_row_group,
             commands::data::count_parquet_data,
+            commands::data::validate_filter,
+            commands::data::estimate_parquet_count,
+            commands::data::get_pruning_report,
+            commands::data::describe_parquet,
+            commands::data::column_value_counts,
+            commands::data::column_histogram,
+            commands::data::time_series,
+            commands::data::null_profile,
+            commands::data::get_column_stats,
+            commands::data::compute_column_stats,
+            commands::data::set_background_stats,
+            commands::data::search_rows,
+            commands::data::regex_search,
+            commands::data::find_row_positions,
+            commands::data::sample_rows,
+            commands::data::detect_sensitive_columns,
+            commands::data::distinct_values,
+            commands::data::pivot,
+            commands::data::diff_data,
             commands::data::export_data,
+            commands::data::export_rows,
             commands::data::evict_cache,
-            commands::query::execute_sql
+            commands::data::clear_disk_cache,
+            commands::diagnostics::collect_diagnostics,
+            commands::data::get_cache_stats,
+            commands::data::set_cache_limits,
+            commands::data::get_engine_config,
+            commands::data::set_engine_config,
+            commands::data::get_display_settings,
+            commands::data::set_display_settings,
+            commands::data::get_read_mode,
+            commands::data::set_read_mode,
+            commands::data::get_cell_value,
+            commands::data::get_cell_bytes,
+            commands::data::export_to_clipboard,
+            commands::convert::rewrite_parquet,
+            commands::convert::split_parquet,
+            commands::convert::create_parquet_from_sql,
+            commands::convert::extract_columns,
+            commands::convert::sort_parquet,
+            commands::convert::partition_parquet,
+            commands::convert::start_ingest,
+            commands::convert::stop_ingest,
+            commands::convert::list_ingest_folders,
+            commands::convert::list_ingest_results,
+            commands::query::execute_sql,
+            commands::query::execute_sql_streaming,
+            commands::query::explain_sql,
+            commands::query::set_allow_writes,
+            commands::query::validate_sql,
+            commands::saved_queries::save_query,
+            commands::saved_queries::list_saved_queries,
+            commands::saved_queries::rename_saved_query,
+            commands::saved_queries::delete_saved_query,
+            commands::recent_files::add_recent_file,
+            commands::recent_files::list_recent_files,
+            commands::recent_files::pin_file,
+            commands::recent_files::remove_recent,
+            commands::workspace::save_workspace,
+            commands::workspace::load_workspace,
+            commands::workspace::list_workspaces,
+            commands::workspace::delete_workspace,
+            commands::jobs::save_job,
+            commands::jobs::list_jobs,
+            commands::jobs::delete_job,
+            commands::jobs::run_job,
+            commands::jobs::list_job_runs,
+            commands::view_state::get_view_state,
+            commands::view_state::save_view_state,
+            commands::view_state::clear_view_state,
+            commands::settings::get_settings,
+            commands::settings::update_settings,
+            commands::session::export_session,
+            commands::session::import_session,
+            commands::session::register_table,
+            commands::session::unregister_table,
+            commands::session::rename_table,
+            commands::session::create_session,
+            commands::session::destroy_session,
+            commands::session::list_views,
+            commands::session::get_sql_completion_info,
+            commands::tasks::list_tasks,
+            commands::tasks::cancel_task
         ])
         .on_window_event(|window, event| {
             match event {
                 tauri::WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
-                    println!("Files dropped: {:?}", paths);
+                    tracing::debug!("Files dropped: {:?}", paths);
                     // Send event to frontend
                     window.emit("file-drop", paths).unwrap();
+
+                    // Expanding dropped directories walks them, so keep it off the event loop
+                    let window = window.clone();
+                    let paths = paths.clone();
+                    tauri::async_runtime::spawn_blocking(move || {
+                        let _ = window.emit("files-dropped", services::drop::classify_drop(&paths));
+                    });
                 }
                 _ => {}
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // macOS delivers "Open With" and double-clicked files as events
+            // rather than arguments
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                let queue = _app.state::<LaunchQueue>();
+                for path in urls.iter().filter_map(launch::path_from_url) {
+                    queue.dispatch(OpenFileRequest { path, query: None });
+                }
+            }
+        });
 }
@@ -1,10 +1,29 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParquetMetadata {
     pub num_rows: i64,
     pub num_columns: usize,
     pub columns: Vec<ColumnInfo>,
+    /// Full schema tree, one node per top-level field.
+    pub schema: Vec<SchemaNode>,
+}
+
+/// A field of the Parquet schema with its nested fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaNode {
+    pub name: String,
+    /// Dotted path from the root, e.g. `address.geo.lat`.
+    pub path: String,
+    /// `REQUIRED`, `OPTIONAL` or `REPEATED`.
+    pub repetition: String,
+    /// `None` for groups.
+    pub physical_type: Option<String>,
+    pub logical_type: Option<String>,
+    pub max_definition_level: i16,
+    pub max_repetition_level: i16,
+    pub children: Vec<SchemaNode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +32,84 @@ pub struct ColumnInfo {
     pub column_type: String,
     pub logical_type: Option<String>,
     pub physical_type: String,
+    /// `REQUIRED`, `OPTIONAL` or `REPEATED`.
+    pub repetition: String,
+    /// Whether the column may hold nulls (`OPTIONAL`).
+    pub nullable: bool,
+    /// Distinct encodings used by the column's chunks across all row groups.
+    pub encodings: Vec<String>,
+    /// Distinct compression codecs of the column's chunks; normally one.
+    pub compression: Vec<String>,
+    pub statistics: Option<ColumnStatistics>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortSpec {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+    /// `None` keeps the engine default (nulls last ascending, first descending).
+    pub nulls_first: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterOperator {
+    Eq,
+    NotEq,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+    Like,
+    NotLike,
+    In,
+    NotIn,
+    IsNull,
+    IsNotNull,
+}
+
+/// Typed row filter sent by the frontend and compiled to SQL by the backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterExpr {
+    Condition {
+        column: String,
+        operator: FilterOperator,
+        #[serde(default)]
+        value: Option<serde_json::Value>,
+    },
+    And {
+        filters: Vec<FilterExpr>,
+    },
+    Or {
+        filters: Vec<FilterExpr>,
+    },
+    Not {
+        filter: Box<FilterExpr>,
+    },
+}
+
+/// Rows picked out of a view, e.g. those highlighted in the grid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SelectedRows {
+    /// Positions in the view, counted from 0.
+    Indices { indices: Vec<usize> },
+    /// Values of `columns` identifying each row, one entry per row.
+    Keys {
+        columns: Vec<String>,
+        values: Vec<Vec<serde_json::Value>>,
+    },
+}
+
+/// A filter as sent to `validate_filter`: a filter tree, or the text of a
+/// SQL `WHERE` condition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FilterInput {
+    Expr(FilterExpr),
+    Text(String),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,3 +128,1118 @@ pub struct FileEntry {
     pub size: Option<u64>,
     pub children: Option<Vec<FileEntry>>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTable {
+    pub name: String,
+    pub path: String,
+    pub format: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionView {
+    pub name: String,
+    pub definition: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSetting {
+    pub key: String,
+    pub value: String,
+}
+
+/// Reproducible description of a SQL session: enough to rebuild the same
+/// tables, views and settings and replay the queries that were run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSpec {
+    pub version: u32,
+    pub tables: Vec<SessionTable>,
+    pub views: Vec<SessionView>,
+    pub settings: Vec<SessionSetting>,
+    pub queries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    Light,
+    Dark,
+    #[default]
+    System,
+}
+
+/// User preferences kept by the backend. Fields missing from a stored file
+/// (e.g. written by an older version) take their defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Rows per grid page.
+    pub default_page_size: usize,
+    /// Timezone UTC-adjusted timestamps are shown in: `"utc"`, `"local"` or an
+    /// IANA timezone name.
+    pub display_timezone: String,
+    /// chrono `strftime` pattern for the date and time of timestamps, e.g.
+    /// `"%Y-%m-%d %H:%M:%S"`; fractional seconds and offsets are appended.
+    pub timestamp_format: String,
+    /// Rows a SQL query collects when it doesn't set its own cap.
+    pub max_result_rows: usize,
+    pub theme: Theme,
+    pub telemetry_opt_in: bool,
+    /// Directories files may be opened from and written to; empty allows any.
+    pub allowed_directories: Vec<String>,
+    /// Cap on the threads blocking file reads run on, or `None` for tokio's
+    /// default. Takes effect at the next start.
+    pub blocking_threads: Option<usize>,
+}
+
+/// Changes to `Settings`; omitted fields keep their current value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SettingsUpdate {
+    pub default_page_size: Option<usize>,
+    pub display_timezone: Option<String>,
+    pub timestamp_format: Option<String>,
+    pub max_result_rows: Option<usize>,
+    pub theme: Option<Theme>,
+    pub telemetry_opt_in: Option<bool>,
+    pub allowed_directories: Option<Vec<String>>,
+    /// 0 restores tokio's default.
+    pub blocking_threads: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DroppedKind {
+    ParquetFile,
+    /// A directory with Parquet files below it.
+    Dataset,
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedItem {
+    pub path: String,
+    pub kind: DroppedKind,
+    /// Parquet files the item stands for: the file itself, or every one below a dataset.
+    pub files: Vec<String>,
+    /// Why an unsupported item can't be opened.
+    pub reason: Option<String>,
+}
+
+/// What the app should do with a drop as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DropHandling {
+    OpenFile,
+    OpenDataset,
+    /// Several files, or a mix of files and datasets; open each of `files`.
+    OpenFiles,
+    /// Nothing dropped can be opened.
+    Unsupported,
+}
+
+/// Payload of the `files-dropped` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesDropped {
+    pub handling: DropHandling,
+    pub items: Vec<DroppedItem>,
+    /// Parquet files of every supported item, in drop order.
+    pub files: Vec<String>,
+}
+
+/// A file the app was asked to open from outside, e.g. a launch argument,
+/// the OS "Open With" menu or a `parqsee://` link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFileRequest {
+    pub path: String,
+    /// Read-only SQL to run once the file is open, from a link's `query` parameter.
+    #[serde(default)]
+    pub query: Option<String>,
+}
+
+/// How a file's grid was laid out when it was last viewed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewState {
+    pub path: String,
+    #[serde(default)]
+    pub column_order: Vec<String>,
+    #[serde(default)]
+    pub hidden_columns: Vec<String>,
+    /// Column widths in pixels, by column name.
+    #[serde(default)]
+    pub column_widths: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub filter: Option<FilterExpr>,
+    #[serde(default)]
+    pub sort: Vec<SortSpec>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+/// A file open in a workspace, with the filter and sort applied to its grid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceFile {
+    pub path: String,
+    #[serde(default)]
+    pub filter: Option<FilterExpr>,
+    #[serde(default)]
+    pub sort: Vec<SortSpec>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSqlTab {
+    pub title: String,
+    pub query: String,
+    /// SQL session the tab runs in: a session id or a file path. Saving
+    /// captures it in `session`; loading opens a new session and puts its id here.
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub session: Option<SessionSpec>,
+    /// Why the session couldn't be captured on save or rebuilt on load.
+    #[serde(default)]
+    pub restore_error: Option<String>,
+}
+
+/// Everything needed to resume an investigation: open files with their
+/// filters and sorts, and the SQL tabs with their sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    #[serde(default)]
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub files: Vec<WorkspaceFile>,
+    #[serde(default)]
+    pub active_file: Option<String>,
+    #[serde(default)]
+    pub sql_tabs: Vec<WorkspaceSqlTab>,
+    #[serde(default)]
+    pub saved_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceInfo {
+    pub name: String,
+    pub saved_at: String,
+    pub file_count: usize,
+    pub sql_tab_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnStatistics {
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub null_count: Option<u64>,
+    pub distinct_count: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnChunkInfo {
+    pub column: String,
+    pub physical_type: String,
+    pub compression: String,
+    pub encodings: Vec<String>,
+    pub num_values: i64,
+    pub compressed_size: i64,
+    pub uncompressed_size: i64,
+    pub statistics: Option<ColumnStatistics>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowGroupInfo {
+    pub index: usize,
+    pub num_rows: i64,
+    pub compressed_size: i64,
+    pub uncompressed_size: i64,
+    pub columns: Vec<ColumnChunkInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnSize {
+    /// Dotted path of the leaf column.
+    pub column: String,
+    pub compressed_size: i64,
+    pub uncompressed_size: i64,
+    /// Share of the file's total compressed column data, 0-100.
+    pub compressed_percent: f64,
+    /// Share of the file's total uncompressed column data, 0-100.
+    pub uncompressed_percent: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnSizes {
+    pub total_compressed_size: i64,
+    pub total_uncompressed_size: i64,
+    /// Largest compressed size first.
+    pub columns: Vec<ColumnSize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileMetadataInfo {
+    pub created_by: Option<String>,
+    pub format_version: i32,
+    pub num_rows: i64,
+    pub num_row_groups: usize,
+    pub key_value_metadata: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnIndexInfo {
+    pub row_group: usize,
+    pub column: String,
+    pub has_column_index: bool,
+    pub column_index_length: Option<i32>,
+    pub has_offset_index: bool,
+    pub offset_index_length: Option<i32>,
+    pub has_bloom_filter: bool,
+    pub bloom_filter_length: Option<i32>,
+}
+
+/// What the footer says about a value in one row group.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BloomVerdict {
+    /// Min/max statistics, or the column's type, rule the value out.
+    OutOfRange,
+    /// The bloom filter rules the value out.
+    Absent,
+    /// The bloom filter says the value might be present.
+    MaybePresent,
+    /// No bloom filter, and statistics don't rule the value out.
+    NoFilter,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowGroupMembership {
+    pub index: usize,
+    pub num_rows: i64,
+    pub verdict: BloomVerdict,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BloomCheck {
+    /// Dotted path of the leaf column checked.
+    pub column: String,
+    pub might_exist: bool,
+    pub has_bloom_filters: bool,
+    /// Row groups that may hold the value and would have to be read.
+    pub candidate_row_groups: Vec<usize>,
+    pub row_groups: Vec<RowGroupMembership>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Percentile {
+    pub quantile: f64,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub data_type: String,
+    pub count: i64,
+    pub null_count: i64,
+    pub distinct_count: Option<i64>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub mean: Option<f64>,
+    pub stddev: Option<f64>,
+    pub percentiles: Vec<Percentile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DataProfile {
+    pub row_count: i64,
+    pub columns: Vec<ColumnProfile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValueCount {
+    pub value: serde_json::Value,
+    pub count: i64,
+    pub percentage: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValueCounts {
+    pub column: String,
+    pub total: usize,
+    pub values: Vec<ValueCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Histogram {
+    pub column: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub null_count: i64,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeSeriesPoint {
+    pub bucket: String,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeSeries {
+    pub time_column: String,
+    pub interval: String,
+    pub aggregation: String,
+    pub points: Vec<TimeSeriesPoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColumnNullInfo {
+    pub name: String,
+    pub null_count: i64,
+    pub null_ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSummary {
+    pub name: String,
+    pub data_type: String,
+    pub null_count: i64,
+    /// HyperLogLog estimate; `None` for nested columns.
+    pub approx_distinct: Option<i64>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnSummaries {
+    pub row_count: i64,
+    pub columns: Vec<ColumnSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NullProfile {
+    pub row_count: i64,
+    pub columns: Vec<ColumnNullInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DataPage {
+    pub rows: Vec<serde_json::Value>,
+    /// Opaque token for the next page; `None` once the last page was returned.
+    pub next_cursor: Option<String>,
+}
+
+/// A page of rows with the counts shown beside it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DataWindow {
+    pub rows: Vec<serde_json::Value>,
+    pub total_rows: usize,
+    /// Rows matching the filter; equal to `total_rows` without one.
+    pub filtered_rows: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub row: serde_json::Value,
+    pub matched_columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResults {
+    pub total: usize,
+    pub matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PivotTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QueryPlan {
+    pub logical_plan: String,
+    pub physical_plan: String,
+    /// Physical plan annotated with runtime metrics, when the query was analyzed.
+    pub analyzed_plan: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompletionColumn {
+    pub name: String,
+    pub data_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompletionTable {
+    pub name: String,
+    pub columns: Vec<CompletionColumn>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompletionInfo {
+    pub tables: Vec<CompletionTable>,
+    pub scalar_functions: Vec<String>,
+    pub aggregate_functions: Vec<String>,
+    pub window_functions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqlIssue {
+    pub message: String,
+    /// 1-based position of the offending token, when the parser reports one.
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqlValidation {
+    pub valid: bool,
+    pub issues: Vec<SqlIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub id: u64,
+    pub name: String,
+    pub query: String,
+    /// File the query was written against, if any.
+    pub file_path: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// What a job does: run `query` against `source` (table `t`) and write the
+/// result to `destination` as `format`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobSpec {
+    pub name: String,
+    pub source: String,
+    /// Without a query the whole source is written.
+    pub query: Option<String>,
+    pub destination: String,
+    /// "csv", "json", "jsonl" or "parquet".
+    pub format: String,
+    /// Seconds between runs while the app is open; `None` runs only on demand.
+    pub interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub spec: JobSpec,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobTrigger {
+    Manual,
+    Schedule,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    pub job_id: u64,
+    pub job_name: String,
+    pub trigger: JobTrigger,
+    pub started_at: String,
+    pub finished_at: String,
+    /// Rows written, `None` when the run failed.
+    pub rows: Option<i64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub name: String,
+    #[serde(default)]
+    pub pinned: bool,
+    pub last_opened: String,
+    /// Size and row count as of the last open.
+    pub size: Option<u64>,
+    pub row_count: Option<i64>,
+    /// Whether the file still exists; checked when listing.
+    #[serde(default)]
+    pub exists: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowGroupDiagnosis {
+    pub index: usize,
+    pub num_rows: i64,
+    /// First row of the group within the file.
+    pub first_row: i64,
+    pub readable: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParquetDiagnosis {
+    pub file_size: u64,
+    pub header_magic_ok: bool,
+    pub footer_magic_ok: bool,
+    pub footer_length: Option<u32>,
+    pub metadata_ok: bool,
+    pub metadata_error: Option<String>,
+    pub row_groups: Vec<RowGroupDiagnosis>,
+    /// Rows in row groups that could be read completely.
+    pub salvageable_rows: i64,
+    pub total_rows: i64,
+    /// Human-readable summary of everything found wrong, empty for a healthy file.
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedRange {
+    pub row_group: usize,
+    pub first_row: i64,
+    pub num_rows: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowGroupPage {
+    pub row_group: usize,
+    /// Position in the file of the row group's first row.
+    pub first_row: i64,
+    pub num_rows: i64,
+    pub rows: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LenientData {
+    pub rows: Vec<serde_json::Value>,
+    /// Row groups left out because they could not be decoded.
+    pub skipped: Vec<SkippedRange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskInfo {
+    pub id: String,
+    /// Kind of work, e.g. "export" or "profile".
+    pub kind: String,
+    pub label: String,
+    /// One of "running", "completed", "failed" or "cancelled".
+    pub state: String,
+    /// Fraction done in 0.0..=1.0, when the work can tell.
+    pub progress: Option<f64>,
+    pub message: Option<String>,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntryStats {
+    pub path: String,
+    pub has_session: bool,
+    pub has_metadata: bool,
+    /// Estimated memory held for this file, in bytes.
+    pub approx_bytes: usize,
+    pub hits: u64,
+    pub last_accessed: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Cached files, most recently used first.
+    pub entries: Vec<CacheEntryStats>,
+    pub approx_total_bytes: usize,
+    /// SQL workspaces are managed explicitly and never evicted.
+    pub workspace_count: usize,
+    pub max_entries: usize,
+    pub memory_budget: usize,
+    pub evictions: u64,
+}
+
+/// State of the app written into a diagnostics bundle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub app_version: String,
+    pub datafusion_version: String,
+    pub os: String,
+    pub arch: String,
+    pub created_at: String,
+    pub read_mode: String,
+    pub settings: Settings,
+    pub cache: CacheStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsBundle {
+    pub path: String,
+    pub size: u64,
+    /// Entries written into the archive.
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CountEstimate {
+    pub count: usize,
+    /// Whether some row groups could not be decided from statistics, making
+    /// `count` an upper bound rather than exact.
+    pub approximate: bool,
+}
+
+/// What row-group statistics decide about a filter for one row group.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneVerdict {
+    /// No row can match, so the row group is skipped.
+    Pruned,
+    /// Some rows may match; the row group has to be read.
+    Scan,
+    /// Every row matches without evaluating the filter.
+    AllMatch,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RowGroupPruning {
+    pub index: usize,
+    pub num_rows: i64,
+    pub compressed_size: i64,
+    pub verdict: PruneVerdict,
+    /// Statistics of each column the filter names, `None` where the chunk has none.
+    pub statistics: BTreeMap<String, Option<ColumnStatistics>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PruningReport {
+    pub row_groups: Vec<RowGroupPruning>,
+    pub pruned_row_groups: usize,
+    /// Row groups that have to be read, including those where every row matches.
+    pub scanned_row_groups: usize,
+    pub pruned_rows: i64,
+    pub scanned_rows: i64,
+    pub pruned_bytes: i64,
+    pub scanned_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineSettings {
+    /// Query memory limit in bytes, or `None` for unbounded.
+    pub memory_limit: Option<usize>,
+    /// Where queries over the limit spill; `None` is the OS temp directory.
+    pub spill_dir: Option<String>,
+    /// Partitions per query, or `None` for one per core.
+    pub target_partitions: Option<usize>,
+    /// Blocking thread cap set at startup, or `None` for tokio's default.
+    pub blocking_threads: Option<usize>,
+    /// Bytes currently reserved by running queries.
+    pub memory_used: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisplaySettings {
+    /// `"utc"`, `"local"` or an IANA name; applies to UTC-adjusted timestamps.
+    pub timezone: String,
+    /// chrono `strftime` pattern for the date and time of timestamps.
+    pub timestamp_format: String,
+    /// `"hex"`, `"base64"` or `"size"`; applies to binary values.
+    pub binary: String,
+    /// `"string"` or `"null"`; applies to NaN and ±Infinity floats.
+    pub non_finite: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellBytes {
+    pub size: usize,
+    /// The full payload, base64-encoded.
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CellValue {
+    pub column: String,
+    pub data_type: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileChangedEvent {
+    pub path: String,
+    /// "modified" or "removed".
+    pub kind: String,
+    pub size: Option<u64>,
+    pub modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryChangedEvent {
+    /// Watched directory the change happened under, as passed to `watch_directory`.
+    pub directory: String,
+    pub path: String,
+    /// "added", "removed" or "modified".
+    pub kind: String,
+}
+
+/// A folder whose new CSV and JSON Lines files are converted to Parquet as
+/// they appear.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestFolder {
+    pub folder: String,
+    /// Directory the Parquet files are written to, named after their source.
+    /// Defaults to the watched folder itself.
+    pub destination: Option<String>,
+    /// Column name -> SQL type (e.g. "BIGINT", "TIMESTAMP") used instead of
+    /// the inferred type.
+    #[serde(default)]
+    pub schema_overrides: BTreeMap<String, String>,
+    #[serde(default)]
+    pub write_options: WriteOptions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestResult {
+    /// Watched folder the file appeared in, as passed to `start_ingest`.
+    pub folder: String,
+    pub source: String,
+    pub dest_path: String,
+    /// Rows written, `None` when the conversion failed.
+    pub rows: Option<i64>,
+    pub error: Option<String>,
+    pub finished_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataPrefetch {
+    pub total: usize,
+    pub loaded: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaVariant {
+    /// Columns as "name: type", in file order.
+    pub columns: Vec<String>,
+    pub file_count: usize,
+    pub example_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionRange {
+    pub key: String,
+    pub min: String,
+    pub max: String,
+    pub distinct_values: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectorySummary {
+    pub file_count: usize,
+    pub total_size: u64,
+    /// Sum of footer row counts over readable files.
+    pub total_rows: i64,
+    /// Distinct schemas, most common first.
+    pub schemas: Vec<SchemaVariant>,
+    /// Ranges of hive-style `key=value` path segments.
+    pub partitions: Vec<PartitionRange>,
+    /// Files whose footer couldn't be read, with the reason.
+    pub unreadable_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnTypeChange {
+    pub name: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSchemaDrift {
+    pub path: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub retyped: Vec<ColumnTypeChange>,
+    /// Same columns and types as the baseline, but in a different order.
+    pub reordered: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDriftReport {
+    /// Baseline columns as "name: type".
+    pub baseline: Vec<String>,
+    /// File the baseline was taken from.
+    pub baseline_path: String,
+    pub files_checked: usize,
+    /// Only files that differ from the baseline.
+    pub drifted_files: Vec<FileSchemaDrift>,
+    pub unreadable_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaField {
+    pub name: String,
+    /// Arrow type the column reads as.
+    pub data_type: String,
+    pub physical_type: String,
+    pub logical_type: Option<String>,
+    pub nullable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaFieldChange {
+    pub name: String,
+    pub before: SchemaField,
+    pub after: SchemaField,
+    /// Human-readable list of what changed, e.g. "nullable: false -> true".
+    pub changes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDiff {
+    /// Columns only in the second file.
+    pub added: Vec<SchemaField>,
+    /// Columns only in the first file.
+    pub removed: Vec<SchemaField>,
+    pub changed: Vec<SchemaFieldChange>,
+    pub unchanged: usize,
+    pub identical: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueChange {
+    pub column: String,
+    pub a: serde_json::Value,
+    pub b: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedRow {
+    /// Key column values identifying the row in both files.
+    pub key: serde_json::Map<String, serde_json::Value>,
+    /// Only the compared columns whose values differ.
+    pub changes: Vec<ValueChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataDiff {
+    pub key_columns: Vec<String>,
+    pub compare_columns: Vec<String>,
+    pub only_in_a_count: usize,
+    pub only_in_b_count: usize,
+    pub changed_count: usize,
+    /// Current page of each section, ordered by key.
+    pub only_in_a: Vec<serde_json::Value>,
+    pub only_in_b: Vec<serde_json::Value>,
+    pub changed: Vec<ChangedRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowGroupVerification {
+    pub index: usize,
+    /// Row count recorded in the footer.
+    pub expected_rows: i64,
+    /// Rows actually decoded, when decoding succeeded.
+    pub actual_rows: Option<i64>,
+    pub passed: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnChunkVerification {
+    pub row_group: usize,
+    pub column: String,
+    pub pages: usize,
+    /// Values counted across data page headers.
+    pub page_values: i64,
+    /// Values recorded in the column chunk metadata.
+    pub expected_values: i64,
+    pub crc_mismatches: usize,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileVerification {
+    pub passed: bool,
+    pub footer_rows: i64,
+    /// Sum of the row group row counts in the footer.
+    pub row_group_rows: i64,
+    pub row_groups: Vec<RowGroupVerification>,
+    pub pages_checked: usize,
+    /// Pages whose header carried a CRC that was recomputed.
+    pub crc_checked: usize,
+    pub crc_mismatches: usize,
+    /// Only column chunks where something failed.
+    pub failed_chunks: Vec<ColumnChunkVerification>,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHash {
+    pub path: String,
+    /// "md5" or "sha256".
+    pub algorithm: String,
+    /// Lowercase hex digest, as `md5sum` and `sha256sum` print it.
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Options for writing Parquet output. Unset fields keep the writer defaults,
+/// except `compression`, which keeps the source file's codec when rewriting.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WriteOptions {
+    /// "none", "snappy", "zstd", "gzip", "lz4" or "brotli".
+    pub compression: Option<String>,
+    /// Codec level for zstd, gzip and brotli.
+    pub compression_level: Option<i32>,
+    /// Maximum rows per row group.
+    pub row_group_size: Option<usize>,
+    pub dictionary: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewriteResult {
+    pub dest_path: String,
+    pub rows: i64,
+    pub row_groups: usize,
+    pub compression: String,
+    pub source_size: u64,
+    pub dest_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitFile {
+    pub path: String,
+    pub rows: i64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitResult {
+    pub files: Vec<SplitFile>,
+    pub total_rows: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParquetWriteResult {
+    pub dest_path: String,
+    pub rows: i64,
+    pub row_groups: usize,
+    pub compression: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionResult {
+    /// One file per partition, under `key=value` directories.
+    pub files: Vec<SplitFile>,
+    pub total_rows: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SensitiveKind {
+    Email,
+    Phone,
+    /// US Social Security or UK National Insurance number.
+    NationalId,
+    CreditCard,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitiveColumn {
+    pub column: String,
+    pub kind: SensitiveKind,
+    /// 0-1, mostly the share of sampled values matching `kind`.
+    pub confidence: f64,
+    pub matched: usize,
+    /// Non-null values checked.
+    pub checked: usize,
+    /// Whether the column name suggests `kind` too.
+    pub name_hint: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SensitiveColumns {
+    pub sampled_rows: usize,
+    /// Most confident first.
+    pub columns: Vec<SensitiveColumn>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDdl {
+    pub dialect: String,
+    pub table: String,
+    pub statement: String,
+    /// Columns whose type the dialect can't represent exactly, and what they became.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDocument {
+    pub format: String,
+    pub content: String,
+    /// Where `content` was written, if a destination was given.
+    pub path: Option<String>,
+    /// Fields the format can't represent exactly, and what they became.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PandasRangeIndex {
+    pub name: Option<String>,
+    pub start: i64,
+    pub stop: i64,
+    pub step: i64,
+}
+
+/// Summary of the `pandas` key-value entry written by pyarrow.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PandasMetadata {
+    pub pandas_version: Option<String>,
+    /// Library and version that wrote the file, e.g. "pyarrow 15.0.0".
+    pub creator: Option<String>,
+    /// Columns holding the DataFrame index.
+    pub index_columns: Vec<String>,
+    /// Index levels stored only as a range, not as a column.
+    pub range_indexes: Vec<PandasRangeIndex>,
+}
+
+/// Summary of the Spark entries written alongside the row schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SparkMetadata {
+    pub spark_version: Option<String>,
+    /// Whether dates and timestamps were rebased to the legacy hybrid calendar.
+    pub legacy_datetime: bool,
+}
+
+/// What the Parquet schema and the pandas/Spark metadata say about one column.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnLineage {
+    pub name: String,
+    pub arrow_type: String,
+    pub nullable: bool,
+    /// Arrow field metadata.
+    pub field_metadata: BTreeMap<String, String>,
+    /// Name in the DataFrame, when pandas stored it under a different one.
+    pub pandas_name: Option<String>,
+    pub pandas_type: Option<String>,
+    pub numpy_type: Option<String>,
+    pub pandas_metadata: Option<serde_json::Value>,
+    pub is_index: bool,
+    pub spark_type: Option<String>,
+    pub spark_nullable: Option<bool>,
+    pub spark_metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExtendedMetadata {
+    pub file: FileMetadataInfo,
+    pub pandas: Option<PandasMetadata>,
+    pub spark: Option<SparkMetadata>,
+    pub columns: Vec<ColumnLineage>,
+    /// Where the pandas or Spark metadata disagrees with the Parquet schema.
+    pub mismatches: Vec<String>,
+}
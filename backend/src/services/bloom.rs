@@ -0,0 +1,248 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use parquet::basic::{LogicalType, TimeUnit, Type as PhysicalType};
+use parquet::file::properties::ReaderProperties;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::serialized_reader::ReadOptionsBuilder;
+use parquet::schema::types::ColumnDescriptor;
+use serde_json::Value;
+
+use crate::models::{BloomCheck, BloomVerdict, FilterExpr, FilterOperator, RowGroupMembership};
+use crate::services::parquet::ParquetCache;
+use crate::services::pruning::{self, RowGroupMatch};
+use crate::services::reader::FileSource;
+
+/// `value` scaled by `10^scale` as an integer, or `None` when it has more
+/// decimal places than the column can hold.
+fn scaled_decimal(value: &str, scale: i32) -> Result<Option<i128>, String> {
+    let invalid = || format!("'{}' is not a decimal number", value);
+    let (negative, digits) = match value.trim().strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.trim().trim_start_matches('+')),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let scale = scale.max(0) as usize;
+    let kept = fraction.trim_end_matches('0');
+    if kept.len() > scale {
+        return Ok(None);
+    }
+    let scaled = format!("{}{}{}", whole, kept, "0".repeat(scale - kept.len()));
+    let unsigned: i128 = match scaled.as_str() {
+        "" => 0,
+        digits => digits.parse().map_err(|_| invalid())?,
+    };
+    Ok(Some(if negative { -unsigned } else { unsigned }))
+}
+
+fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.naive_utc());
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+fn as_integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::Number(n) => n.as_i64().map(i128::from),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The integer a column of `descr`'s logical type stores for `value`, or
+/// `None` when no stored value can equal it.
+fn stored_integer(descr: &ColumnDescriptor, value: &Value) -> Result<Option<i128>, String> {
+    let not_a = |what: &str| {
+        format!(
+            "{} is not {} for column '{}'",
+            value,
+            what,
+            descr.path().string()
+        )
+    };
+    match descr.logical_type() {
+        Some(LogicalType::Decimal { scale, .. }) => scaled_decimal(&as_text(value), scale),
+        Some(LogicalType::Date) => match value {
+            Value::String(s) => {
+                let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+                    .map_err(|_| not_a("a date (YYYY-MM-DD)"))?;
+                Ok(Some((date - NaiveDate::default()).num_days() as i128))
+            }
+            _ => as_integer(value).map(Some).ok_or_else(|| not_a("a date")),
+        },
+        Some(LogicalType::Timestamp { unit, .. }) if value.is_string() => {
+            let datetime = parse_datetime(value.as_str().unwrap_or_default())
+                .ok_or_else(|| not_a("a timestamp"))?
+                .and_utc();
+            Ok(match unit {
+                TimeUnit::MILLIS(_) => Some(datetime.timestamp_millis() as i128),
+                TimeUnit::MICROS(_) => Some(datetime.timestamp_micros() as i128),
+                TimeUnit::NANOS(_) => datetime.timestamp_nanos_opt().map(i128::from),
+            })
+        }
+        _ => as_integer(value)
+            .map(Some)
+            .ok_or_else(|| not_a("an integer")),
+    }
+}
+
+/// `value` in the plain encoding of `descr`'s physical type, which is what
+/// bloom filters hash, or `None` when the column can't hold it at all.
+fn plain_bytes(descr: &ColumnDescriptor, value: &Value) -> Result<Option<Vec<u8>>, String> {
+    Ok(match descr.physical_type() {
+        PhysicalType::INT32 => stored_integer(descr, value)?
+            .and_then(|v| i32::try_from(v).ok())
+            .map(|v| v.to_le_bytes().to_vec()),
+        PhysicalType::INT64 => stored_integer(descr, value)?
+            .and_then(|v| i64::try_from(v).ok())
+            .map(|v| v.to_le_bytes().to_vec()),
+        PhysicalType::FLOAT | PhysicalType::DOUBLE => {
+            let number = match value {
+                Value::Number(n) => n.as_f64(),
+                Value::String(s) => s.trim().parse().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| format!("{} is not a number", value))?;
+            Some(match descr.physical_type() {
+                PhysicalType::FLOAT => (number as f32).to_le_bytes().to_vec(),
+                _ => number.to_le_bytes().to_vec(),
+            })
+        }
+        PhysicalType::BOOLEAN => match value {
+            Value::Bool(b) => Some(vec![*b as u8]),
+            _ => return Err(format!("{} is not true or false", value)),
+        },
+        PhysicalType::BYTE_ARRAY => Some(as_text(value).into_bytes()),
+        PhysicalType::FIXED_LEN_BYTE_ARRAY => {
+            let text = as_text(value);
+            let bytes = match descr.logical_type() {
+                Some(LogicalType::Uuid) => {
+                    let hex: String = text.chars().filter(|c| *c != '-').collect();
+                    (0..hex.len())
+                        .step_by(2)
+                        .map(|i| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("x"), 16))
+                        .collect::<Result<Vec<u8>, _>>()
+                        .map_err(|_| format!("'{}' is not a UUID", text))?
+                }
+                _ => text.into_bytes(),
+            };
+            (bytes.len() == descr.type_length().max(0) as usize).then_some(bytes)
+        }
+        PhysicalType::INT96 => {
+            return Err(format!(
+                "Column '{}' is INT96, which bloom filters can't be checked for",
+                descr.path().string()
+            ))
+        }
+    })
+}
+
+/// Whether `value` might occur in `column` and in which row groups, decided
+/// from the footer's min/max statistics and the column's bloom filters
+/// without reading any data pages. A bloom filter can only rule a value out;
+/// `MaybePresent` row groups still have to be read to be sure.
+pub fn bloom_check(
+    cache: &ParquetCache,
+    path: &str,
+    column: &str,
+    value: &Value,
+) -> Result<BloomCheck, String> {
+    let options = ReadOptionsBuilder::new()
+        .with_reader_properties(
+            ReaderProperties::builder()
+                .set_read_bloom_filter(true)
+                .build(),
+        )
+        .build();
+    let source = FileSource::open(path, cache.read_mode())?;
+    let reader =
+        SerializedFileReader::new_with_options(source, options).map_err(|e| e.to_string())?;
+
+    let leaves = reader.metadata().file_metadata().schema_descr().columns();
+    let index = leaves
+        .iter()
+        .position(|leaf| leaf.path().string() == column)
+        .or_else(|| {
+            let mut matches = leaves
+                .iter()
+                .enumerate()
+                .filter(|(_, leaf)| leaf.path().string().eq_ignore_ascii_case(column));
+            match (matches.next(), matches.next()) {
+                (Some((index, _)), None) => Some(index),
+                _ => None,
+            }
+        })
+        .ok_or_else(|| format!("Column '{}' not found", column))?;
+    let descr = leaves[index].clone();
+    let bytes = plain_bytes(&descr, value)?;
+
+    let condition = FilterExpr::Condition {
+        column: descr.path().string(),
+        operator: FilterOperator::Eq,
+        value: Some(value.clone()),
+    };
+    let mut row_groups = Vec::new();
+    for (i, row_group) in reader.metadata().row_groups().iter().enumerate() {
+        let verdict = if bytes.is_none()
+            || pruning::classify_row_group(row_group, &condition) == RowGroupMatch::Empty
+        {
+            BloomVerdict::OutOfRange
+        } else {
+            let row_group_reader = reader.get_row_group(i).map_err(|e| e.to_string())?;
+            match (row_group_reader.get_column_bloom_filter(index), &bytes) {
+                (Some(filter), Some(bytes)) if filter.check(bytes) => BloomVerdict::MaybePresent,
+                (Some(_), _) => BloomVerdict::Absent,
+                (None, _) => BloomVerdict::NoFilter,
+            }
+        };
+        row_groups.push(RowGroupMembership {
+            index: i,
+            num_rows: row_group.num_rows(),
+            verdict,
+        });
+    }
+
+    let candidate_row_groups: Vec<usize> = row_groups
+        .iter()
+        .filter(|rg| {
+            matches!(
+                rg.verdict,
+                BloomVerdict::MaybePresent | BloomVerdict::NoFilter
+            )
+        })
+        .map(|rg| rg.index)
+        .collect();
+    Ok(BloomCheck {
+        column: descr.path().string(),
+        might_exist: !candidate_row_groups.is_empty(),
+        has_bloom_filters: reader
+            .metadata()
+            .row_groups()
+            .iter()
+            .any(|rg| rg.column(index).bloom_filter_offset().is_some()),
+        candidate_row_groups,
+        row_groups,
+    })
+}
@@ -0,0 +1,661 @@
+use arrow::array::UInt32Array;
+use arrow::compute::take_record_batch;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use arrow::util::display::array_value_to_string;
+use datafusion::execution::disk_manager::DiskManagerConfig;
+use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::prelude::{ParquetReadOptions, SessionConfig, SessionContext};
+use futures::StreamExt;
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::{BrotliLevel, Compression, GzipLevel, ZstdLevel};
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::{
+    WriterProperties, WriterPropertiesBuilder, DEFAULT_MAX_ROW_GROUP_SIZE,
+};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::format::SortingColumn;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::models::{
+    ParquetWriteResult, PartitionResult, RewriteResult, SortSpec, SplitFile, SplitResult,
+    WriteOptions,
+};
+use crate::services::inspect::compression_to_string;
+use crate::services::sql::{build_order_by_clause, ColumnResolver};
+use crate::services::tasks::TaskHandle;
+
+/// Rows read per batch while copying data between files.
+const COPY_BATCH_SIZE: usize = 8192;
+
+/// Parse a codec name and optional level into a parquet `Compression`.
+pub fn parse_compression(name: &str, level: Option<i32>) -> Result<Compression, String> {
+    let level_error =
+        |e: parquet::errors::ParquetError| format!("Invalid compression level: {}", e);
+    match name.to_lowercase().as_str() {
+        "none" | "uncompressed" => Ok(Compression::UNCOMPRESSED),
+        "snappy" => Ok(Compression::SNAPPY),
+        "lz4" => Ok(Compression::LZ4_RAW),
+        "zstd" => Ok(Compression::ZSTD(match level {
+            Some(level) => ZstdLevel::try_new(level).map_err(level_error)?,
+            None => ZstdLevel::default(),
+        })),
+        "gzip" => Ok(Compression::GZIP(match level {
+            Some(level) => GzipLevel::try_new(level.max(0) as u32).map_err(level_error)?,
+            None => GzipLevel::default(),
+        })),
+        "brotli" => Ok(Compression::BROTLI(match level {
+            Some(level) => BrotliLevel::try_new(level.max(0) as u32).map_err(level_error)?,
+            None => BrotliLevel::default(),
+        })),
+        other => Err(format!("Unsupported compression: {}", other)),
+    }
+}
+
+/// Codec selected by `options`, or `default_compression` when none is chosen.
+pub fn chosen_compression(
+    options: &WriteOptions,
+    default_compression: Compression,
+) -> Result<Compression, String> {
+    match &options.compression {
+        Some(name) => parse_compression(name, options.compression_level),
+        None => Ok(default_compression),
+    }
+}
+
+/// Writer properties for `options`, falling back to `default_compression`
+/// when no codec is chosen.
+pub fn writer_properties(
+    options: &WriteOptions,
+    default_compression: Compression,
+    key_value_metadata: Option<Vec<KeyValue>>,
+) -> Result<WriterProperties, String> {
+    Ok(writer_properties_builder(options, default_compression, key_value_metadata)?.build())
+}
+
+fn writer_properties_builder(
+    options: &WriteOptions,
+    default_compression: Compression,
+    key_value_metadata: Option<Vec<KeyValue>>,
+) -> Result<WriterPropertiesBuilder, String> {
+    Ok(WriterProperties::builder()
+        .set_compression(chosen_compression(options, default_compression)?)
+        .set_max_row_group_size(options.row_group_size.unwrap_or(DEFAULT_MAX_ROW_GROUP_SIZE))
+        .set_dictionary_enabled(options.dictionary.unwrap_or(true))
+        .set_key_value_metadata(key_value_metadata))
+}
+
+/// Temporary sibling of `dest`, renamed into place once writing succeeds so a
+/// failed write never leaves a truncated file (or clobbers the source).
+pub fn temp_path(dest: &Path) -> PathBuf {
+    let name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    dest.with_file_name(format!(".{}.tmp", name))
+}
+
+/// Application key-value metadata worth carrying over to a rewritten file.
+/// The embedded Arrow schema is dropped because the writer produces its own.
+pub fn carried_metadata(reader: &SerializedFileReader<File>) -> Option<Vec<KeyValue>> {
+    let entries: Vec<KeyValue> = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()?
+        .iter()
+        .filter(|kv| kv.key != "ARROW:schema")
+        .cloned()
+        .collect();
+    (!entries.is_empty()).then_some(entries)
+}
+
+/// Settings of a source file that rewritten output inherits by default.
+struct SourceSettings {
+    compression: Compression,
+    key_value_metadata: Option<Vec<KeyValue>>,
+    num_rows: i64,
+}
+
+impl SourceSettings {
+    fn read(path: &str) -> Result<Self, String> {
+        let reader = SerializedFileReader::new(File::open(path).map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+        let compression = reader
+            .metadata()
+            .row_groups()
+            .first()
+            .and_then(|rg| rg.columns().first())
+            .map(|c| c.compression())
+            .unwrap_or(Compression::SNAPPY);
+        Ok(Self {
+            compression,
+            key_value_metadata: carried_metadata(&reader),
+            num_rows: reader.metadata().file_metadata().num_rows(),
+        })
+    }
+}
+
+fn batch_reader(path: &str, batch_size: usize) -> Result<ParquetRecordBatchReader, String> {
+    ParquetRecordBatchReaderBuilder::try_new(File::open(path).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?
+        .with_batch_size(batch_size)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Write `batches` to `dest` through a temporary file, reporting progress
+/// against `total_rows`. Returns the rows and row groups written.
+fn copy_to_parquet(
+    batches: impl Iterator<Item = Result<RecordBatch, String>>,
+    schema: SchemaRef,
+    props: WriterProperties,
+    dest: &str,
+    total_rows: i64,
+    task: Option<&TaskHandle>,
+) -> Result<(i64, usize), String> {
+    let tmp = temp_path(Path::new(dest));
+    let write = || -> Result<(i64, usize), String> {
+        let file = File::create(&tmp).map_err(|e| e.to_string())?;
+        let mut writer =
+            ArrowWriter::try_new(file, schema, Some(props)).map_err(|e| e.to_string())?;
+        let mut rows = 0i64;
+        for batch in batches {
+            let batch = batch?;
+            rows += batch.num_rows() as i64;
+            writer.write(&batch).map_err(|e| e.to_string())?;
+            if let Some(task) = task {
+                task.check_cancelled()?;
+                task.set_progress(rows as f64 / total_rows.max(1) as f64, None);
+            }
+        }
+        let metadata = writer.close().map_err(|e| e.to_string())?;
+        Ok((rows, metadata.row_groups.len()))
+    };
+
+    match write() {
+        Ok(result) => {
+            fs::rename(&tmp, dest).map_err(|e| e.to_string())?;
+            Ok(result)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Rewrite `source` to `dest` with new compression, row group size and
+/// dictionary settings. `dest` may equal `source`.
+pub fn rewrite_parquet(
+    source: &str,
+    dest: &str,
+    options: &WriteOptions,
+    task: Option<&TaskHandle>,
+) -> Result<RewriteResult, String> {
+    let source_size = fs::metadata(source).map_err(|e| e.to_string())?.len();
+
+    let settings = SourceSettings::read(source)?;
+    let props = writer_properties(options, settings.compression, settings.key_value_metadata)?;
+    let compression = compression_to_string(chosen_compression(options, settings.compression)?);
+    let total_rows = settings.num_rows;
+    let reader = batch_reader(source, COPY_BATCH_SIZE)?;
+
+    let schema = reader.schema();
+    let batches = reader.map(|batch| batch.map_err(|e| e.to_string()));
+    let (rows, row_groups) = copy_to_parquet(batches, schema, props, dest, total_rows, task)?;
+    let dest_path = Path::new(dest);
+
+    Ok(RewriteResult {
+        dest_path: dest.to_string(),
+        rows,
+        row_groups,
+        compression,
+        source_size,
+        dest_size: fs::metadata(dest_path).map_err(|e| e.to_string())?.len(),
+    })
+}
+
+/// Split `source` into numbered files in `dest_dir`, each holding at most
+/// `rows_per_file` rows or roughly `max_bytes` bytes (exactly one must be
+/// given). Output keeps the source schema, codec and key-value metadata, so
+/// the byte limit is turned into a row count using the source's average row size.
+pub fn split_parquet(
+    source: &str,
+    dest_dir: &str,
+    rows_per_file: Option<usize>,
+    max_bytes: Option<u64>,
+    task: Option<&TaskHandle>,
+) -> Result<SplitResult, String> {
+    let settings = SourceSettings::read(source)?;
+    let limit = match (rows_per_file, max_bytes) {
+        (Some(rows), None) => rows,
+        (None, Some(bytes)) => {
+            let source_size = fs::metadata(source).map_err(|e| e.to_string())?.len();
+            (bytes as f64 * settings.num_rows as f64 / source_size.max(1) as f64) as usize
+        }
+        _ => return Err("Specify exactly one of rows_per_file or max_bytes".to_string()),
+    };
+    if limit == 0 {
+        return Err("Limit is smaller than a single row".to_string());
+    }
+
+    let props = writer_properties(
+        &WriteOptions::default(),
+        settings.compression,
+        settings.key_value_metadata,
+    )?;
+    let reader = batch_reader(source, limit.min(COPY_BATCH_SIZE))?;
+    let schema = reader.schema();
+
+    fs::create_dir_all(dest_dir).map_err(|e| e.to_string())?;
+    let stem = Path::new(source)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "part".to_string());
+    let part_path =
+        |index: usize| Path::new(dest_dir).join(format!("{}-part-{:05}.parquet", stem, index));
+
+    let mut files: Vec<SplitFile> = Vec::new();
+    let mut writer: Option<(ArrowWriter<File>, usize)> = None;
+    let mut total_rows = 0i64;
+
+    let finish = |writer: ArrowWriter<File>, rows: usize, files: &mut Vec<SplitFile>| {
+        writer.close().map_err(|e| e.to_string())?;
+        let path = part_path(files.len());
+        files.push(SplitFile {
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path: path.to_string_lossy().to_string(),
+            rows: rows as i64,
+        });
+        Ok::<_, String>(())
+    };
+
+    for batch in reader {
+        let mut batch = batch.map_err(|e| e.to_string())?;
+        while batch.num_rows() > 0 {
+            let (current, rows) = match writer.as_mut() {
+                Some(current) => current,
+                None => {
+                    let file = File::create(part_path(files.len())).map_err(|e| e.to_string())?;
+                    let current = ArrowWriter::try_new(file, schema.clone(), Some(props.clone()))
+                        .map_err(|e| e.to_string())?;
+                    writer.insert((current, 0))
+                }
+            };
+
+            // Only take what fits in the current file
+            let take = (limit - *rows).min(batch.num_rows());
+            current
+                .write(&batch.slice(0, take))
+                .map_err(|e| e.to_string())?;
+            *rows += take;
+            total_rows += take as i64;
+            batch = batch.slice(take, batch.num_rows() - take);
+
+            if *rows >= limit {
+                if let Some((current, rows)) = writer.take() {
+                    finish(current, rows, &mut files)?;
+                }
+            }
+        }
+
+        if let Some(task) = task {
+            task.check_cancelled()?;
+            task.set_progress(total_rows as f64 / settings.num_rows.max(1) as f64, None);
+        }
+    }
+    if let Some((current, rows)) = writer.take() {
+        finish(current, rows, &mut files)?;
+    }
+
+    Ok(SplitResult { files, total_rows })
+}
+
+/// Write every batch of a result stream to `dest` through a temporary file,
+/// keeping the stream's Arrow types. Returns the rows and row groups written.
+async fn copy_stream_to_parquet(
+    mut stream: SendableRecordBatchStream,
+    props: WriterProperties,
+    dest: &str,
+) -> Result<(i64, usize), String> {
+    let tmp = temp_path(Path::new(dest));
+    let file = File::create(&tmp).map_err(|e| e.to_string())?;
+    let mut writer =
+        ArrowWriter::try_new(file, stream.schema(), Some(props)).map_err(|e| e.to_string())?;
+
+    let mut rows = 0i64;
+    let mut result = Ok(());
+    while let Some(batch) = stream.next().await {
+        result = batch
+            .map_err(|e| format!("Failed to execute query: {}", e))
+            .and_then(|batch| {
+                rows += batch.num_rows() as i64;
+                writer.write(&batch).map_err(|e| e.to_string())
+            });
+        if result.is_err() {
+            break;
+        }
+    }
+
+    match result.and_then(|_| writer.close().map_err(|e| e.to_string())) {
+        Ok(metadata) => {
+            fs::rename(&tmp, dest).map_err(|e| e.to_string())?;
+            Ok((rows, metadata.row_groups.len()))
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Write a query result stream to a new Parquet file at `dest`, keeping the
+/// result's Arrow types. Without a chosen codec the file is written with snappy.
+pub async fn write_stream_to_parquet(
+    stream: SendableRecordBatchStream,
+    dest: &str,
+    options: &WriteOptions,
+) -> Result<ParquetWriteResult, String> {
+    let compression = chosen_compression(options, Compression::SNAPPY)?;
+    let props = writer_properties(options, compression, None)?;
+    let (rows, row_groups) = copy_stream_to_parquet(stream, props, dest).await?;
+
+    Ok(ParquetWriteResult {
+        dest_path: dest.to_string(),
+        rows,
+        row_groups,
+        compression: compression_to_string(compression),
+        size: fs::metadata(dest).map_err(|e| e.to_string())?.len(),
+    })
+}
+
+/// Copy only `columns` of `source` into a new file at `dest`, in the order
+/// given. Unselected columns are never decoded thanks to the projection mask.
+pub fn extract_columns(
+    source: &str,
+    columns: &[String],
+    dest: &str,
+    options: &WriteOptions,
+    task: Option<&TaskHandle>,
+) -> Result<RewriteResult, String> {
+    if columns.is_empty() {
+        return Err("Select at least one column".to_string());
+    }
+    let source_size = fs::metadata(source).map_err(|e| e.to_string())?.len();
+    let settings = SourceSettings::read(source)?;
+
+    let builder =
+        ParquetRecordBatchReaderBuilder::try_new(File::open(source).map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+    let file_schema = builder.schema().clone();
+    let mut roots = Vec::with_capacity(columns.len());
+    for column in columns {
+        let index = file_schema
+            .index_of(column)
+            .map_err(|_| format!("Column not found: {}", column))?;
+        if roots.contains(&index) {
+            return Err(format!("Column selected twice: {}", column));
+        }
+        roots.push(index);
+    }
+    let mask = ProjectionMask::roots(builder.parquet_schema(), roots.iter().copied());
+    let reader = builder
+        .with_projection(mask)
+        .with_batch_size(COPY_BATCH_SIZE)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // The mask yields columns in file order; reorder them to match the request
+    let mut sorted = roots.clone();
+    sorted.sort_unstable();
+    let order: Vec<usize> = roots
+        .iter()
+        .map(|root| sorted.iter().position(|r| r == root).unwrap_or(0))
+        .collect();
+    let schema = Arc::new(reader.schema().project(&order).map_err(|e| e.to_string())?);
+    let batches = reader.map(|batch| {
+        batch
+            .and_then(|b| b.project(&order))
+            .map_err(|e| e.to_string())
+    });
+
+    let props = writer_properties(options, settings.compression, settings.key_value_metadata)?;
+    let compression = compression_to_string(chosen_compression(options, settings.compression)?);
+    let (rows, row_groups) =
+        copy_to_parquet(batches, schema, props, dest, settings.num_rows, task)?;
+
+    Ok(RewriteResult {
+        dest_path: dest.to_string(),
+        rows,
+        row_groups,
+        compression,
+        source_size,
+        dest_size: fs::metadata(dest).map_err(|e| e.to_string())?.len(),
+    })
+}
+
+/// Memory DataFusion may use for sorting before spilling to temp files.
+const SORT_MEMORY_LIMIT: usize = 512 * 1024 * 1024;
+
+/// Sort `source` by `sort` and write the result to `dest`. The sort runs in
+/// DataFusion with a bounded memory pool and spills to disk, so files larger
+/// than memory can be sorted. The sort order is recorded in each row group's
+/// `sorting_columns` so readers know the data is ordered.
+pub async fn sort_parquet(
+    source: &str,
+    dest: &str,
+    sort: Vec<SortSpec>,
+    options: &WriteOptions,
+) -> Result<RewriteResult, String> {
+    if sort.is_empty() {
+        return Err("Choose at least one sort column".to_string());
+    }
+    let source_size = fs::metadata(source).map_err(|e| e.to_string())?.len();
+    let settings = SourceSettings::read(source)?;
+
+    let runtime = RuntimeEnv::new(
+        RuntimeConfig::new()
+            .with_memory_limit(SORT_MEMORY_LIMIT, 1.0)
+            .with_disk_manager(DiskManagerConfig::NewOs),
+    )
+    .map_err(|e| e.to_string())?;
+    let ctx = SessionContext::new_with_config_rt(SessionConfig::new(), Arc::new(runtime));
+    ctx.register_parquet("t", source, ParquetReadOptions::default())
+        .await
+        .map_err(|e| format!("Failed to register parquet file: {}", e))?;
+
+    let sorting_columns = sorting_columns(source, &sort)?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+    let query = format!(
+        "SELECT * FROM t {}",
+        build_order_by_clause(Some(sort), &columns)?
+    );
+    let stream = ctx
+        .sql(&query)
+        .await
+        .map_err(|e| format!("Failed to sort: {}", e))?
+        .execute_stream()
+        .await
+        .map_err(|e| format!("Failed to sort: {}", e))?;
+
+    let props =
+        writer_properties_builder(options, settings.compression, settings.key_value_metadata)?
+            .set_sorting_columns(Some(sorting_columns))
+            .build();
+    let compression = compression_to_string(chosen_compression(options, settings.compression)?);
+    let (rows, row_groups) = copy_stream_to_parquet(stream, props, dest).await?;
+
+    Ok(RewriteResult {
+        dest_path: dest.to_string(),
+        rows,
+        row_groups,
+        compression,
+        source_size,
+        dest_size: fs::metadata(dest).map_err(|e| e.to_string())?.len(),
+    })
+}
+
+/// Footer `sorting_columns` entries for `sort`. Only top-level leaf columns
+/// can be described this way.
+fn sorting_columns(source: &str, sort: &[SortSpec]) -> Result<Vec<SortingColumn>, String> {
+    let reader = SerializedFileReader::new(File::open(source).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    let schema = reader.metadata().file_metadata().schema_descr_ptr();
+    sort.iter()
+        .map(|spec| {
+            let index = schema
+                .columns()
+                .iter()
+                .position(|c| c.path().parts() == [spec.column.clone()])
+                .ok_or_else(|| {
+                    format!("Cannot sort by nested or missing column: {}", spec.column)
+                })?;
+            Ok(SortingColumn {
+                column_idx: index as i32,
+                descending: spec.descending,
+                // Same default as SQL: nulls last ascending, first descending
+                nulls_first: spec.nulls_first.unwrap_or(spec.descending),
+            })
+        })
+        .collect()
+}
+
+/// Directory name Hive uses for null partition values.
+const HIVE_NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Open partitions are each an in-progress writer, so cap them to keep
+/// memory and file handles bounded.
+const MAX_PARTITIONS: usize = 10_000;
+
+/// Percent-encode characters Hive escapes in partition directory names.
+fn escape_partition_value(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_control() || "\"#%'*/:=?\\{}[]^".contains(c) {
+                let mut buf = [0u8; 4];
+                c.encode_utf8(&mut buf)
+                    .bytes()
+                    .map(|b| format!("%{:02X}", b))
+                    .collect()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Write `source` into a Hive layout under `dest_dir`: one
+/// `col=value/.../part-00000.parquet` file per distinct combination of
+/// `partition_columns`. Partition columns are dropped from the files since
+/// readers recover them from the path.
+pub fn partition_parquet(
+    source: &str,
+    dest_dir: &str,
+    partition_columns: &[String],
+    options: &WriteOptions,
+    task: Option<&TaskHandle>,
+) -> Result<PartitionResult, String> {
+    if partition_columns.is_empty() {
+        return Err("Choose at least one partition column".to_string());
+    }
+    let settings = SourceSettings::read(source)?;
+    let reader = batch_reader(source, COPY_BATCH_SIZE)?;
+    let schema = reader.schema();
+
+    let key_indices = partition_columns
+        .iter()
+        .map(|c| {
+            schema
+                .index_of(c)
+                .map_err(|_| format!("Column not found: {}", c))
+        })
+        .collect::<Result<Vec<usize>, String>>()?;
+    let data_indices: Vec<usize> = (0..schema.fields().len())
+        .filter(|i| !key_indices.contains(i))
+        .collect();
+    if data_indices.is_empty() {
+        return Err("At least one column must remain outside the partition columns".to_string());
+    }
+    let data_schema = Arc::new(schema.project(&data_indices).map_err(|e| e.to_string())?);
+    let props = writer_properties(options, settings.compression, settings.key_value_metadata)?;
+
+    let mut writers: HashMap<PathBuf, (ArrowWriter<File>, i64)> = HashMap::new();
+    let mut total_rows = 0i64;
+    let write = || -> Result<(), String> {
+        for batch in reader {
+            let batch = batch.map_err(|e| e.to_string())?;
+
+            // Group row indices by their partition directory
+            let mut groups: HashMap<PathBuf, Vec<u32>> = HashMap::new();
+            for row in 0..batch.num_rows() {
+                let mut dir = PathBuf::from(dest_dir);
+                for (column, &index) in partition_columns.iter().zip(&key_indices) {
+                    let array = batch.column(index);
+                    let value = if array.is_null(row) {
+                        HIVE_NULL_PARTITION.to_string()
+                    } else {
+                        escape_partition_value(
+                            &array_value_to_string(array, row).map_err(|e| e.to_string())?,
+                        )
+                    };
+                    dir.push(format!("{}={}", escape_partition_value(column), value));
+                }
+                groups.entry(dir).or_default().push(row as u32);
+            }
+
+            let data = batch.project(&data_indices).map_err(|e| e.to_string())?;
+            for (dir, rows) in groups {
+                if !writers.contains_key(&dir) {
+                    if writers.len() >= MAX_PARTITIONS {
+                        return Err(format!(
+                            "More than {} partitions; choose coarser partition columns",
+                            MAX_PARTITIONS
+                        ));
+                    }
+                    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+                    let file =
+                        File::create(dir.join("part-00000.parquet")).map_err(|e| e.to_string())?;
+                    let writer =
+                        ArrowWriter::try_new(file, data_schema.clone(), Some(props.clone()))
+                            .map_err(|e| e.to_string())?;
+                    writers.insert(dir.clone(), (writer, 0));
+                }
+                let slice = take_record_batch(&data, &UInt32Array::from(rows))
+                    .map_err(|e| e.to_string())?;
+                if let Some((writer, count)) = writers.get_mut(&dir) {
+                    writer.write(&slice).map_err(|e| e.to_string())?;
+                    *count += slice.num_rows() as i64;
+                }
+            }
+
+            total_rows += batch.num_rows() as i64;
+            if let Some(task) = task {
+                task.check_cancelled()?;
+                task.set_progress(total_rows as f64 / settings.num_rows.max(1) as f64, None);
+            }
+        }
+        Ok(())
+    };
+    let result = write();
+
+    let mut files = Vec::with_capacity(writers.len());
+    for (dir, (writer, rows)) in writers {
+        writer.close().map_err(|e| e.to_string())?;
+        let path = dir.join("part-00000.parquet");
+        files.push(SplitFile {
+            size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path: path.to_string_lossy().to_string(),
+            rows,
+        });
+    }
+    result?;
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(PartitionResult { files, total_rows })
+}
@@ -0,0 +1,89 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::models::{DiagnosticsBundle, DiagnosticsReport};
+use crate::services::convert::temp_path;
+use crate::services::logging;
+
+const DIAGNOSTICS_DIR: &str = "diagnostics";
+
+/// Bytes kept from the end of each log file, so a noisy log can't bloat the bundle.
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Where a bundle goes when no destination is given.
+pub fn default_bundle_path(data_dir: &Path) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    data_dir
+        .join(DIAGNOSTICS_DIR)
+        .join(format!("parqsee-diagnostics-{}.zip", stamp))
+}
+
+/// The last `MAX_LOG_BYTES` of `path`.
+fn log_tail(path: &Path) -> Result<Vec<u8>, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    if len > MAX_LOG_BYTES {
+        file.seek(SeekFrom::Start(len - MAX_LOG_BYTES))
+            .map_err(|e| e.to_string())?;
+    }
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).map_err(|e| e.to_string())?;
+    Ok(contents)
+}
+
+/// Zip `report` (versions, settings, cache state) and the recent log files
+/// into `dest` for attaching to a bug report. Logs and cache state name the
+/// files that were opened, but no data from them: logged errors have quoted
+/// values and numbers masked.
+pub fn collect_diagnostics(
+    data_dir: &Path,
+    dest: &Path,
+    report: &DiagnosticsReport,
+) -> Result<DiagnosticsBundle, String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp = temp_path(dest);
+    let file = File::create(&tmp).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut entries = Vec::new();
+    let mut add = |zip: &mut ZipWriter<File>, name: String, contents: &[u8]| {
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(contents).map_err(|e| e.to_string())?;
+        entries.push(name);
+        Ok::<_, String>(())
+    };
+
+    let report_json = serde_json::to_vec_pretty(report).map_err(|e| e.to_string())?;
+    let result = add(&mut zip, "report.json".to_string(), &report_json).and_then(|_| {
+        for log in logging::log_files(data_dir) {
+            let Some(name) = log.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            // A log that can't be read is noted rather than failing the bundle
+            let contents = log_tail(&log)
+                .unwrap_or_else(|e| format!("Failed to read log: {}", e).into_bytes());
+            add(&mut zip, format!("logs/{}", name), &contents)?;
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, dest).map_err(|e| e.to_string())?;
+
+    Ok(DiagnosticsBundle {
+        path: dest.to_string_lossy().into_owned(),
+        size: fs::metadata(dest).map(|m| m.len()).unwrap_or(0),
+        files: entries,
+    })
+}
@@ -0,0 +1,191 @@
+use datafusion::execution::context::SessionContext;
+use datafusion::prelude::ParquetReadOptions;
+use serde_json::Value;
+
+use crate::models::{ChangedRow, DataDiff, ValueChange};
+use crate::services::parquet::{batches_to_rows, collect_query, first_row_i64, with_timeout};
+use crate::services::sql::quote_identifier;
+
+async fn column_names(ctx: &SessionContext, table: &str) -> Result<Vec<String>, String> {
+    let df = ctx.table(table).await.map_err(|e| e.to_string())?;
+    Ok(df
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().clone())
+        .collect())
+}
+
+async fn count(ctx: &SessionContext, query: &str) -> Result<usize, String> {
+    let batches = collect_query(ctx, &format!("SELECT COUNT(*) FROM ({})", query)).await?;
+    Ok(batches
+        .first()
+        .and_then(|batch| first_row_i64(batch, 0))
+        .unwrap_or(0) as usize)
+}
+
+/// Compare two files row by row, matching rows on `key_columns`.
+///
+/// Rows whose key is only in one file are listed per side; rows present in both
+/// are reported when any of `compare_columns` differ (all shared non-key
+/// columns by default). Keys are expected to be unique, and rows with a null
+/// key never match. `offset`/`limit` page each section independently.
+#[allow(clippy::too_many_arguments)]
+pub async fn diff_data(
+    path_a: &str,
+    path_b: &str,
+    key_columns: Vec<String>,
+    compare_columns: Option<Vec<String>>,
+    offset: usize,
+    limit: usize,
+    timeout_ms: Option<u64>,
+) -> Result<DataDiff, String> {
+    if key_columns.is_empty() {
+        return Err("At least one key column is required".to_string());
+    }
+
+    let ctx = SessionContext::new();
+    for (name, path) in [("a", path_a), ("b", path_b)] {
+        ctx.register_parquet(name, path, ParquetReadOptions::default())
+            .await
+            .map_err(|e| format!("Failed to register {}: {}", path, e))?;
+    }
+    let columns_a = column_names(&ctx, "a").await?;
+    let columns_b = column_names(&ctx, "b").await?;
+
+    for key in &key_columns {
+        if !columns_a.contains(key) || !columns_b.contains(key) {
+            return Err(format!("Key column {} must exist in both files", key));
+        }
+    }
+    let compare_columns = match compare_columns {
+        Some(columns) => {
+            if let Some(missing) = columns
+                .iter()
+                .find(|c| !columns_a.contains(c) || !columns_b.contains(c))
+            {
+                return Err(format!("Column {} must exist in both files", missing));
+            }
+            columns
+        }
+        None => columns_a
+            .iter()
+            .filter(|c| columns_b.contains(c) && !key_columns.contains(c))
+            .cloned()
+            .collect(),
+    };
+
+    let join_on = key_columns
+        .iter()
+        .map(|k| {
+            let k = quote_identifier(k);
+            format!("a.{k} = b.{k}")
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let order_by = |table: &str| {
+        key_columns
+            .iter()
+            .map(|k| format!("{}.{}", table, quote_identifier(k)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let only_in_a = format!("SELECT a.* FROM a LEFT ANTI JOIN b ON {join_on}");
+    let only_in_b = format!("SELECT b.* FROM b LEFT ANTI JOIN a ON {join_on}");
+
+    let mut select: Vec<String> = key_columns
+        .iter()
+        .enumerate()
+        .map(|(i, k)| format!("a.{} AS k{}", quote_identifier(k), i))
+        .collect();
+    for (i, column) in compare_columns.iter().enumerate() {
+        let column = quote_identifier(column);
+        select.push(format!("a.{column} AS a{i}"));
+        select.push(format!("b.{column} AS b{i}"));
+    }
+    let differs = if compare_columns.is_empty() {
+        "FALSE".to_string()
+    } else {
+        compare_columns
+            .iter()
+            .map(|c| {
+                let c = quote_identifier(c);
+                // Parenthesized because IS DISTINCT FROM binds looser than OR here
+                format!("(a.{c} IS DISTINCT FROM b.{c})")
+            })
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    };
+    let changed = format!(
+        "SELECT {} FROM a JOIN b ON {join_on} WHERE {differs}",
+        select.join(", ")
+    );
+
+    with_timeout(timeout_ms, async {
+        let only_in_a_count = count(&ctx, &only_in_a).await?;
+        let only_in_b_count = count(&ctx, &only_in_b).await?;
+        let changed_count = count(&ctx, &changed).await?;
+
+        let page = format!("LIMIT {} OFFSET {}", limit, offset);
+        let only_in_a_rows = batches_to_rows(
+            &collect_query(
+                &ctx,
+                &format!("{only_in_a} ORDER BY {} {page}", order_by("a")),
+            )
+            .await?,
+        )?;
+        let only_in_b_rows = batches_to_rows(
+            &collect_query(
+                &ctx,
+                &format!("{only_in_b} ORDER BY {} {page}", order_by("b")),
+            )
+            .await?,
+        )?;
+        let changed_rows = batches_to_rows(
+            &collect_query(
+                &ctx,
+                &format!("{changed} ORDER BY {} {page}", order_by("a")),
+            )
+            .await?,
+        )?;
+
+        let changed = changed_rows
+            .iter()
+            .map(|row| {
+                let field = |name: String| row.get(&name).cloned().unwrap_or(Value::Null);
+                let key = key_columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, k)| (k.clone(), field(format!("k{}", i))))
+                    .collect();
+                let changes = compare_columns
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, column)| {
+                        let a = field(format!("a{}", i));
+                        let b = field(format!("b{}", i));
+                        (a != b).then(|| ValueChange {
+                            column: column.clone(),
+                            a,
+                            b,
+                        })
+                    })
+                    .collect();
+                ChangedRow { key, changes }
+            })
+            .collect();
+
+        Ok(DataDiff {
+            key_columns: key_columns.clone(),
+            compare_columns: compare_columns.clone(),
+            only_in_a_count,
+            only_in_b_count,
+            changed_count,
+            only_in_a: only_in_a_rows,
+            only_in_b: only_in_b_rows,
+            changed,
+        })
+    })
+    .await
+}
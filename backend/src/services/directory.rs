@@ -0,0 +1,395 @@
+use glob::Pattern;
+use parquet::arrow::parquet_to_arrow_schema;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{read_dir, File};
+use std::path::{Path, PathBuf};
+
+use crate::models::{
+    ColumnTypeChange, DirectorySummary, FileEntry, FileSchemaDrift, PartitionRange,
+    SchemaDriftReport, SchemaVariant,
+};
+
+/// Depth used when `scan_directory` is called without one, so a scan of a
+/// home directory doesn't walk the whole disk.
+pub const DEFAULT_SCAN_DEPTH: usize = 8;
+
+/// Sort entries directories first, then by name case-insensitively.
+pub fn sort_entries(entries: &mut [FileEntry]) {
+    entries.sort_by(|a, b| match (a.is_directory, b.is_directory) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        _ => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+    });
+}
+
+struct ScanOptions {
+    pattern: Option<Pattern>,
+    /// Whether the glob contains a separator and so matches relative paths
+    /// like `year=*/month=*/*.parquet` rather than bare file names.
+    match_path: bool,
+    max_depth: usize,
+    parquet_only: bool,
+}
+
+impl ScanOptions {
+    fn filters_files(&self) -> bool {
+        self.pattern.is_some() || self.parquet_only
+    }
+
+    fn keeps_file(&self, relative: &str, name: &str) -> bool {
+        if self.parquet_only && !name.ends_with(".parquet") {
+            return false;
+        }
+        match &self.pattern {
+            Some(pattern) if self.match_path => pattern.matches(relative),
+            Some(pattern) => pattern.matches(name),
+            None => true,
+        }
+    }
+}
+
+/// Walk `path` recursively up to `max_depth` levels of subdirectories,
+/// populating `FileEntry.children`. Directories at the depth limit are
+/// returned with `children: None` so the UI can load them lazily. When a glob
+/// or `parquet_only` is given, directories without any matching file below
+/// them are left out.
+pub fn scan_directory(
+    path: &str,
+    glob: Option<&str>,
+    max_depth: Option<usize>,
+    parquet_only: bool,
+) -> Result<Vec<FileEntry>, String> {
+    let root = Path::new(path);
+    if !root.exists() {
+        return Err("Directory does not exist".to_string());
+    }
+    if !root.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+
+    let pattern = glob
+        .filter(|g| !g.trim().is_empty())
+        .map(|g| Pattern::new(g.trim()).map_err(|e| format!("Invalid glob pattern: {}", e)))
+        .transpose()?;
+    let options = ScanOptions {
+        match_path: glob.is_some_and(|g| g.contains('/')),
+        pattern,
+        max_depth: max_depth.unwrap_or(DEFAULT_SCAN_DEPTH),
+        parquet_only,
+    };
+
+    scan_level(root, "", 0, &options)
+}
+
+fn scan_level(
+    dir: &Path,
+    relative: &str,
+    depth: usize,
+    options: &ScanOptions,
+) -> Result<Vec<FileEntry>, String> {
+    let mut entries = Vec::new();
+
+    for entry in read_dir(dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let child_relative = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        // file_type doesn't follow symlinks, which keeps link cycles out of the walk
+        let file_type = entry.file_type().map_err(|e| e.to_string())?;
+
+        if file_type.is_dir() {
+            let children = if depth < options.max_depth {
+                // Unreadable subdirectories are shown empty rather than failing the scan
+                Some(scan_level(&path, &child_relative, depth + 1, options).unwrap_or_default())
+            } else {
+                None
+            };
+            if options.filters_files() && children.as_ref().is_some_and(|c| c.is_empty()) {
+                continue;
+            }
+            entries.push(FileEntry {
+                path: path.to_string_lossy().to_string(),
+                name,
+                is_directory: true,
+                is_parquet: false,
+                size: None,
+                children,
+            });
+        } else {
+            if !options.keeps_file(&child_relative, &name) {
+                continue;
+            }
+            let size = entry.metadata().ok().map(|m| m.len());
+            entries.push(FileEntry {
+                path: path.to_string_lossy().to_string(),
+                is_parquet: name.ends_with(".parquet"),
+                name,
+                is_directory: false,
+                size,
+                children: None,
+            });
+        }
+    }
+
+    sort_entries(&mut entries);
+    Ok(entries)
+}
+
+/// All `.parquet` files below `root`, sorted by path. Symlinked directories
+/// are not followed.
+pub fn parquet_files(root: &Path) -> Result<Vec<PathBuf>, String> {
+    if !root.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if path.extension().and_then(|e| e.to_str()) == Some("parquet") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Row count and "name: type" columns of a file, read from its footer only.
+pub fn footer_schema(path: &Path) -> Result<(i64, Vec<(String, String)>), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = SerializedFileReader::new(file).map_err(|e| e.to_string())?;
+    let file_metadata = reader.metadata().file_metadata();
+    let schema = parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )
+    .map_err(|e| e.to_string())?;
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|f| (f.name().clone(), f.data_type().to_string()))
+        .collect();
+    Ok((file_metadata.num_rows(), columns))
+}
+
+/// Hive-style `key=value` segments of `path` relative to `root`.
+fn partition_values(root: &Path, path: &Path) -> Vec<(String, String)> {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return Vec::new();
+    };
+    let Some(parent) = relative.parent() else {
+        return Vec::new();
+    };
+    parent
+        .components()
+        .filter_map(|c| {
+            let segment = c.as_os_str().to_str()?;
+            let (key, value) = segment.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Min and max of partition values, numerically when every value is a number.
+fn partition_range(key: String, values: &BTreeSet<String>) -> Option<PartitionRange> {
+    let numeric: Option<Vec<(f64, &String)>> = values
+        .iter()
+        .map(|v| v.parse::<f64>().ok().map(|n| (n, v)))
+        .collect();
+    let (min, max) = match numeric {
+        Some(numbers) => (
+            numbers
+                .iter()
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, v)| *v)?,
+            numbers
+                .iter()
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, v)| *v)?,
+        ),
+        None => (values.first()?, values.last()?),
+    };
+    Some(PartitionRange {
+        key,
+        min: min.clone(),
+        max: max.clone(),
+        distinct_values: values.len(),
+    })
+}
+
+/// Totals, distinct schemas and partition ranges for every parquet file below
+/// `path`, using footers only.
+pub fn summarize_directory(path: &str) -> Result<DirectorySummary, String> {
+    let root = Path::new(path);
+    let files = parquet_files(root)?;
+
+    let mut total_size = 0;
+    let mut total_rows = 0;
+    let mut schemas: HashMap<Vec<String>, SchemaVariant> = HashMap::new();
+    let mut partitions: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut unreadable_files = Vec::new();
+
+    for file in &files {
+        total_size += file.metadata().map(|m| m.len()).unwrap_or(0);
+        for (key, value) in partition_values(root, file) {
+            partitions.entry(key).or_default().insert(value);
+        }
+
+        match footer_schema(file) {
+            Ok((rows, columns)) => {
+                total_rows += rows;
+                let columns: Vec<String> = columns
+                    .into_iter()
+                    .map(|(name, data_type)| format!("{}: {}", name, data_type))
+                    .collect();
+                schemas
+                    .entry(columns.clone())
+                    .or_insert_with(|| SchemaVariant {
+                        columns,
+                        file_count: 0,
+                        example_path: file.to_string_lossy().to_string(),
+                    })
+                    .file_count += 1;
+            }
+            Err(e) => unreadable_files.push(format!("{}: {}", file.display(), e)),
+        }
+    }
+
+    let mut schemas: Vec<SchemaVariant> = schemas.into_values().collect();
+    schemas.sort_by(|a, b| {
+        b.file_count
+            .cmp(&a.file_count)
+            .then_with(|| a.example_path.cmp(&b.example_path))
+    });
+
+    Ok(DirectorySummary {
+        file_count: files.len(),
+        total_size,
+        total_rows,
+        schemas,
+        partitions: partitions
+            .into_iter()
+            .filter_map(|(key, values)| partition_range(key, &values))
+            .collect(),
+        unreadable_files,
+    })
+}
+
+/// Compare `actual` columns against `expected`, or `None` when they match exactly.
+pub fn schema_drift(
+    path: &str,
+    expected: &[(String, String)],
+    actual: &[(String, String)],
+) -> Option<FileSchemaDrift> {
+    if expected == actual {
+        return None;
+    }
+    let expected_types: HashMap<&str, &str> = expected
+        .iter()
+        .map(|(n, t)| (n.as_str(), t.as_str()))
+        .collect();
+    let actual_types: HashMap<&str, &str> = actual
+        .iter()
+        .map(|(n, t)| (n.as_str(), t.as_str()))
+        .collect();
+
+    let added: Vec<String> = actual
+        .iter()
+        .filter(|(name, _)| !expected_types.contains_key(name.as_str()))
+        .map(|(name, _)| name.clone())
+        .collect();
+    let removed: Vec<String> = expected
+        .iter()
+        .filter(|(name, _)| !actual_types.contains_key(name.as_str()))
+        .map(|(name, _)| name.clone())
+        .collect();
+    let retyped: Vec<ColumnTypeChange> = expected
+        .iter()
+        .filter_map(|(name, expected_type)| {
+            let actual_type = actual_types.get(name.as_str())?;
+            (*actual_type != expected_type).then(|| ColumnTypeChange {
+                name: name.clone(),
+                expected: expected_type.clone(),
+                actual: actual_type.to_string(),
+            })
+        })
+        .collect();
+    let reordered = added.is_empty() && removed.is_empty() && retyped.is_empty();
+
+    Some(FileSchemaDrift {
+        path: path.to_string(),
+        added,
+        removed,
+        retyped,
+        reordered,
+    })
+}
+
+/// Check every parquet file below `path` against a baseline schema: the
+/// footer of `reference_path` when given, otherwise the most common schema.
+pub fn check_schema_drift(
+    path: &str,
+    reference_path: Option<&str>,
+) -> Result<SchemaDriftReport, String> {
+    let files = parquet_files(Path::new(path))?;
+
+    let mut schemas = Vec::new();
+    let mut unreadable_files = Vec::new();
+    for file in &files {
+        match footer_schema(file) {
+            Ok((_, columns)) => schemas.push((file.to_string_lossy().to_string(), columns)),
+            Err(e) => unreadable_files.push(format!("{}: {}", file.display(), e)),
+        }
+    }
+
+    let (baseline_path, baseline) = match reference_path {
+        Some(reference) => {
+            let (_, columns) = footer_schema(Path::new(reference))?;
+            (reference.to_string(), columns)
+        }
+        None => {
+            // Most common schema wins; ties go to the first file in path order
+            let mut counts: HashMap<&Vec<(String, String)>, (usize, usize)> = HashMap::new();
+            for (index, (_, columns)) in schemas.iter().enumerate() {
+                counts.entry(columns).or_insert((0, index)).0 += 1;
+            }
+            let Some((_, (_, index))) = counts
+                .into_iter()
+                .max_by(|a, b| a.1 .0.cmp(&b.1 .0).then(b.1 .1.cmp(&a.1 .1)))
+            else {
+                return Err("No readable parquet files found".to_string());
+            };
+            schemas[index].clone()
+        }
+    };
+
+    let drifted_files = schemas
+        .iter()
+        .filter_map(|(file, columns)| schema_drift(file, &baseline, columns))
+        .collect();
+
+    Ok(SchemaDriftReport {
+        baseline: baseline
+            .iter()
+            .map(|(name, data_type)| format!("{}: {}", name, data_type))
+            .collect(),
+        baseline_path,
+        files_checked: schemas.len(),
+        drifted_files,
+        unreadable_files,
+    })
+}
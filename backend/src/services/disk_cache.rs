@@ -0,0 +1,156 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+const CACHE_FILE: &str = "cache.sqlite3";
+/// Bumped whenever the `entries` table changes; older tables are dropped, as
+/// everything in them can be recomputed.
+const SCHEMA_VERSION: i64 = 1;
+
+pub const KIND_METADATA: &str = "metadata";
+pub const KIND_COLUMN_STATS: &str = "column_stats";
+
+/// Computed results persisted across restarts, keyed by path and kind.
+/// An entry is only returned while the path's `file_version` still matches
+/// the one it was computed from.
+pub struct DiskCache {
+    conn: Mutex<Connection>,
+}
+
+/// Size and modification time (ns since the epoch) of a file.
+fn size_and_mtime(metadata: &fs::Metadata) -> Option<(u64, u128)> {
+    let mtime = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_nanos();
+    Some((metadata.len(), mtime))
+}
+
+/// Every file under `dir` as `(path relative to it, size, mtime)`.
+fn directory_members(
+    dir: &Path,
+    prefix: &str,
+    members: &mut Vec<(String, u64, u128)>,
+) -> Option<()> {
+    for entry in fs::read_dir(dir).ok()? {
+        let entry = entry.ok()?;
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        let metadata = fs::metadata(entry.path()).ok()?;
+        if metadata.is_dir() {
+            directory_members(&entry.path(), &format!("{}/", name), members)?;
+        } else {
+            let (size, mtime) = size_and_mtime(&metadata)?;
+            members.push((name, size, mtime));
+        }
+    }
+    Some(())
+}
+
+/// Identifies the version of `path` a result is computed from: the size and
+/// modification time of a file, or a digest of the names, sizes and
+/// modification times of every file under a directory, since rewriting a
+/// member leaves the directory's own modification time alone. Take it before
+/// computing, so a file changing meanwhile makes the entry stale rather than
+/// labelling old results with the new version.
+pub fn file_version(path: &str) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_dir() {
+        let (size, mtime) = size_and_mtime(&metadata)?;
+        return Some(format!("{}:{}", size, mtime));
+    }
+    let mut members = Vec::new();
+    directory_members(Path::new(path), "", &mut members)?;
+    members.sort();
+    let mut digest = Sha256::new();
+    for (name, size, mtime) in members {
+        digest.update(format!("{}\0{}\0{}\n", name, size, mtime));
+    }
+    Some(
+        digest
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    )
+}
+
+impl DiskCache {
+    pub fn open(data_dir: &Path) -> Result<Self, String> {
+        fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+        let conn = Connection::open(data_dir.join(CACHE_FILE)).map_err(|e| e.to_string())?;
+        let schema_version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to initialize cache database: {}", e))?;
+        if schema_version != SCHEMA_VERSION {
+            conn.execute_batch(&format!(
+                "DROP TABLE IF EXISTS entries; PRAGMA user_version = {};",
+                SCHEMA_VERSION
+            ))
+            .map_err(|e| format!("Failed to initialize cache database: {}", e))?;
+        }
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS entries (
+                path TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                version TEXT NOT NULL,
+                value TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                PRIMARY KEY (path, kind)
+            )",
+        )
+        .map_err(|e| format!("Failed to initialize cache database: {}", e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The `kind` entry for `path`, if one exists for the file as it is now.
+    pub fn get<T: DeserializeOwned>(&self, path: &str, kind: &str) -> Option<T> {
+        let version = file_version(path)?;
+        let conn = self.conn.lock().ok()?;
+        let value: String = conn
+            .query_row(
+                "SELECT value FROM entries
+                 WHERE path = ?1 AND kind = ?2 AND version = ?3",
+                params![path, kind, version],
+                |row| row.get(0),
+            )
+            .optional()
+            .ok()??;
+        serde_json::from_str(&value).ok()
+    }
+
+    /// Store `value` as the `kind` entry for `version` of `path`, as returned
+    /// by `file_version` before computing it, replacing any older entry.
+    pub fn put<T: Serialize>(
+        &self,
+        path: &str,
+        kind: &str,
+        version: &str,
+        value: &T,
+    ) -> Result<(), String> {
+        let value = serde_json::to_string(value).map_err(|e| e.to_string())?;
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO entries (path, kind, version, value, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![path, kind, version, value, chrono::Utc::now().to_rfc3339()],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Remove every entry, returning how many there were.
+    pub fn clear(&self) -> Result<usize, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM entries", [])
+            .map_err(|e| e.to_string())
+    }
+}
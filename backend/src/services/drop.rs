@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::models::{DropHandling, DroppedItem, DroppedKind, FilesDropped};
+use crate::services::directory;
+
+/// Whether `path` is a Parquet file: a `.parquet` extension, or the `PAR1`
+/// magic for files written without one (e.g. Spark `part-*` files).
+fn is_parquet_file(path: &Path) -> bool {
+    if path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("parquet"))
+    {
+        return true;
+    }
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok_and(|_| &magic == b"PAR1")
+}
+
+fn classify_item(path: &Path) -> DroppedItem {
+    let display = path.to_string_lossy().into_owned();
+    let unsupported = |reason: String| DroppedItem {
+        path: display.clone(),
+        kind: DroppedKind::Unsupported,
+        files: Vec::new(),
+        reason: Some(reason),
+    };
+
+    if path.is_dir() {
+        return match directory::parquet_files(path) {
+            Ok(files) if files.is_empty() => {
+                unsupported("No Parquet files in directory".to_string())
+            }
+            Ok(files) => DroppedItem {
+                path: display.clone(),
+                kind: DroppedKind::Dataset,
+                files: files
+                    .iter()
+                    .map(|f| f.to_string_lossy().into_owned())
+                    .collect(),
+                reason: None,
+            },
+            Err(e) => unsupported(e),
+        };
+    }
+    if !path.is_file() {
+        return unsupported("File not found".to_string());
+    }
+    if !is_parquet_file(path) {
+        let format = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| format!(".{}", e))
+            .unwrap_or_else(|| "unknown".to_string());
+        return unsupported(format!("Unsupported format: {}", format));
+    }
+    DroppedItem {
+        path: display.clone(),
+        kind: DroppedKind::ParquetFile,
+        files: vec![display],
+        reason: None,
+    }
+}
+
+/// Classify dropped paths, expanding directories to the Parquet files of the
+/// dataset below them, and decide how the drop should be opened.
+pub fn classify_drop(paths: &[PathBuf]) -> FilesDropped {
+    let items: Vec<DroppedItem> = paths.iter().map(|p| classify_item(p)).collect();
+    let supported: Vec<&DroppedItem> = items
+        .iter()
+        .filter(|item| item.kind != DroppedKind::Unsupported)
+        .collect();
+
+    let handling = match supported.as_slice() {
+        [] => DropHandling::Unsupported,
+        [item] if item.kind == DroppedKind::ParquetFile => DropHandling::OpenFile,
+        [item] if item.kind == DroppedKind::Dataset => DropHandling::OpenDataset,
+        _ => DropHandling::OpenFiles,
+    };
+    let files = supported
+        .iter()
+        .flat_map(|item| item.files.iter().cloned())
+        .collect();
+
+    FilesDropped {
+        handling,
+        items,
+        files,
+    }
+}
@@ -1,18 +1,55 @@
+use arrow::array::{ArrayRef, AsArray, StringArray};
+use arrow::datatypes::{DataType, Field as ArrowField, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
 use csv::Writer;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use flate2::write::GzEncoder;
+use futures::StreamExt;
 use parquet::file::reader::{FileReader, SerializedFileReader};
-use parquet::record::Row;
+use parquet::record::{Field, Row};
+use serde_json::Value;
+use std::collections::HashSet;
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
 
-use crate::utils::{field_to_string, row_to_json};
+use crate::models::{FilterExpr, SelectedRows, SortSpec};
+use crate::services::inspect::read_footer;
+use crate::services::parquet::{
+    display_rows, estimate_count, execute_sql_stream, file_order_session, select_view_query,
+    selected_rows, ParquetCache,
+};
+use crate::services::schema;
+use crate::services::sql::ColumnResolver;
+use crate::services::tasks::TaskHandle;
+use crate::utils::{
+    field_to_string_with, flatten_row_fields, flatten_structs, flattened_column_names,
+    row_to_json_with, utc_timestamp_columns, wkb, DisplayOptions, RenderOptions,
+};
 
+#[allow(clippy::too_many_arguments)]
 pub fn export_data(
     source_path: String,
     export_path: String,
     format: String,
     offset: Option<usize>,
     limit: Option<usize>,
+    compression: Option<String>,
+    password: Option<String>,
+    display: DisplayOptions,
+    flatten: bool,
+    task: Option<&TaskHandle>,
 ) -> Result<String, String> {
+    let dest = Destination::new(
+        &export_path,
+        &format,
+        Compression::parse(compression.as_deref())?,
+        password.as_deref(),
+    )?;
+
     // Read parquet file
     let file = File::open(&source_path).map_err(|e| e.to_string())?;
     let reader = SerializedFileReader::new(file).map_err(|e| e.to_string())?;
@@ -20,13 +57,23 @@ pub fn export_data(
     let metadata = reader.metadata();
     let schema = metadata.file_metadata().schema();
     let total_rows = metadata.file_metadata().num_rows() as usize;
+    let render = RenderOptions {
+        geometry_columns: wkb::geoparquet_columns(metadata.file_metadata().key_value_metadata()),
+        utc_columns: utc_timestamp_columns(schema),
+        display,
+        flatten,
+    };
 
     // Get column names
-    let columns: Vec<String> = schema
-        .get_fields()
-        .iter()
-        .map(|field| field.name().to_string())
-        .collect();
+    let columns: Vec<String> = if flatten {
+        flattened_column_names(schema)
+    } else {
+        schema
+            .get_fields()
+            .iter()
+            .map(|field| field.name().to_string())
+            .collect()
+    };
 
     let mut iter = reader.get_row_iter(None).map_err(|e| e.to_string())?;
 
@@ -39,73 +86,578 @@ pub fn export_data(
     }
 
     // Determine how many rows to export
-    let limit = limit.unwrap_or(total_rows - offset);
-    let rows_to_export = limit.min(total_rows - offset);
+    let limit = limit.unwrap_or(total_rows.saturating_sub(offset));
+    let rows_to_export = limit.min(total_rows.saturating_sub(offset));
 
-    // Collect data
-    let mut rows_data = Vec::new();
-    for _ in 0..rows_to_export {
+    let mut writer = RowWriter::create(&dest, &format, &columns)?;
+    let mut exported = 0;
+    for i in 0..rows_to_export {
+        if let Some(task) = task {
+            if i % 1000 == 0 {
+                task.check_cancelled()?;
+                task.set_progress(i as f64 / rows_to_export as f64, None);
+            }
+        }
         match iter.next() {
             Some(Ok(row)) => {
-                rows_data.push(row);
+                writer.write_row(&row, &render)?;
+                exported += 1;
             }
             Some(Err(e)) => return Err(e.to_string()),
             None => break,
         }
     }
+    writer.finish()?;
 
-    // Export based on format
-    match format.to_lowercase().as_str() {
-        "csv" => export_to_csv(&export_path, &columns, &rows_data),
-        "json" => export_to_json(&export_path, &rows_data),
-        _ => Err(format!("Unsupported export format: {}", format)),
-    }?;
+    Ok(format!(
+        "Successfully exported {} rows to {}",
+        exported, export_path
+    ))
+}
+
+/// Export the rows `selection` picks from the view described by `filter` and
+/// `sort`, so exactly the rows highlighted in the grid are written.
+#[allow(clippy::too_many_arguments)]
+pub async fn export_rows(
+    cache: &ParquetCache,
+    source_path: &str,
+    selection: &SelectedRows,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    export_path: &str,
+    format: &str,
+    compression: Option<String>,
+    password: Option<String>,
+    flatten: bool,
+) -> Result<String, String> {
+    let dest = Destination::new(
+        export_path,
+        format,
+        Compression::parse(compression.as_deref())?,
+        password.as_deref(),
+    )?;
+
+    let batches = selected_rows(cache, source_path, selection, filter, sort).await?;
+    let renderer = BatchRenderer::new(cache, source_path, flatten);
+    let schema = match batches.first() {
+        Some(batch) => batch.schema(),
+        // Still write the header for an empty selection
+        None => Arc::new(schema::arrow_schema(source_path)?),
+    };
+    let columns = renderer.columns(schema)?;
+
+    let mut writer = RowWriter::create(&dest, format, &columns)?;
+    let mut exported = 0;
+    for batch in &batches {
+        for row in renderer.rows(batch, &columns)? {
+            writer.write_value(&row)?;
+        }
+        exported += batch.num_rows();
+    }
+    writer.finish()?;
 
     Ok(format!(
         "Successfully exported {} rows to {}",
-        rows_data.len(),
-        export_path
+        exported, export_path
     ))
 }
 
-fn export_to_csv(path: &str, columns: &[String], rows: &[Row]) -> Result<(), String> {
-    let mut file = File::create(path).map_err(|e| e.to_string())?;
+/// Export the view `filter` and `sort` describe, from `offset` on, by running
+/// the same file-ordered query as the grid pages, so the file holds the rows in
+/// the order the grid shows them.
+#[allow(clippy::too_many_arguments)]
+pub async fn export_view(
+    cache: &ParquetCache,
+    source_path: &str,
+    export_path: &str,
+    format: &str,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    compression: Option<String>,
+    password: Option<String>,
+    flatten: bool,
+    task: Option<&TaskHandle>,
+) -> Result<String, String> {
+    let dest = Destination::new(
+        export_path,
+        format,
+        Compression::parse(compression.as_deref())?,
+        password.as_deref(),
+    )?;
+
+    let ctx = file_order_session(&cache.get_or_create_session(source_path).await?);
+    let columns = ColumnResolver::for_session(&ctx).await?;
+    let mut query = select_view_query(filter.as_ref(), sort, &columns)?;
+    if let Some(limit) = limit {
+        query.push_str(&format!(" LIMIT {}", limit));
+    }
+    let offset = offset.unwrap_or(0);
+    if offset > 0 {
+        query.push_str(&format!(" OFFSET {}", offset));
+    }
+    // Statistics bound the matching rows closely enough for progress; a
+    // directory has none to go by
+    let expected = estimate_count(cache, source_path, filter.as_ref())
+        .ok()
+        .map(|estimate| {
+            estimate
+                .count
+                .saturating_sub(offset)
+                .min(limit.unwrap_or(usize::MAX))
+                .max(1)
+        });
 
-    // Write UTF-8 BOM for Excel compatibility
-    file.write_all(&[0xEF, 0xBB, 0xBF])
-        .map_err(|e| e.to_string())?;
+    let stream = ctx
+        .sql(&query)
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?
+        .execute_stream()
+        .await
+        .map_err(|e| format!("Failed to execute query: {}", e))?;
+    let renderer = BatchRenderer::new(cache, source_path, flatten);
+    let exported = write_stream(stream, &renderer, &dest, format, expected, task).await?;
 
-    let mut writer = Writer::from_writer(file);
+    Ok(format!(
+        "Successfully exported {} rows to {}",
+        exported, export_path
+    ))
+}
 
-    // Write header
-    writer.write_record(columns).map_err(|e| e.to_string())?;
+/// Run `query` against `source_path` and write its result to `export_path`,
+/// returning how many rows were written.
+pub async fn export_query(
+    cache: &ParquetCache,
+    source_path: &str,
+    query: &str,
+    export_path: &str,
+    format: &str,
+    task: Option<&TaskHandle>,
+) -> Result<usize, String> {
+    let dest = Destination::new(export_path, format, Compression::None, None)?;
+    let stream = execute_sql_stream(cache, source_path, query).await?;
+    let renderer = BatchRenderer::new(cache, source_path, false);
+    write_stream(stream, &renderer, &dest, format, None, task).await
+}
 
-    // Write data rows
-    for row in rows {
-        let mut record = Vec::new();
-        for col_name in columns {
-            let value = row
-                .get_column_iter()
-                .find(|(name, _)| *name == col_name)
-                .map(|(_, field)| field_to_string(field))
-                .unwrap_or_else(|| "".to_string());
-            record.push(value);
+/// Write every batch of `stream` as it arrives. `expected` rows, when known,
+/// drive the task's progress.
+async fn write_stream(
+    mut stream: SendableRecordBatchStream,
+    renderer: &BatchRenderer,
+    dest: &Destination<'_>,
+    format: &str,
+    expected: Option<usize>,
+    task: Option<&TaskHandle>,
+) -> Result<usize, String> {
+    let columns = renderer.columns(stream.schema())?;
+    let mut writer = RowWriter::create(dest, format, &columns)?;
+    let mut exported = 0;
+    while let Some(batch) = stream.next().await {
+        let batch = batch.map_err(|e| format!("Failed to collect results: {}", e))?;
+        for row in renderer.rows(&batch, &columns)? {
+            writer.write_value(&row)?;
+        }
+        exported += batch.num_rows();
+        if let Some(task) = task {
+            task.check_cancelled()?;
+            if let Some(expected) = expected {
+                task.set_progress(exported as f64 / expected as f64, None);
+            }
+        }
+    }
+    writer.finish()?;
+    Ok(exported)
+}
+
+/// Renders query results for export the way the grid shows them, with the
+/// WKB of geometry columns as WKT as in raw exports.
+struct BatchRenderer {
+    geometry: HashSet<String>,
+    display: DisplayOptions,
+    flatten: bool,
+}
+
+impl BatchRenderer {
+    fn new(cache: &ParquetCache, source_path: &str, flatten: bool) -> Self {
+        // A directory has no single footer to declare geometry columns
+        let geometry = read_footer(source_path)
+            .map(|footer| wkb::geoparquet_columns(footer.file_metadata().key_value_metadata()))
+            .unwrap_or_default();
+        Self {
+            geometry,
+            display: cache.display_options(),
+            flatten,
+        }
+    }
+
+    fn prepare(&self, batch: &RecordBatch) -> Result<RecordBatch, String> {
+        let batch = render_geometry(batch, &self.geometry, self.display)?;
+        if self.flatten {
+            flatten_structs(&batch)
+        } else {
+            Ok(batch)
+        }
+    }
+
+    /// Names of the exported columns for results with `schema`.
+    fn columns(&self, schema: SchemaRef) -> Result<Vec<String>, String> {
+        let batch = self.prepare(&RecordBatch::new_empty(schema))?;
+        Ok(batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().to_string())
+            .collect())
+    }
+
+    fn rows(&self, batch: &RecordBatch, columns: &[String]) -> Result<Vec<Value>, String> {
+        let mut rows = display_rows(&[self.prepare(batch)?], self.display)?;
+        // The JSON encoder leaves nulls out; exports keep every column in every row
+        for row in &mut rows {
+            if let Value::Object(row) = row {
+                for column in columns {
+                    row.entry(column.as_str()).or_insert(Value::Null);
+                }
+            }
+        }
+        Ok(rows)
+    }
+}
+
+/// Replace binary `geometry` columns by their WKT, falling back to the
+/// display rendering for blobs that aren't valid WKB.
+fn render_geometry(
+    batch: &RecordBatch,
+    geometry: &HashSet<String>,
+    display: DisplayOptions,
+) -> Result<RecordBatch, String> {
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let rendered = match column.as_binary_opt::<i32>() {
+            Some(blobs) if geometry.contains(field.name()) => blobs
+                .iter()
+                .map(|blob| {
+                    blob.map(|bytes| {
+                        wkb::wkb_to_wkt(bytes).unwrap_or_else(|| display.binary.render(bytes))
+                    })
+                })
+                .collect::<StringArray>(),
+            _ => {
+                fields.push(field.as_ref().clone());
+                columns.push(column.clone());
+                continue;
+            }
+        };
+        fields.push(ArrowField::new(field.name(), DataType::Utf8, true));
+        columns.push(Arc::new(rendered));
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value.map(|v| v.to_lowercase()).as_deref() {
+            None | Some("") | Some("none") => Ok(Compression::None),
+            Some("gzip") | Some("gz") => Ok(Compression::Gzip),
+            Some("zstd") | Some("zst") => Ok(Compression::Zstd),
+            Some(other) => Err(format!("Unsupported compression: {}", other)),
+        }
+    }
+}
+
+/// Where an export is written and how it is packaged.
+struct Destination<'a> {
+    path: &'a str,
+    compression: Compression,
+    /// When set, the export is the single entry of an AES-256 encrypted ZIP
+    /// archive at `path`, named `entry_name`.
+    password: Option<&'a str>,
+    entry_name: String,
+}
+
+impl<'a> Destination<'a> {
+    fn new(
+        path: &'a str,
+        format: &str,
+        compression: Compression,
+        password: Option<&'a str>,
+    ) -> Result<Self, String> {
+        if let Some(password) = password {
+            if password.is_empty() {
+                return Err("Archive password must not be empty".to_string());
+            }
+            if !matches!(compression, Compression::None) {
+                return Err("Compression can't be combined with a password; the archive is already compressed".to_string());
+            }
+            // Otherwise the archive would open as the format its name promises and fail
+            let is_zip = Path::new(path)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+            if !is_zip {
+                return Err("A password-protected export must be saved as a .zip file".to_string());
+            }
+        }
+        // `out.zip` holds `out.csv`
+        let stem = Path::new(path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("export");
+        let entry_name = format!("{}.{}", stem, format.to_lowercase());
+        Ok(Self {
+            path,
+            compression,
+            password,
+            entry_name,
+        })
+    }
+}
+
+/// Export destination, optionally wrapped in a compression encoder or an
+/// encrypted archive. Must be closed with `finish` so compressed streams get
+/// their trailer and archives their directory.
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+    Zip(Box<ZipWriter<BufWriter<File>>>),
+}
+
+impl Output {
+    fn create(dest: &Destination) -> Result<Self, String> {
+        let file = BufWriter::new(File::create(dest.path).map_err(|e| e.to_string())?);
+        if let Some(password) = dest.password {
+            let options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .with_aes_encryption(AesMode::Aes256, password)
+                // Exports can pass 4 GiB, which needs ZIP64 sizes
+                .large_file(true);
+            let mut zip = ZipWriter::new(file);
+            zip.start_file(dest.entry_name.as_str(), options)
+                .map_err(|e| e.to_string())?;
+            return Ok(Output::Zip(Box::new(zip)));
+        }
+        Ok(match dest.compression {
+            Compression::None => Output::Plain(file),
+            Compression::Gzip => Output::Gzip(GzEncoder::new(file, flate2::Compression::default())),
+            Compression::Zstd => {
+                Output::Zstd(zstd::Encoder::new(file, 0).map_err(|e| e.to_string())?)
+            }
+        })
+    }
+
+    fn finish(self) -> Result<(), String> {
+        let mut file = match self {
+            Output::Plain(file) => file,
+            Output::Gzip(encoder) => encoder.finish().map_err(|e| e.to_string())?,
+            Output::Zstd(encoder) => encoder.finish().map_err(|e| e.to_string())?,
+            Output::Zip(zip) => zip.finish().map_err(|e| e.to_string())?,
+        };
+        file.flush().map_err(|e| e.to_string())
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Plain(w) => w.write(buf),
+            Output::Gzip(w) => w.write(buf),
+            Output::Zstd(w) => w.write(buf),
+            Output::Zip(w) => w.write(buf),
         }
-        writer.write_record(&record).map_err(|e| e.to_string())?;
     }
 
-    writer.flush().map_err(|e| e.to_string())?;
-    Ok(())
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Plain(w) => w.flush(),
+            Output::Gzip(w) => w.flush(),
+            Output::Zstd(w) => w.flush(),
+            Output::Zip(w) => w.flush(),
+        }
+    }
 }
 
-fn export_to_json(path: &str, rows: &[Row]) -> Result<(), String> {
-    let json_rows: Vec<serde_json::Value> = rows.iter().map(|row| row_to_json(row)).collect();
+/// Writes an export one row at a time, so exports larger than memory still
+/// succeed. Must be closed with `finish` to complete the file.
+enum RowWriter {
+    Csv {
+        writer: Box<Writer<Output>>,
+        columns: Vec<String>,
+    },
+    /// A pretty-printed JSON array; `rows` written so far decides the separators.
+    Json {
+        out: Output,
+        rows: usize,
+    },
+    Jsonl(Output),
+}
 
-    let json_string = serde_json::to_string_pretty(&json_rows).map_err(|e| e.to_string())?;
+impl RowWriter {
+    fn create(dest: &Destination, format: &str, columns: &[String]) -> Result<Self, String> {
+        let format = format.to_lowercase();
+        if !matches!(format.as_str(), "csv" | "json" | "jsonl" | "ndjson") {
+            return Err(format!("Unsupported export format: {}", format));
+        }
+        let mut out = Output::create(dest)?;
+        Ok(match format.as_str() {
+            "csv" => {
+                // Write UTF-8 BOM for Excel compatibility
+                out.write_all(&[0xEF, 0xBB, 0xBF])
+                    .map_err(|e| e.to_string())?;
+                let mut writer = Writer::from_writer(out);
+                writer.write_record(columns).map_err(|e| e.to_string())?;
+                RowWriter::Csv {
+                    writer: Box::new(writer),
+                    columns: columns.to_vec(),
+                }
+            }
+            "json" => RowWriter::Json { out, rows: 0 },
+            _ => RowWriter::Jsonl(out),
+        })
+    }
 
-    let mut file = File::create(path).map_err(|e| e.to_string())?;
-    file.write_all(json_string.as_bytes())
-        .map_err(|e| e.to_string())?;
+    /// Write a row read from the file.
+    fn write_row(&mut self, row: &Row, render: &RenderOptions) -> Result<(), String> {
+        let RowWriter::Csv { writer, columns } = self else {
+            return self.write_value(&row_to_json_with(row, render));
+        };
+        let fields: Vec<(String, &Field)> = if render.flatten {
+            flatten_row_fields(row)
+        } else {
+            row.get_column_iter()
+                .map(|(name, field)| (name.clone(), field))
+                .collect()
+        };
+        let record: Vec<String> = columns
+            .iter()
+            .map(|col_name| {
+                fields
+                    .iter()
+                    .find(|(name, _)| name == col_name)
+                    .map(|(name, field)| field_to_string_with(name, field, render))
+                    .unwrap_or_default()
+            })
+            .collect();
+        writer.write_record(&record).map_err(|e| e.to_string())
+    }
 
-    Ok(())
+    /// Write a row already rendered as a JSON object; nested values become
+    /// JSON text in CSV.
+    fn write_value(&mut self, row: &Value) -> Result<(), String> {
+        match self {
+            RowWriter::Csv { writer, columns } => {
+                let record: Vec<String> = columns
+                    .iter()
+                    .map(|c| json_value_to_cell(row.get(c)))
+                    .collect();
+                writer.write_record(&record).map_err(|e| e.to_string())
+            }
+            RowWriter::Json { out, rows } => {
+                out.write_all(if *rows == 0 { b"[\n" } else { b",\n" })
+                    .map_err(|e| e.to_string())?;
+                // Indent the object one level, matching a pretty-printed array;
+                // strings can't span lines since newlines in them are escaped
+                let pretty = serde_json::to_string_pretty(row).map_err(|e| e.to_string())?;
+                for (i, line) in pretty.lines().enumerate() {
+                    let separator: &[u8] = if i == 0 { b"  " } else { b"\n  " };
+                    out.write_all(separator).map_err(|e| e.to_string())?;
+                    out.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+                }
+                *rows += 1;
+                Ok(())
+            }
+            RowWriter::Jsonl(out) => {
+                serde_json::to_writer(&mut *out, row).map_err(|e| e.to_string())?;
+                out.write_all(b"\n").map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            RowWriter::Csv { mut writer, .. } => {
+                writer.flush().map_err(|e| e.to_string())?;
+                writer.into_inner().map_err(|e| e.to_string())?.finish()
+            }
+            RowWriter::Json { mut out, rows } => {
+                out.write_all(if rows == 0 { b"[]" } else { b"\n]" })
+                    .map_err(|e| e.to_string())?;
+                out.finish()
+            }
+            RowWriter::Jsonl(out) => out.finish(),
+        }
+    }
+}
+
+fn json_value_to_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// Render rows as text suitable for pasting into spreadsheets ("tsv") or
+/// documents ("markdown"). Only the given columns are included, in order.
+pub fn render_rows(rows: &[Value], columns: &[String], format: &str) -> Result<String, String> {
+    match format.to_lowercase().as_str() {
+        "tsv" => Ok(render_tsv(rows, columns)),
+        "markdown" | "md" => Ok(render_markdown(rows, columns)),
+        _ => Err(format!("Unsupported clipboard format: {}", format)),
+    }
+}
+
+fn render_tsv(rows: &[Value], columns: &[String]) -> String {
+    let escape = |s: &str| s.replace(['\t', '\n', '\r'], " ");
+
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    lines.push(
+        columns
+            .iter()
+            .map(|c| escape(c))
+            .collect::<Vec<_>>()
+            .join("\t"),
+    );
+    for row in rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|c| escape(&json_value_to_cell(row.get(c))))
+            .collect();
+        lines.push(cells.join("\t"));
+    }
+    lines.join("\n")
+}
+
+fn render_markdown(rows: &[Value], columns: &[String]) -> String {
+    let escape = |s: &str| s.replace('|', "\\|").replace(['\n', '\r'], " ");
+
+    let mut lines = Vec::with_capacity(rows.len() + 2);
+    lines.push(format!(
+        "| {} |",
+        columns
+            .iter()
+            .map(|c| escape(c))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    ));
+    lines.push(format!("| {} |", vec!["---"; columns.len()].join(" | ")));
+    for row in rows {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|c| escape(&json_value_to_cell(row.get(c))))
+            .collect();
+        lines.push(format!("| {} |", cells.join(" | ")));
+    }
+    lines.join("\n")
 }
@@ -0,0 +1,416 @@
+use datafusion::prelude::{CsvReadOptions, NdJsonReadOptions, SessionContext};
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::parser::Parser;
+use datafusion::sql::sqlparser::tokenizer::Token;
+use notify::event::{EventKind, ModifyKind};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::models::{IngestFolder, IngestResult, ParquetWriteResult, WriteOptions};
+use crate::services::convert;
+use crate::services::logging;
+use crate::services::sql::quote_identifier;
+use crate::services::tasks::TaskManager;
+use crate::services::watcher::with_watcher;
+
+/// Results kept for `results`; the oldest are dropped first.
+const MAX_INGEST_RESULTS: usize = 100;
+
+/// How long a new file's size and mtime must hold still before it is read, so
+/// files still being copied in aren't converted half-written.
+const SETTLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Called for each new file to convert, with the folder it appeared in.
+pub type IngestListener = Arc<dyn Fn(&IngestFolder, &Path) + Send + Sync>;
+
+/// Format DataFusion reads a file as, by extension.
+fn source_format(path: &Path) -> Option<&'static str> {
+    match path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .as_deref()
+    {
+        Some("csv") => Some("csv"),
+        Some("jsonl") | Some("ndjson") => Some("json"),
+        _ => None,
+    }
+}
+
+/// Parquet file `source` is converted to: its stem in the folder's destination.
+pub fn destination_path(folder: &IngestFolder, source: &Path) -> PathBuf {
+    let dir = match &folder.destination {
+        Some(dest) => PathBuf::from(dest),
+        None => source.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    dir.join(format!("{}.parquet", stem))
+}
+
+/// `ty` normalised as a SQL type for `CAST`, rejecting anything that isn't
+/// exactly one type.
+fn cast_type(column: &str, ty: &str) -> Result<String, String> {
+    let invalid = |e: String| format!("Invalid type for column '{}': {}", column, e);
+    let mut parser = Parser::new(&GenericDialect {})
+        .try_with_sql(ty)
+        .map_err(|e| invalid(e.to_string()))?;
+    let data_type = parser
+        .parse_data_type()
+        .map_err(|e| invalid(e.to_string()))?;
+    let rest = parser.peek_token().token;
+    if rest != Token::EOF {
+        return Err(invalid(format!("unexpected '{}'", rest)));
+    }
+    Ok(data_type.to_string())
+}
+
+/// Convert a CSV (with a header row) or JSON Lines file to Parquet at `dest`.
+/// Column types are inferred from the data except those in `overrides`, which
+/// are cast to the given SQL types.
+pub async fn convert_to_parquet(
+    source: &str,
+    dest: &str,
+    overrides: &BTreeMap<String, String>,
+    options: &WriteOptions,
+) -> Result<ParquetWriteResult, String> {
+    let source_path = Path::new(source);
+    let format = source_format(source_path)
+        .ok_or_else(|| format!("Not a CSV or JSON Lines file: {}", source))?;
+    // Single files are still filtered by extension, so match the file's own
+    let extension = format!(
+        ".{}",
+        source_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+    );
+
+    let ctx = SessionContext::new();
+    match format {
+        "csv" => {
+            ctx.register_csv(
+                "t",
+                source,
+                CsvReadOptions::new().file_extension(&extension),
+            )
+            .await
+        }
+        _ => {
+            ctx.register_json(
+                "t",
+                source,
+                NdJsonReadOptions::default().file_extension(&extension),
+            )
+            .await
+        }
+    }
+    .map_err(|e| format!("Failed to read {}: {}", source, e))?;
+
+    let schema = ctx
+        .table("t")
+        .await
+        .map_err(|e| e.to_string())?
+        .schema()
+        .clone();
+    let mut casts = HashMap::new();
+    for (column, ty) in overrides {
+        let field = schema
+            .fields()
+            .iter()
+            .find(|f| f.name() == column)
+            .or_else(|| {
+                schema
+                    .fields()
+                    .iter()
+                    .find(|f| f.name().eq_ignore_ascii_case(column))
+            })
+            .ok_or_else(|| format!("Column '{}' not found in {}", column, source))?;
+        casts.insert(field.name().clone(), cast_type(column, ty)?);
+    }
+    let select: Vec<String> = schema
+        .fields()
+        .iter()
+        .map(|f| {
+            let name = quote_identifier(f.name());
+            match casts.get(f.name()) {
+                Some(ty) => format!("CAST({} AS {}) AS {}", name, ty, name),
+                None => name,
+            }
+        })
+        .collect();
+
+    let stream = ctx
+        .sql(&format!("SELECT {} FROM t", select.join(", ")))
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?
+        .execute_stream()
+        .await
+        .map_err(|e| format!("Failed to execute query: {}", e))?;
+    convert::write_stream_to_parquet(stream, dest, options).await
+}
+
+fn file_stamp(path: &Path) -> Option<(u64, Option<SystemTime>)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.len(), meta.modified().ok()))
+}
+
+/// Wait until `path` stops changing, failing if it disappears meanwhile.
+async fn wait_until_settled(path: &Path) -> Result<(), String> {
+    let gone = || {
+        format!(
+            "File disappeared before it could be read: {}",
+            path.display()
+        )
+    };
+    let mut last = file_stamp(path).ok_or_else(gone)?;
+    loop {
+        tokio::time::sleep(SETTLE_INTERVAL).await;
+        let stamp = file_stamp(path).ok_or_else(gone)?;
+        if stamp == last {
+            return Ok(());
+        }
+        last = stamp;
+    }
+}
+
+/// Convert `source`, a new file in `folder`, as a task once it has finished
+/// being written, and log the outcome.
+pub async fn ingest_file(
+    tasks: &TaskManager,
+    folder: &IngestFolder,
+    source: &Path,
+) -> IngestResult {
+    let source_str = source.to_string_lossy().to_string();
+    let dest = destination_path(folder, source)
+        .to_string_lossy()
+        .to_string();
+    let name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| source_str.clone());
+
+    let result = tasks
+        .run("ingest", &format!("Ingest {}", name), |_task| async {
+            wait_until_settled(source).await?;
+            convert_to_parquet(
+                &source_str,
+                &dest,
+                &folder.schema_overrides,
+                &folder.write_options,
+            )
+            .await
+        })
+        .await;
+    match &result {
+        Ok(written) => tracing::info!(
+            "Ingested {} into {} ({} rows)",
+            source_str,
+            dest,
+            written.rows
+        ),
+        Err(e) => tracing::warn!("Failed to ingest {}: {}", source_str, logging::redact(e)),
+    }
+
+    IngestResult {
+        folder: folder.folder.clone(),
+        source: source_str,
+        dest_path: dest,
+        rows: result.as_ref().ok().map(|written| written.rows),
+        error: result.err(),
+        finished_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+#[derive(Default)]
+struct IngestState {
+    /// Canonical folder -> its settings.
+    folders: HashMap<PathBuf, IngestFolder>,
+    /// Files handed to the listener and not yet finished, so the burst of
+    /// events a single copy raises converts it once.
+    pending: HashSet<PathBuf>,
+    /// Most recent first.
+    results: VecDeque<IngestResult>,
+}
+
+/// Watches folders for new CSV and JSON Lines files to convert to Parquet.
+/// Only files that appear after watching starts are picked up.
+pub struct IngestWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    state: Arc<Mutex<IngestState>>,
+    listener: Arc<Mutex<Option<IngestListener>>>,
+}
+
+impl Default for IngestWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IngestWatcher {
+    pub fn new() -> Self {
+        Self {
+            watcher: Mutex::new(None),
+            state: Arc::new(Mutex::new(IngestState::default())),
+            listener: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn set_listener(&self, listener: impl Fn(&IngestFolder, &Path) + Send + Sync + 'static) {
+        if let Ok(mut current) = self.listener.lock() {
+            *current = Some(Arc::new(listener));
+        }
+    }
+
+    /// Start converting new files in `folder.folder`. Watching a folder again
+    /// replaces its settings.
+    pub fn watch(&self, folder: IngestFolder) -> Result<(), String> {
+        let canonical = fs::canonicalize(&folder.folder).map_err(|e| e.to_string())?;
+        if !canonical.is_dir() {
+            return Err("Path is not a directory".to_string());
+        }
+        for (column, ty) in &folder.schema_overrides {
+            cast_type(column, ty)?;
+        }
+        convert::chosen_compression(&folder.write_options, parquet::basic::Compression::SNAPPY)?;
+        if let Some(dest) = &folder.destination {
+            fs::create_dir_all(dest)
+                .map_err(|e| format!("Cannot create destination {}: {}", dest, e))?;
+        }
+
+        let is_new = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            state
+                .folders
+                .insert(canonical.clone(), folder.clone())
+                .is_none()
+        };
+        if !is_new {
+            return Ok(());
+        }
+        let registered = with_watcher(
+            &self.watcher,
+            || self.create_watcher(),
+            |watcher| {
+                watcher
+                    .watch(&canonical, RecursiveMode::NonRecursive)
+                    .map_err(|e| format!("Failed to watch {}: {}", folder.folder, e))
+            },
+        );
+        if registered.is_err() {
+            if let Ok(mut state) = self.state.lock() {
+                state.folders.remove(&canonical);
+            }
+        }
+        registered
+    }
+
+    pub fn unwatch(&self, folder: &str) -> Result<(), String> {
+        let canonical = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            let canonical = state
+                .folders
+                .iter()
+                .find(|(_, watched)| watched.folder == folder)
+                .map(|(canonical, _)| canonical.clone())
+                .ok_or_else(|| format!("Folder is not watched: {}", folder))?;
+            state.folders.remove(&canonical);
+            canonical
+        };
+
+        // The folder may already be gone, which unwatches it anyway
+        let _ = with_watcher(
+            &self.watcher,
+            || self.create_watcher(),
+            |watcher| watcher.unwatch(&canonical).map_err(|e| e.to_string()),
+        );
+        Ok(())
+    }
+
+    pub fn folders(&self) -> Result<Vec<IngestFolder>, String> {
+        let state = self.state.lock().map_err(|e| e.to_string())?;
+        let mut folders: Vec<IngestFolder> = state.folders.values().cloned().collect();
+        folders.sort_by(|a, b| a.folder.cmp(&b.folder));
+        Ok(folders)
+    }
+
+    /// Conversions finished this session, most recent first.
+    pub fn results(&self) -> Result<Vec<IngestResult>, String> {
+        let state = self.state.lock().map_err(|e| e.to_string())?;
+        Ok(state.results.iter().cloned().collect())
+    }
+
+    /// Record the outcome for a file handed to the listener, so it is picked
+    /// up again if it is replaced later.
+    pub fn finished(&self, source: &Path, result: IngestResult) {
+        if let Ok(mut state) = self.state.lock() {
+            state.pending.remove(source);
+            state.results.push_front(result);
+            state.results.truncate(MAX_INGEST_RESULTS);
+        }
+    }
+
+    fn create_watcher(&self) -> Result<RecommendedWatcher, String> {
+        let state = self.state.clone();
+        let listener = self.listener.clone();
+        notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                handle_ingest_event(&state, &listener, &event);
+            }
+        })
+        .map_err(|e| format!("Failed to start ingest watcher: {}", e))
+    }
+}
+
+fn handle_ingest_event(
+    state: &Mutex<IngestState>,
+    listener: &Mutex<Option<IngestListener>>,
+    event: &Event,
+) {
+    // Files appear either by being created in place or renamed into the folder
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))
+    ) {
+        return;
+    }
+    let arrivals: Vec<(IngestFolder, PathBuf)> = {
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        let mut arrivals = Vec::new();
+        for path in &event.paths {
+            // Hidden files are usually partial downloads or our own temp files
+            let hidden = path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+            if hidden || source_format(path).is_none() || !path.is_file() {
+                continue;
+            }
+            let Some(folder) = path
+                .parent()
+                .and_then(|dir| state.folders.get(dir))
+                .cloned()
+            else {
+                continue;
+            };
+            if state.pending.insert(path.clone()) {
+                arrivals.push((folder, path.clone()));
+            }
+        }
+        arrivals
+    };
+
+    let Some(listener) = listener.lock().ok().and_then(|l| l.clone()) else {
+        return;
+    };
+    for (folder, path) in &arrivals {
+        listener(folder, path);
+    }
+}
@@ -0,0 +1,820 @@
+use md5::Md5;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::parquet_to_arrow_schema;
+use parquet::basic::{Compression, ConvertedType, Repetition};
+use parquet::data_type::{ByteArray, FixedLenByteArray, Int96};
+use parquet::file::footer::decode_metadata;
+use parquet::file::metadata::{ColumnChunkMetaData, ParquetMetaData};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::statistics::Statistics;
+use parquet::format::PageHeader;
+use parquet::thrift::TSerializable;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use thrift::protocol::TCompactInputProtocol;
+
+use crate::models::{
+    ColumnChunkInfo, ColumnChunkVerification, ColumnIndexInfo, ColumnSize, ColumnSizes,
+    ColumnStatistics, FileHash, FileMetadataInfo, FileVerification, ParquetDiagnosis,
+    RowGroupDiagnosis, RowGroupInfo, RowGroupVerification, SchemaDiff, SchemaField,
+    SchemaFieldChange,
+};
+use crate::services::parquet::{converted_type_to_string, logical_type_to_string};
+use crate::services::tasks::TaskHandle;
+
+pub(crate) fn read_footer(path: &str) -> Result<ParquetMetaData, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = SerializedFileReader::new(file).map_err(|e| e.to_string())?;
+    Ok(reader.metadata().clone())
+}
+
+pub fn compression_to_string(compression: Compression) -> String {
+    match compression {
+        Compression::UNCOMPRESSED => "UNCOMPRESSED",
+        Compression::SNAPPY => "SNAPPY",
+        Compression::GZIP(_) => "GZIP",
+        Compression::LZO => "LZO",
+        Compression::BROTLI(_) => "BROTLI",
+        Compression::LZ4 => "LZ4",
+        Compression::ZSTD(_) => "ZSTD",
+        Compression::LZ4_RAW => "LZ4_RAW",
+    }
+    .to_string()
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Render the min/max of a column chunk as display strings. Byte arrays are
+/// decoded as UTF-8 where possible; fixed-length binaries are shown as hex.
+fn min_max_to_strings(stats: &Statistics) -> (Option<String>, Option<String>) {
+    merge_min_max(&[stats]).unzip()
+}
+
+/// Fold typed (min, max) pairs into an overall range. Returns `None` as soon as
+/// one input lacks a range, since a partial range would be misleading.
+fn fold_min_max<T: PartialOrd>(values: impl Iterator<Item = Option<(T, T)>>) -> Option<(T, T)> {
+    let mut merged: Option<(T, T)> = None;
+    for value in values {
+        let (min, max) = value?;
+        merged = Some(match merged {
+            None => (min, max),
+            Some((cur_min, cur_max)) => (
+                if min < cur_min { min } else { cur_min },
+                if max > cur_max { max } else { cur_max },
+            ),
+        });
+    }
+    merged
+}
+
+macro_rules! merge_variant {
+    ($stats:expr, $variant:ident, $key:expr, $fmt:expr) => {
+        fold_min_max($stats.iter().map(|s| match s {
+            Statistics::$variant(v) if v.has_min_max_set() => Some(($key(v.min()), $key(v.max()))),
+            _ => None,
+        }))
+        .map(|(min, max)| ($fmt(min), $fmt(max)))
+    };
+}
+
+/// Overall min/max across several chunks of the same column, as display strings.
+fn merge_min_max(stats: &[&Statistics]) -> Option<(String, String)> {
+    let utf8 = |v: Vec<u8>| String::from_utf8_lossy(&v).to_string();
+
+    match stats.first()? {
+        Statistics::Boolean(_) => {
+            merge_variant!(stats, Boolean, |v: &bool| *v, |v: bool| v.to_string())
+        }
+        Statistics::Int32(_) => merge_variant!(stats, Int32, |v: &i32| *v, |v: i32| v.to_string()),
+        Statistics::Int64(_) => merge_variant!(stats, Int64, |v: &i64| *v, |v: i64| v.to_string()),
+        Statistics::Int96(_) => merge_variant!(stats, Int96, |v: &Int96| v.to_nanos(), |v: i64| v
+            .to_string()),
+        Statistics::Float(_) => merge_variant!(stats, Float, |v: &f32| *v, |v: f32| v.to_string()),
+        Statistics::Double(_) => {
+            merge_variant!(stats, Double, |v: &f64| *v, |v: f64| v.to_string())
+        }
+        Statistics::ByteArray(_) => {
+            merge_variant!(stats, ByteArray, |v: &ByteArray| v.data().to_vec(), utf8)
+        }
+        Statistics::FixedLenByteArray(_) => merge_variant!(
+            stats,
+            FixedLenByteArray,
+            |v: &FixedLenByteArray| v.data().to_vec(),
+            |v: Vec<u8>| bytes_to_hex(&v)
+        ),
+    }
+}
+
+pub fn statistics_to_model(stats: &Statistics) -> ColumnStatistics {
+    let (min, max) = min_max_to_strings(stats);
+    ColumnStatistics {
+        min,
+        max,
+        null_count: Some(stats.null_count()),
+        distinct_count: stats.distinct_count(),
+    }
+}
+
+/// Combine the statistics of every chunk of one column into file-level values.
+/// Null counts are summed; min/max are only reported when every chunk has them.
+/// Distinct counts cannot be combined, so they are only kept for single-chunk files.
+pub fn aggregate_statistics(chunks: &[&ColumnChunkMetaData]) -> Option<ColumnStatistics> {
+    let stats: Option<Vec<&Statistics>> = chunks.iter().map(|c| c.statistics()).collect();
+    let stats = stats.filter(|s| !s.is_empty())?;
+
+    let (min, max) = merge_min_max(&stats).unzip();
+    let distinct_count = match stats.as_slice() {
+        [single] => single.distinct_count(),
+        _ => None,
+    };
+
+    Some(ColumnStatistics {
+        min,
+        max,
+        null_count: Some(stats.iter().map(|s| s.null_count()).sum()),
+        distinct_count,
+    })
+}
+
+fn column_chunk_info(column: &ColumnChunkMetaData) -> ColumnChunkInfo {
+    ColumnChunkInfo {
+        column: column.column_path().string(),
+        physical_type: column.column_type().to_string(),
+        compression: compression_to_string(column.compression()),
+        encodings: column.encodings().iter().map(|e| e.to_string()).collect(),
+        num_values: column.num_values(),
+        compressed_size: column.compressed_size(),
+        uncompressed_size: column.uncompressed_size(),
+        statistics: column.statistics().map(statistics_to_model),
+    }
+}
+
+pub fn get_row_group_info(path: &str) -> Result<Vec<RowGroupInfo>, String> {
+    let metadata = read_footer(path)?;
+
+    Ok(metadata
+        .row_groups()
+        .iter()
+        .enumerate()
+        .map(|(index, row_group)| RowGroupInfo {
+            index,
+            num_rows: row_group.num_rows(),
+            compressed_size: row_group.compressed_size(),
+            uncompressed_size: row_group.total_byte_size(),
+            columns: row_group.columns().iter().map(column_chunk_info).collect(),
+        })
+        .collect())
+}
+
+/// Compressed and uncompressed bytes of every leaf column, summed over row groups.
+pub fn column_sizes(path: &str) -> Result<ColumnSizes, String> {
+    let metadata = read_footer(path)?;
+    let leaves = metadata.file_metadata().schema_descr().columns();
+
+    let mut sizes: Vec<(i64, i64)> = vec![(0, 0); leaves.len()];
+    for row_group in metadata.row_groups() {
+        for (size, chunk) in sizes.iter_mut().zip(row_group.columns()) {
+            size.0 += chunk.compressed_size();
+            size.1 += chunk.uncompressed_size();
+        }
+    }
+
+    let total_compressed_size: i64 = sizes.iter().map(|s| s.0).sum();
+    let total_uncompressed_size: i64 = sizes.iter().map(|s| s.1).sum();
+    let percent = |part: i64, total: i64| {
+        if total > 0 {
+            part as f64 / total as f64 * 100.0
+        } else {
+            0.0
+        }
+    };
+
+    let mut columns: Vec<ColumnSize> = leaves
+        .iter()
+        .zip(sizes)
+        .map(|(leaf, (compressed, uncompressed))| ColumnSize {
+            column: leaf.path().string(),
+            compressed_size: compressed,
+            uncompressed_size: uncompressed,
+            compressed_percent: percent(compressed, total_compressed_size),
+            uncompressed_percent: percent(uncompressed, total_uncompressed_size),
+        })
+        .collect();
+    columns.sort_by_key(|c| std::cmp::Reverse(c.compressed_size));
+
+    Ok(ColumnSizes {
+        total_compressed_size,
+        total_uncompressed_size,
+        columns,
+    })
+}
+
+pub fn get_file_metadata(path: &str) -> Result<FileMetadataInfo, String> {
+    let metadata = read_footer(path)?;
+    let file_metadata = metadata.file_metadata();
+
+    let key_value_metadata = file_metadata
+        .key_value_metadata()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|kv| (kv.key.clone(), kv.value.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(FileMetadataInfo {
+        created_by: file_metadata.created_by().map(|s| s.to_string()),
+        format_version: file_metadata.version(),
+        num_rows: file_metadata.num_rows(),
+        num_row_groups: metadata.num_row_groups(),
+        key_value_metadata,
+    })
+}
+
+/// Report which column chunks carry a column index, offset index and bloom filter.
+/// Only the footer is read; lengths are `None` when the writer did not record them.
+pub fn get_index_info(path: &str) -> Result<Vec<ColumnIndexInfo>, String> {
+    let metadata = read_footer(path)?;
+
+    Ok(metadata
+        .row_groups()
+        .iter()
+        .enumerate()
+        .flat_map(|(row_group, rg)| {
+            rg.columns().iter().map(move |column| ColumnIndexInfo {
+                row_group,
+                column: column.column_path().string(),
+                has_column_index: column.column_index_offset().is_some(),
+                column_index_length: column.column_index_length(),
+                has_offset_index: column.offset_index_offset().is_some(),
+                offset_index_length: column.offset_index_length(),
+                has_bloom_filter: column.bloom_filter_offset().is_some(),
+                bloom_filter_length: column.bloom_filter_length(),
+            })
+        })
+        .collect())
+}
+
+const PARQUET_MAGIC: &[u8; 4] = b"PAR1";
+
+/// Try to decode every row of one row group, returning the first error.
+fn check_row_group(path: &str, index: usize) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .map_err(|e| e.to_string())?
+        .with_row_groups(vec![index])
+        .build()
+        .map_err(|e| e.to_string())?;
+    for batch in reader {
+        batch.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Decode the row group and return how many rows it actually holds.
+fn count_row_group_rows(path: &str, index: usize) -> Result<i64, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .map_err(|e| e.to_string())?
+        .with_row_groups(vec![index])
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut rows = 0;
+    for batch in reader {
+        rows += batch.map_err(|e| e.to_string())?.num_rows() as i64;
+    }
+    Ok(rows)
+}
+
+/// Bytes of page data read per step while verifying.
+const VERIFY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Tracks how many bytes have been read through it, so page offsets are
+/// known while thrift pulls headers off the stream.
+struct CountingReader<R> {
+    inner: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+/// Walk the raw page headers of one column chunk, checking they parse and
+/// stay inside the chunk, that their value counts add up to the chunk
+/// metadata, and optionally that each page's CRC matches its data.
+fn verify_column_chunk(
+    file: &mut File,
+    file_size: u64,
+    row_group: usize,
+    column: &ColumnChunkMetaData,
+    verify_checksums: bool,
+    crc_checked: &mut usize,
+) -> ColumnChunkVerification {
+    let mut result = ColumnChunkVerification {
+        row_group,
+        column: column.column_path().string(),
+        pages: 0,
+        page_values: 0,
+        expected_values: column.num_values(),
+        crc_mismatches: 0,
+        errors: Vec::new(),
+    };
+
+    let (start, length) = match column_chunk_range(column, file_size) {
+        Ok(range) => range,
+        Err(e) => {
+            result.errors.push(e);
+            return result;
+        }
+    };
+    if let Err(e) = file.seek(SeekFrom::Start(start)) {
+        result
+            .errors
+            .push(format!("Failed to read column chunk: {}", e));
+        return result;
+    }
+
+    // Pages are streamed through a fixed buffer rather than reading the whole
+    // chunk, whose size comes from metadata that may be corrupt
+    let mut reader = CountingReader {
+        inner: BufReader::new(Read::take(&mut *file, length)),
+        count: 0,
+    };
+    let mut buf = vec![0u8; VERIFY_BUFFER_SIZE];
+    while reader.count < length {
+        let page_start = reader.count;
+        let header = {
+            let mut protocol = TCompactInputProtocol::new(&mut reader);
+            PageHeader::read_from_in_protocol(&mut protocol)
+        };
+        let header = match header {
+            Ok(header) => header,
+            Err(e) => {
+                result.errors.push(format!(
+                    "Page header at offset {} is corrupt: {}",
+                    start + page_start,
+                    e
+                ));
+                return result;
+            }
+        };
+
+        let page_size = header.compressed_page_size;
+        if page_size < 0 || reader.count + page_size as u64 > length {
+            result.errors.push(format!(
+                "Page at offset {} claims {} bytes, past the end of the chunk",
+                start + page_start,
+                page_size
+            ));
+            return result;
+        }
+        result.pages += 1;
+
+        if let Some(page) = &header.data_page_header {
+            result.page_values += page.num_values as i64;
+        } else if let Some(page) = &header.data_page_header_v2 {
+            result.page_values += page.num_values as i64;
+        }
+
+        let expected_crc = header.crc.filter(|_| verify_checksums);
+        let mut crc = crc32fast::Hasher::new();
+        let mut remaining = page_size as usize;
+        while remaining > 0 {
+            let step = remaining.min(buf.len());
+            if let Err(e) = reader.read_exact(&mut buf[..step]) {
+                result
+                    .errors
+                    .push(format!("Failed to read column chunk: {}", e));
+                return result;
+            }
+            if expected_crc.is_some() {
+                crc.update(&buf[..step]);
+            }
+            remaining -= step;
+        }
+
+        if let Some(expected) = expected_crc {
+            *crc_checked += 1;
+            if crc.finalize() != expected as u32 {
+                result.crc_mismatches += 1;
+                result.errors.push(format!(
+                    "CRC mismatch in page at offset {}",
+                    start + page_start
+                ));
+            }
+        }
+    }
+
+    if result.page_values != result.expected_values {
+        result.errors.push(format!(
+            "Pages hold {} values but the chunk metadata records {}",
+            result.page_values, result.expected_values
+        ));
+    }
+    result
+}
+
+/// Verify a file end to end: decoded row counts per row group against the
+/// footer, page headers of every column chunk, and with `verify_checksums`
+/// the CRC of every page that carries one. Meant for checking copies that
+/// may have been damaged in transit, so it reads the whole file.
+pub fn verify_file(
+    path: &str,
+    verify_checksums: bool,
+    task: Option<&TaskHandle>,
+) -> Result<FileVerification, String> {
+    let metadata = read_footer(path)?;
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let file_size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let footer_rows = metadata.file_metadata().num_rows();
+    let mut report = FileVerification {
+        passed: true,
+        footer_rows,
+        row_group_rows: metadata.row_groups().iter().map(|rg| rg.num_rows()).sum(),
+        row_groups: Vec::new(),
+        pages_checked: 0,
+        crc_checked: 0,
+        crc_mismatches: 0,
+        failed_chunks: Vec::new(),
+        issues: Vec::new(),
+    };
+    if report.row_group_rows != footer_rows {
+        report.issues.push(format!(
+            "Row groups hold {} rows but the footer records {}",
+            report.row_group_rows, footer_rows
+        ));
+    }
+
+    let total = metadata.num_row_groups();
+    for (index, row_group) in metadata.row_groups().iter().enumerate() {
+        if let Some(task) = task {
+            task.check_cancelled()?;
+            task.set_progress(index as f64 / total as f64, None);
+        }
+
+        let mut chunks_ok = true;
+        for column in row_group.columns() {
+            let chunk = verify_column_chunk(
+                &mut file,
+                file_size,
+                index,
+                column,
+                verify_checksums,
+                &mut report.crc_checked,
+            );
+            report.pages_checked += chunk.pages;
+            report.crc_mismatches += chunk.crc_mismatches;
+            if !chunk.errors.is_empty() {
+                chunks_ok = false;
+                report.issues.extend(
+                    chunk
+                        .errors
+                        .iter()
+                        .map(|e| format!("Row group {}, column {}: {}", index, chunk.column, e)),
+                );
+                report.failed_chunks.push(chunk);
+            }
+        }
+
+        let expected_rows = row_group.num_rows();
+        // The decoder panics on chunks with invalid offsets, already reported above
+        let decodable = row_group
+            .columns()
+            .iter()
+            .all(|column| column_chunk_range(column, file_size).is_ok());
+        let decoded = if decodable {
+            count_row_group_rows(path, index)
+        } else {
+            Err("Column chunk offsets are invalid".to_string())
+        };
+        let error = match &decoded {
+            Ok(rows) if *rows != expected_rows => Some(format!(
+                "Decoded {} rows but the footer records {}",
+                rows, expected_rows
+            )),
+            Ok(_) => None,
+            Err(e) => Some(format!("Failed to decode: {}", e)),
+        };
+        if let Some(error) = &error {
+            report
+                .issues
+                .push(format!("Row group {}: {}", index, error));
+        }
+        report.row_groups.push(RowGroupVerification {
+            index,
+            expected_rows,
+            actual_rows: decoded.ok(),
+            passed: error.is_none() && chunks_ok,
+            error,
+        });
+    }
+
+    report.passed = report.issues.is_empty();
+    Ok(report)
+}
+
+/// Bytes read per step while hashing a file.
+const HASH_CHUNK_SIZE: usize = 1024 * 1024;
+
+fn digest_file<D: Digest>(
+    file: &mut File,
+    size: u64,
+    task: Option<&TaskHandle>,
+) -> Result<String, String> {
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; HASH_CHUNK_SIZE];
+    let mut read = 0u64;
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        read += n as u64;
+        if let Some(task) = task {
+            task.check_cancelled()?;
+            task.set_progress(read as f64 / size.max(1) as f64, None);
+        }
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Hash the whole of `path` with "md5" or "sha256", for checking extracts
+/// against the checksums in an ETL manifest. Works on any file, not just
+/// Parquet.
+pub fn hash_file(
+    path: &str,
+    algorithm: &str,
+    task: Option<&TaskHandle>,
+) -> Result<FileHash, String> {
+    let algorithm = algorithm.to_lowercase().replace('-', "");
+    let mut file = File::open(path).map_err(|e| format!("Cannot open {}: {}", path, e))?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let hash = match algorithm.as_str() {
+        "md5" => digest_file::<Md5>(&mut file, size, task)?,
+        "sha256" => digest_file::<Sha256>(&mut file, size, task)?,
+        other => return Err(format!("Unsupported hash algorithm: {}", other)),
+    };
+    Ok(FileHash {
+        path: path.to_string(),
+        algorithm,
+        hash,
+        size,
+    })
+}
+
+/// Byte offset and length of a column chunk, validated against `limit` (the
+/// end of the region chunks may occupy). `ColumnChunkMetaData::byte_range`
+/// asserts instead, which panics on exactly the files worth diagnosing.
+fn column_chunk_range(column: &ColumnChunkMetaData, limit: u64) -> Result<(u64, u64), String> {
+    let name = column.column_path().string();
+    let start = column
+        .dictionary_page_offset()
+        .unwrap_or_else(|| column.data_page_offset());
+    let length = column.compressed_size();
+    let start = u64::try_from(start)
+        .map_err(|_| format!("Column {} starts at negative offset {}", name, start))?;
+    let length = u64::try_from(length)
+        .map_err(|_| format!("Column {} has negative size {}", name, length))?;
+    match start.checked_add(length) {
+        Some(end) if end <= limit => Ok((start, length)),
+        _ => Err(format!(
+            "Column {} ({} bytes at offset {}) extends beyond the data region",
+            name, length, start
+        )),
+    }
+}
+
+/// Check a possibly corrupt file layer by layer: magic bytes, footer length,
+/// thrift metadata, then whether each row group decodes, so it is clear what is
+/// broken and which row groups can still be salvaged.
+pub fn diagnose_parquet(path: &str) -> Result<ParquetDiagnosis, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let file_size = file.metadata().map_err(|e| e.to_string())?.len();
+
+    let mut report = ParquetDiagnosis {
+        file_size,
+        header_magic_ok: false,
+        footer_magic_ok: false,
+        footer_length: None,
+        metadata_ok: false,
+        metadata_error: None,
+        row_groups: Vec::new(),
+        salvageable_rows: 0,
+        total_rows: 0,
+        issues: Vec::new(),
+    };
+
+    // Smallest valid file: header magic + footer length + footer magic
+    if file_size < 12 {
+        report.issues.push(format!(
+            "File is too small to be Parquet ({} bytes)",
+            file_size
+        ));
+        return Ok(report);
+    }
+
+    let mut header = [0u8; 4];
+    file.read_exact(&mut header).map_err(|e| e.to_string())?;
+    report.header_magic_ok = &header == PARQUET_MAGIC;
+    if !report.header_magic_ok {
+        report
+            .issues
+            .push("Missing PAR1 magic at start of file".to_string());
+    }
+
+    let mut tail = [0u8; 8];
+    file.seek(SeekFrom::End(-8)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut tail).map_err(|e| e.to_string())?;
+    report.footer_magic_ok = &tail[4..] == PARQUET_MAGIC;
+    if !report.footer_magic_ok {
+        report
+            .issues
+            .push("Missing PAR1 magic at end of file (file may be truncated)".to_string());
+        return Ok(report);
+    }
+
+    let footer_length = u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]);
+    report.footer_length = Some(footer_length);
+    if footer_length as u64 + 12 > file_size {
+        report.issues.push(format!(
+            "Footer length {} exceeds file size {}",
+            footer_length, file_size
+        ));
+        return Ok(report);
+    }
+
+    let mut footer = vec![0u8; footer_length as usize];
+    file.seek(SeekFrom::End(-8 - footer_length as i64))
+        .map_err(|e| e.to_string())?;
+    file.read_exact(&mut footer).map_err(|e| e.to_string())?;
+    let metadata = match decode_metadata(&footer) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            report.metadata_error = Some(e.to_string());
+            report
+                .issues
+                .push(format!("Footer metadata could not be parsed: {}", e));
+            return Ok(report);
+        }
+    };
+    report.metadata_ok = true;
+    report.total_rows = metadata.file_metadata().num_rows();
+
+    let data_end = file_size - 8 - footer_length as u64;
+    let mut first_row = 0;
+    for (index, row_group) in metadata.row_groups().iter().enumerate() {
+        // Column chunks with bad offsets cannot be read, and the reader panics on some
+        let bad_chunk = row_group
+            .columns()
+            .iter()
+            .find_map(|column| column_chunk_range(column, data_end).err());
+        let result = match bad_chunk {
+            Some(e) => Err(e),
+            None => check_row_group(path, index),
+        };
+
+        if let Err(e) = &result {
+            report
+                .issues
+                .push(format!("Row group {} is unreadable: {}", index, e));
+        } else {
+            report.salvageable_rows += row_group.num_rows();
+        }
+        report.row_groups.push(RowGroupDiagnosis {
+            index,
+            num_rows: row_group.num_rows(),
+            first_row,
+            readable: result.is_ok(),
+            error: result.err(),
+        });
+        first_row += row_group.num_rows();
+    }
+
+    Ok(report)
+}
+
+/// Top-level fields of a file with both their Arrow and Parquet types.
+fn schema_fields(path: &str) -> Result<Vec<SchemaField>, String> {
+    let footer = read_footer(path)?;
+    let file_metadata = footer.file_metadata();
+    let arrow_schema = parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(file_metadata
+        .schema()
+        .get_fields()
+        .iter()
+        .map(|field| {
+            let info = field.get_basic_info();
+            let logical_type = match info.logical_type() {
+                Some(lt) => Some(logical_type_to_string(&lt)),
+                None if info.converted_type() != ConvertedType::NONE => {
+                    Some(converted_type_to_string(info.converted_type()))
+                }
+                None => None,
+            };
+            let data_type = arrow_schema
+                .field_with_name(field.name())
+                .map(|f| f.data_type().to_string())
+                .unwrap_or_default();
+            SchemaField {
+                name: field.name().to_string(),
+                data_type,
+                physical_type: if field.is_primitive() {
+                    format!("{:?}", field.get_physical_type())
+                } else {
+                    "GROUP".to_string()
+                },
+                logical_type,
+                nullable: !info.has_repetition() || info.repetition() != Repetition::REQUIRED,
+            }
+        })
+        .collect())
+}
+
+/// Compare the top-level schemas of two files, matching columns by name.
+pub fn diff_schemas(path_a: &str, path_b: &str) -> Result<SchemaDiff, String> {
+    let before = schema_fields(path_a)?;
+    let after = schema_fields(path_b)?;
+
+    let removed: Vec<SchemaField> = before
+        .iter()
+        .filter(|a| !after.iter().any(|b| b.name == a.name))
+        .cloned()
+        .collect();
+    let added: Vec<SchemaField> = after
+        .iter()
+        .filter(|b| !before.iter().any(|a| a.name == b.name))
+        .cloned()
+        .collect();
+
+    let mut changed = Vec::new();
+    let mut unchanged = 0;
+    for a in &before {
+        let Some(b) = after.iter().find(|b| b.name == a.name) else {
+            continue;
+        };
+        if a == b {
+            unchanged += 1;
+            continue;
+        }
+        let mut changes = Vec::new();
+        if a.data_type != b.data_type {
+            changes.push(format!("type: {} -> {}", a.data_type, b.data_type));
+        }
+        if a.physical_type != b.physical_type {
+            changes.push(format!(
+                "physical type: {} -> {}",
+                a.physical_type, b.physical_type
+            ));
+        }
+        if a.logical_type != b.logical_type {
+            let show = |t: &Option<String>| t.clone().unwrap_or_else(|| "none".to_string());
+            changes.push(format!(
+                "logical type: {} -> {}",
+                show(&a.logical_type),
+                show(&b.logical_type)
+            ));
+        }
+        if a.nullable != b.nullable {
+            changes.push(format!("nullable: {} -> {}", a.nullable, b.nullable));
+        }
+        changed.push(SchemaFieldChange {
+            name: a.name.clone(),
+            before: a.clone(),
+            after: b.clone(),
+            changes,
+        });
+    }
+
+    // Column order matters to positional readers, so a pure reorder isn't identical
+    let same_order = before
+        .iter()
+        .map(|f| &f.name)
+        .eq(after.iter().map(|f| &f.name));
+    Ok(SchemaDiff {
+        identical: added.is_empty() && removed.is_empty() && changed.is_empty() && same_order,
+        added,
+        removed,
+        changed,
+        unchanged,
+    })
+}
@@ -0,0 +1,256 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::models::{Job, JobRun, JobSpec, JobTrigger, WriteOptions};
+use crate::services::parquet::{execute_sql_stream, ParquetCache};
+use crate::services::tasks::{TaskHandle, TaskManager};
+use crate::services::{convert, export, store};
+
+const JOBS_FILE: &str = "jobs.json";
+const RUNS_FILE: &str = "job_runs.json";
+
+/// Runs kept in the history; the oldest are dropped first.
+const MAX_JOB_RUNS: usize = 200;
+
+/// Shortest interval a job may repeat at.
+pub const MIN_INTERVAL_SECS: u64 = 60;
+
+/// How often the scheduler looks for due jobs.
+pub const SCHEDULE_TICK: Duration = Duration::from_secs(15);
+
+const FORMATS: [&str; 5] = ["csv", "json", "jsonl", "ndjson", "parquet"];
+
+fn load<T: DeserializeOwned>(data_dir: &Path, file: &str) -> Result<Vec<T>, String> {
+    Ok(store::read_json(&data_dir.join(file), file)?.unwrap_or_default())
+}
+
+fn store<T: Serialize>(data_dir: &Path, file: &str, items: &[T]) -> Result<(), String> {
+    store::atomic_write_json(&data_dir.join(file), items)
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+fn validate(spec: &JobSpec) -> Result<(), String> {
+    if spec.name.trim().is_empty() {
+        return Err("Job name must not be empty".to_string());
+    }
+    if spec.destination.trim().is_empty() {
+        return Err("Job destination must not be empty".to_string());
+    }
+    if !FORMATS.contains(&spec.format.to_lowercase().as_str()) {
+        return Err(format!("Unsupported job format: {}", spec.format));
+    }
+    if spec
+        .interval_secs
+        .is_some_and(|secs| secs < MIN_INTERVAL_SECS)
+    {
+        return Err(format!(
+            "Job interval must be at least {} seconds",
+            MIN_INTERVAL_SECS
+        ));
+    }
+    Ok(())
+}
+
+/// Save a new job, or replace the definition of job `id`.
+pub fn save_job(data_dir: &Path, id: Option<u64>, mut spec: JobSpec) -> Result<Job, String> {
+    validate(&spec)?;
+    spec.name = spec.name.trim().to_string();
+    spec.query = spec.query.filter(|q| !q.trim().is_empty());
+
+    store::locked(|| {
+        let mut jobs: Vec<Job> = load(data_dir, JOBS_FILE)?;
+        let timestamp = now();
+        let job = match id {
+            Some(id) => {
+                let job = jobs
+                    .iter_mut()
+                    .find(|j| j.id == id)
+                    .ok_or_else(|| format!("Job not found: {}", id))?;
+                job.spec = spec;
+                job.updated_at = timestamp;
+                job.clone()
+            }
+            None => {
+                let job = Job {
+                    id: jobs.iter().map(|j| j.id).max().unwrap_or(0) + 1,
+                    spec,
+                    created_at: timestamp.clone(),
+                    updated_at: timestamp,
+                };
+                jobs.push(job.clone());
+                job
+            }
+        };
+        store(data_dir, JOBS_FILE, &jobs)?;
+        Ok(job)
+    })
+}
+
+pub fn list_jobs(data_dir: &Path) -> Result<Vec<Job>, String> {
+    let mut jobs: Vec<Job> = load(data_dir, JOBS_FILE)?;
+    jobs.sort_by_key(|j| j.spec.name.to_lowercase());
+    Ok(jobs)
+}
+
+pub fn get_job(data_dir: &Path, id: u64) -> Result<Job, String> {
+    load::<Job>(data_dir, JOBS_FILE)?
+        .into_iter()
+        .find(|j| j.id == id)
+        .ok_or_else(|| format!("Job not found: {}", id))
+}
+
+/// Delete job `id`; its past runs stay in the history.
+pub fn delete_job(data_dir: &Path, id: u64) -> Result<(), String> {
+    store::locked(|| {
+        let mut jobs: Vec<Job> = load(data_dir, JOBS_FILE)?;
+        let before = jobs.len();
+        jobs.retain(|j| j.id != id);
+        if jobs.len() == before {
+            return Err(format!("Job not found: {}", id));
+        }
+        store(data_dir, JOBS_FILE, &jobs)
+    })
+}
+
+/// Past runs, most recent first, optionally only those of job `id`.
+pub fn job_runs(data_dir: &Path, id: Option<u64>) -> Result<Vec<JobRun>, String> {
+    let mut runs: Vec<JobRun> = load(data_dir, RUNS_FILE)?;
+    runs.retain(|r| id.is_none_or(|id| r.job_id == id));
+    runs.reverse();
+    Ok(runs)
+}
+
+fn record_run(data_dir: &Path, run: &JobRun) -> Result<(), String> {
+    store::locked(|| {
+        let mut runs: Vec<JobRun> = load(data_dir, RUNS_FILE)?;
+        runs.push(run.clone());
+        let excess = runs.len().saturating_sub(MAX_JOB_RUNS);
+        runs.drain(..excess);
+        store(data_dir, RUNS_FILE, &runs)
+    })
+}
+
+/// Write the job's result to its destination, returning the rows written.
+/// Without a query the whole source is written.
+async fn execute(cache: &ParquetCache, spec: &JobSpec, task: &TaskHandle) -> Result<i64, String> {
+    let query = spec.query.as_deref().unwrap_or("SELECT * FROM t");
+    let format = spec.format.to_lowercase();
+    if format == "parquet" {
+        let stream = execute_sql_stream(cache, &spec.source, query).await?;
+        let result =
+            convert::write_stream_to_parquet(stream, &spec.destination, &WriteOptions::default())
+                .await?;
+        return Ok(result.rows);
+    }
+    let rows = export::export_query(
+        cache,
+        &spec.source,
+        query,
+        &spec.destination,
+        &format,
+        Some(task),
+    )
+    .await?;
+    Ok(rows as i64)
+}
+
+/// Run `job` as a task and add the outcome to the run history. A failed run
+/// is recorded and returned like a successful one, with its error.
+pub async fn run_job(
+    cache: &ParquetCache,
+    tasks: &TaskManager,
+    data_dir: &Path,
+    job: &Job,
+    trigger: JobTrigger,
+) -> Result<JobRun, String> {
+    let started_at = now();
+    let label = format!("Job {}", job.spec.name);
+    let result = tasks
+        .run("job", &label, |task| async move {
+            execute(cache, &job.spec, &task).await
+        })
+        .await;
+
+    let run = JobRun {
+        job_id: job.id,
+        job_name: job.spec.name.clone(),
+        trigger,
+        started_at,
+        finished_at: now(),
+        rows: result.as_ref().ok().copied(),
+        error: result.err(),
+    };
+    record_run(data_dir, &run)?;
+    Ok(run)
+}
+
+/// Tracks when jobs last ran in this session and which are running, so
+/// repeating jobs run once per interval and never overlap themselves.
+pub struct JobScheduler {
+    last_started: Mutex<HashMap<u64, Instant>>,
+    running: Mutex<HashSet<u64>>,
+}
+
+impl Default for JobScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobScheduler {
+    pub fn new() -> Self {
+        Self {
+            last_started: Mutex::new(HashMap::new()),
+            running: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Mark job `id` as running; `false` when it already is.
+    pub fn try_start(&self, id: u64) -> bool {
+        let Ok(mut running) = self.running.lock() else {
+            return false;
+        };
+        if !running.insert(id) {
+            return false;
+        }
+        if let Ok(mut last_started) = self.last_started.lock() {
+            last_started.insert(id, Instant::now());
+        }
+        true
+    }
+
+    pub fn finished(&self, id: u64) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(&id);
+        }
+    }
+
+    /// Repeating jobs whose interval has passed since they last started, each
+    /// marked as running. A job's first interval counts from when the
+    /// scheduler first sees it, so opening the app doesn't start every job.
+    pub fn take_due(&self, jobs: Vec<Job>) -> Vec<Job> {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        for job in jobs {
+            let Some(interval) = job.spec.interval_secs else {
+                continue;
+            };
+            let last = match self.last_started.lock() {
+                Ok(mut last_started) => *last_started.entry(job.id).or_insert(now),
+                Err(_) => continue,
+            };
+            if now.duration_since(last) >= Duration::from_secs(interval) && self.try_start(job.id) {
+                due.push(job);
+            }
+        }
+        due
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::models::OpenFileRequest;
+
+type OpenListener = Arc<dyn Fn(&OpenFileRequest) + Send + Sync>;
+
+/// Files named on a command line, resolved against `cwd`. The program name,
+/// flags and paths that don't exist are skipped; `file://` URLs (passed by
+/// some Linux desktops) are accepted too.
+pub fn paths_from_args(args: &[String], cwd: &Path) -> Vec<String> {
+    args.iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .filter_map(|arg| {
+            let path = match url::Url::parse(arg) {
+                Ok(url) if url.scheme() == "file" => url.to_file_path().ok()?,
+                _ => cwd.join(arg),
+            };
+            path.exists().then(|| path.to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// The local path of a `file://` URL, as delivered by macOS open events.
+pub fn path_from_url(url: &url::Url) -> Option<String> {
+    if url.scheme() != "file" {
+        return None;
+    }
+    url.to_file_path()
+        .ok()
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+/// URL scheme of links into the app, e.g. `parqsee://open?path=/data/x.parquet`.
+pub const DEEP_LINK_SCHEME: &str = "parqsee";
+
+/// Parse `parqsee://open?path=...&query=...`. `path` must name an existing
+/// file or directory; `query` is optional SQL to run once it is open. The
+/// caller checks both, since a link may come from any web page.
+pub fn parse_deep_link(url: &url::Url) -> Result<OpenFileRequest, String> {
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(format!("Unsupported link scheme: {}", url.scheme()));
+    }
+    match url.host_str() {
+        Some("open") => {}
+        other => {
+            return Err(format!(
+                "Unsupported link action: {}",
+                other.unwrap_or_default()
+            ))
+        }
+    }
+
+    let mut path = None;
+    let mut query = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "path" => path = Some(value.into_owned()),
+            "query" => query = Some(value.trim().to_string()).filter(|q| !q.is_empty()),
+            _ => {}
+        }
+    }
+    let path = path
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "Link has no path".to_string())?;
+    if !Path::new(&path).exists() {
+        return Err(format!("File not found: {}", path));
+    }
+    Ok(OpenFileRequest { path, query })
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// Whether the frontend is listening; until then requests are held.
+    ready: bool,
+    pending: Vec<OpenFileRequest>,
+    listener: Option<OpenListener>,
+}
+
+/// Requests to open files that arrive from outside the window. Those arriving
+/// before the frontend has loaded (launch arguments, an early "Open With") are
+/// held until it collects them with `take_pending`; later ones go straight to
+/// the listener.
+#[derive(Default)]
+pub struct LaunchQueue {
+    state: Mutex<QueueState>,
+}
+
+impl LaunchQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install the callback that forwards requests, typically to the frontend.
+    pub fn set_listener(&self, listener: impl Fn(&OpenFileRequest) + Send + Sync + 'static) {
+        if let Ok(mut state) = self.state.lock() {
+            state.listener = Some(Arc::new(listener));
+        }
+    }
+
+    pub fn dispatch(&self, request: OpenFileRequest) {
+        let listener = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            match (&state.listener, state.ready) {
+                (Some(listener), true) => listener.clone(),
+                _ => {
+                    state.pending.push(request);
+                    return;
+                }
+            }
+        };
+        listener(&request);
+    }
+
+    /// Requests held so far. The frontend calls this once it is listening;
+    /// from then on requests are delivered as they arrive.
+    pub fn take_pending(&self) -> Vec<OpenFileRequest> {
+        let Ok(mut state) = self.state.lock() else {
+            return Vec::new();
+        };
+        state.ready = true;
+        std::mem::take(&mut state.pending)
+    }
+}
@@ -0,0 +1,328 @@
+use arrow::datatypes::DataType;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::models::{
+    ColumnLineage, ExtendedMetadata, PandasMetadata, PandasRangeIndex, SparkMetadata,
+};
+use crate::services::inspect;
+use crate::services::schema::arrow_schema;
+
+const PANDAS_KEY: &str = "pandas";
+const SPARK_SCHEMA_KEY: &str = "org.apache.spark.sql.parquet.row.metadata";
+const SPARK_VERSION_KEY: &str = "org.apache.spark.version";
+const SPARK_LEGACY_KEYS: [&str; 2] = [
+    "org.apache.spark.legacyDateTime",
+    "org.apache.spark.legacyINT96",
+];
+
+#[derive(Deserialize)]
+struct PandasJson {
+    #[serde(default)]
+    index_columns: Vec<Value>,
+    #[serde(default)]
+    columns: Vec<PandasColumnJson>,
+    creator: Option<PandasCreatorJson>,
+    pandas_version: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PandasColumnJson {
+    #[serde(default)]
+    name: Value,
+    field_name: Option<String>,
+    pandas_type: Option<String>,
+    numpy_type: Option<String>,
+    metadata: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct PandasCreatorJson {
+    library: String,
+    version: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SparkStructJson {
+    fields: Vec<SparkFieldJson>,
+}
+
+#[derive(Deserialize)]
+struct SparkFieldJson {
+    name: String,
+    #[serde(rename = "type")]
+    data_type: Value,
+    #[serde(default = "default_nullable")]
+    nullable: bool,
+    #[serde(default)]
+    metadata: serde_json::Map<String, Value>,
+}
+
+fn default_nullable() -> bool {
+    true
+}
+
+/// A Spark type as written in its JSON schema, in Spark's DDL notation.
+fn spark_type_string(data_type: &Value) -> String {
+    match data_type {
+        Value::String(name) => name.clone(),
+        Value::Object(obj) => match obj.get("type").and_then(Value::as_str) {
+            Some("array") => format!(
+                "array<{}>",
+                spark_type_string(obj.get("elementType").unwrap_or(&Value::Null))
+            ),
+            Some("map") => format!(
+                "map<{},{}>",
+                spark_type_string(obj.get("keyType").unwrap_or(&Value::Null)),
+                spark_type_string(obj.get("valueType").unwrap_or(&Value::Null))
+            ),
+            Some("struct") => {
+                let fields: Vec<String> = obj
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .map(|fields| {
+                        fields
+                            .iter()
+                            .map(|f| {
+                                format!(
+                                    "{}:{}",
+                                    f.get("name").and_then(Value::as_str).unwrap_or(""),
+                                    spark_type_string(f.get("type").unwrap_or(&Value::Null))
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                format!("struct<{}>", fields.join(","))
+            }
+            // User-defined types carry the SQL type they're stored as
+            Some("udt") => spark_type_string(obj.get("sqlType").unwrap_or(&Value::Null)),
+            _ => data_type.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+/// Whether a Spark type could have been written as `arrow`. Nested types
+/// are compared by kind only; unknown types are given the benefit of the doubt.
+fn spark_type_matches(spark: &Value, arrow: &DataType) -> bool {
+    let arrow = match arrow {
+        DataType::Dictionary(_, value) => value.as_ref(),
+        other => other,
+    };
+    let kind = match spark {
+        Value::String(name) => name.as_str(),
+        Value::Object(obj) => obj.get("type").and_then(Value::as_str).unwrap_or(""),
+        _ => "",
+    };
+    match kind {
+        "boolean" => matches!(arrow, DataType::Boolean),
+        "byte" => matches!(arrow, DataType::Int8 | DataType::Int32),
+        "short" => matches!(arrow, DataType::Int16 | DataType::Int32),
+        "integer" => matches!(arrow, DataType::Int32),
+        "long" => matches!(arrow, DataType::Int64),
+        "float" => matches!(arrow, DataType::Float32),
+        "double" => matches!(arrow, DataType::Float64),
+        "string" => matches!(
+            arrow,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+        ),
+        "binary" => matches!(
+            arrow,
+            DataType::Binary | DataType::LargeBinary | DataType::BinaryView
+        ),
+        "date" => matches!(arrow, DataType::Date32 | DataType::Date64),
+        "timestamp" | "timestamp_ntz" => matches!(arrow, DataType::Timestamp(_, _)),
+        k if k.starts_with("decimal") => matches!(
+            arrow,
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _)
+        ),
+        "array" => matches!(
+            arrow,
+            DataType::List(_) | DataType::LargeList(_) | DataType::FixedSizeList(_, _)
+        ),
+        "struct" => matches!(arrow, DataType::Struct(_)),
+        "map" => matches!(arrow, DataType::Map(_, _)),
+        _ => true,
+    }
+}
+
+/// A pandas column name as text; MultiIndex columns are stored as tuples.
+fn pandas_name(name: &Value) -> Option<String> {
+    match name {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// File metadata plus what the `pandas` and Spark key-value entries say about
+/// each column: original dtypes, DataFrame index columns and Spark field
+/// metadata, with any disagreement with the Parquet schema listed.
+pub fn get_extended_metadata(path: &str) -> Result<ExtendedMetadata, String> {
+    let file = inspect::get_file_metadata(path)?;
+    let schema = arrow_schema(path)?;
+    let entry = |key: &str| file.key_value_metadata.get(key).cloned().flatten();
+
+    let mut mismatches = Vec::new();
+    let mut columns: Vec<ColumnLineage> = schema
+        .fields()
+        .iter()
+        .map(|field| ColumnLineage {
+            name: field.name().clone(),
+            arrow_type: field.data_type().to_string(),
+            nullable: field.is_nullable(),
+            field_metadata: field
+                .metadata()
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            pandas_name: None,
+            pandas_type: None,
+            numpy_type: None,
+            pandas_metadata: None,
+            is_index: false,
+            spark_type: None,
+            spark_nullable: None,
+            spark_metadata: None,
+        })
+        .collect();
+    let position: BTreeMap<String, usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.name.clone(), i))
+        .collect();
+
+    let pandas = match entry(PANDAS_KEY) {
+        Some(raw) => match serde_json::from_str::<PandasJson>(&raw) {
+            Ok(parsed) => {
+                let mut index_columns = Vec::new();
+                let mut range_indexes = Vec::new();
+                for index in &parsed.index_columns {
+                    match index {
+                        Value::String(name) => index_columns.push(name.clone()),
+                        Value::Object(range) => range_indexes.push(PandasRangeIndex {
+                            name: range.get("name").and_then(pandas_name),
+                            start: range.get("start").and_then(Value::as_i64).unwrap_or(0),
+                            stop: range.get("stop").and_then(Value::as_i64).unwrap_or(0),
+                            step: range.get("step").and_then(Value::as_i64).unwrap_or(1),
+                        }),
+                        _ => {}
+                    }
+                }
+
+                let mut described = Vec::new();
+                for col in parsed.columns {
+                    let original = pandas_name(&col.name);
+                    let Some(field_name) = col.field_name.or_else(|| original.clone()) else {
+                        continue;
+                    };
+                    let Some(&i) = position.get(&field_name) else {
+                        mismatches.push(format!("pandas column {} is not in the file", field_name));
+                        continue;
+                    };
+                    let column = &mut columns[i];
+                    column.pandas_name = original.filter(|name| *name != field_name);
+                    column.pandas_type = col.pandas_type;
+                    column.numpy_type = col.numpy_type;
+                    column.pandas_metadata = col.metadata.filter(|m| !m.is_null());
+                    column.is_index = index_columns.contains(&field_name);
+                    described.push(field_name);
+                }
+                for column in &columns {
+                    if !described.contains(&column.name) {
+                        mismatches.push(format!(
+                            "Column {} is not described by the pandas metadata",
+                            column.name
+                        ));
+                    }
+                }
+
+                Some(PandasMetadata {
+                    pandas_version: parsed.pandas_version,
+                    creator: parsed.creator.map(|c| match c.version {
+                        Some(version) => format!("{} {}", c.library, version),
+                        None => c.library,
+                    }),
+                    index_columns,
+                    range_indexes,
+                })
+            }
+            Err(e) => {
+                mismatches.push(format!("Invalid pandas metadata: {}", e));
+                None
+            }
+        },
+        None => None,
+    };
+
+    let spark_schema = entry(SPARK_SCHEMA_KEY);
+    if let Some(raw) = &spark_schema {
+        match serde_json::from_str::<SparkStructJson>(raw) {
+            Ok(parsed) => {
+                let mut described = Vec::new();
+                for field in parsed.fields {
+                    let Some(&i) = position.get(&field.name) else {
+                        mismatches.push(format!("Spark field {} is not in the file", field.name));
+                        continue;
+                    };
+                    let column = &mut columns[i];
+                    if column.nullable != field.nullable {
+                        mismatches.push(format!(
+                            "Column {} is {} in the file but {} in the Spark schema",
+                            field.name,
+                            if column.nullable {
+                                "nullable"
+                            } else {
+                                "required"
+                            },
+                            if field.nullable {
+                                "nullable"
+                            } else {
+                                "required"
+                            }
+                        ));
+                    }
+                    let data_type = schema.field(i).data_type();
+                    if !spark_type_matches(&field.data_type, data_type) {
+                        mismatches.push(format!(
+                            "Column {} is {} in the file but {} in the Spark schema",
+                            field.name,
+                            data_type,
+                            spark_type_string(&field.data_type)
+                        ));
+                    }
+                    column.spark_type = Some(spark_type_string(&field.data_type));
+                    column.spark_nullable = Some(field.nullable);
+                    column.spark_metadata =
+                        (!field.metadata.is_empty()).then_some(Value::Object(field.metadata));
+                    described.push(field.name);
+                }
+                for column in &columns {
+                    if !described.contains(&column.name) {
+                        mismatches
+                            .push(format!("Column {} is not in the Spark schema", column.name));
+                    }
+                }
+            }
+            Err(e) => mismatches.push(format!("Invalid Spark schema metadata: {}", e)),
+        }
+    }
+
+    let spark_version = entry(SPARK_VERSION_KEY);
+    let spark = (spark_schema.is_some() || spark_version.is_some()).then(|| SparkMetadata {
+        spark_version,
+        legacy_datetime: SPARK_LEGACY_KEYS
+            .iter()
+            .any(|key| file.key_value_metadata.contains_key(*key)),
+    });
+
+    Ok(ExtendedMetadata {
+        file,
+        pandas,
+        spark,
+        columns,
+        mismatches,
+    })
+}
@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter};
+
+const LOG_DIR: &str = "logs";
+const LOG_FILE_PREFIX: &str = "parqsee";
+
+/// Daily log files kept; older ones are deleted as new ones are started.
+const MAX_LOG_FILES: usize = 7;
+
+/// Environment variable overriding the log filter, e.g. `debug` or
+/// `parqsee_lib=trace,datafusion=info`.
+pub const LOG_FILTER_ENV: &str = "PARQSEE_LOG";
+
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Keeps the background log writer alive, and with it buffered lines, until exit.
+static GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+pub fn log_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(LOG_DIR)
+}
+
+/// Send log events to stderr and to a daily-rotated file under `data_dir`.
+/// Only the first call installs anything.
+pub fn init(data_dir: &Path) -> Result<(), String> {
+    if GUARD.get().is_some() {
+        return Ok(());
+    }
+
+    let dir = log_dir(data_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let filter = EnvFilter::try_from_env(LOG_FILTER_ENV)
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .with(fmt::layer().with_writer(writer).with_ansi(false))
+        .try_init()
+        .map_err(|e| format!("Failed to install logger: {}", e))?;
+
+    let _ = GUARD.set(guard);
+    Ok(())
+}
+
+/// `message` with quoted strings and numbers masked, for logging errors that
+/// may quote a query or values read from a file.
+pub fn redact(message: &str) -> String {
+    let mut redacted = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    let mut in_word = false;
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            // Doubled quotes inside a literal are part of it
+            loop {
+                match chars.next() {
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                    }
+                    Some('\'') | None => break,
+                    Some(_) => {}
+                }
+            }
+            redacted.push_str("'?'");
+            in_word = false;
+        } else if c.is_ascii_digit() && !in_word {
+            while chars
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.')
+            {
+                chars.next();
+            }
+            redacted.push('?');
+        } else {
+            in_word = c.is_alphanumeric() || c == '_';
+            redacted.push(c);
+        }
+    }
+    redacted
+}
+
+/// Log files in `data_dir`, newest first.
+pub fn log_files(data_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(log_dir(data_dir)) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX))
+        })
+        .collect();
+    // Daily files are named by date, so names sort chronologically
+    files.sort();
+    files.reverse();
+    files
+}
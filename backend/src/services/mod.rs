@@ -1,2 +1,33 @@
+pub mod bloom;
+pub mod convert;
+pub mod diagnostics;
+pub mod diff;
+pub mod directory;
+pub mod disk_cache;
+pub mod drop;
 pub mod export;
+pub mod ingest;
+pub mod inspect;
+pub mod jobs;
+pub mod launch;
+pub mod lineage;
+pub mod logging;
+pub mod notifications;
 pub mod parquet;
+pub mod pii;
+pub mod pruning;
+pub mod reader;
+pub mod recent_files;
+pub mod sample;
+pub mod saved_queries;
+pub mod schema;
+pub mod scope;
+pub mod session;
+pub mod sql;
+pub mod settings;
+pub mod store;
+pub mod tasks;
+pub mod udf;
+pub mod view_state;
+pub mod watcher;
+pub mod workspace;
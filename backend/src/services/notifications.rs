@@ -0,0 +1,119 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::DateTime;
+
+use crate::models::TaskInfo;
+
+/// Task kinds announced when they finish.
+const NOTIFY_TASK_KINDS: &[&str] = &["export", "convert"];
+
+/// Queries running at least this long are announced when they finish.
+pub const LONG_QUERY: Duration = Duration::from_secs(10);
+
+/// An OS notification to show.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+pub type NotificationListener = Arc<dyn Fn(&Notification) + Send + Sync>;
+
+/// `90s` as `1m 30s`; under a minute as e.g. `4.2s`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    }
+}
+
+fn task_duration(info: &TaskInfo) -> Option<Duration> {
+    let started = DateTime::parse_from_rfc3339(&info.started_at).ok()?;
+    let finished = DateTime::parse_from_rfc3339(info.finished_at.as_deref()?).ok()?;
+    (finished - started).to_std().ok()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Announces long operations finishing. Whether a notification is actually
+/// shown (e.g. only while the window is unfocused) is up to the listener.
+#[derive(Default)]
+pub struct Notifier {
+    listener: Mutex<Option<NotificationListener>>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install the callback that shows notifications, typically through the OS.
+    pub fn set_listener(&self, listener: impl Fn(&Notification) + Send + Sync + 'static) {
+        if let Ok(mut current) = self.listener.lock() {
+            *current = Some(Arc::new(listener));
+        }
+    }
+
+    fn send(&self, notification: Notification) {
+        let listener = self.listener.lock().ok().and_then(|l| l.clone());
+        if let Some(listener) = listener {
+            listener(&notification);
+        }
+    }
+
+    /// Announce a finished export or conversion. Cancelled tasks and other
+    /// kinds are ignored.
+    pub fn task_finished(&self, info: &TaskInfo) {
+        if !NOTIFY_TASK_KINDS.contains(&info.kind.as_str()) {
+            return;
+        }
+        let took = task_duration(info)
+            .map(|d| format!(" in {}", format_duration(d)))
+            .unwrap_or_default();
+        let (title, body) = match info.state.as_str() {
+            "completed" => (
+                format!("{} finished", capitalize(&info.kind)),
+                format!("{}{}", info.label, took),
+            ),
+            "failed" => (
+                format!("{} failed", capitalize(&info.kind)),
+                format!(
+                    "{}{}: {}",
+                    info.label,
+                    took,
+                    info.error.as_deref().unwrap_or("unknown error")
+                ),
+            ),
+            _ => return,
+        };
+        self.send(Notification { title, body });
+    }
+
+    /// Announce a query that ran for at least `LONG_QUERY`, with its row
+    /// count or error.
+    pub fn query_finished(&self, elapsed: Duration, outcome: Result<usize, &str>) {
+        if elapsed < LONG_QUERY {
+            return;
+        }
+        let took = format_duration(elapsed);
+        let (title, body) = match outcome {
+            Ok(rows) => (
+                "Query finished".to_string(),
+                format!("{} rows in {}", rows, took),
+            ),
+            Err(e) => ("Query failed".to_string(), format!("After {}: {}", took, e)),
+        };
+        self.send(Notification { title, body });
+    }
+}
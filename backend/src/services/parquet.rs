@@ -1,16 +1,210 @@
+use datafusion::common::DataFusionError;
+use datafusion::datasource::streaming::StreamingTable;
+use datafusion::execution::disk_manager::DiskManagerConfig;
+use datafusion::execution::runtime_env::{RuntimeConfig, RuntimeEnv};
+use datafusion::execution::{SendableRecordBatchStream, TaskContext};
+use datafusion::physical_plan::stream::RecordBatchReceiverStreamBuilder;
+use datafusion::physical_plan::streaming::PartitionStream;
+use datafusion::prelude::{SessionConfig, SessionContext};
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ParquetRecordBatchReaderBuilder, RowSelection, RowSelector,
+};
 use parquet::file::reader::{FileReader, SerializedFileReader};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 
-use crate::models::{ColumnInfo, ParquetMetadata};
+use crate::models::{
+    CacheEntryStats, CacheStats, CellBytes, CellValue, ColumnInfo, ColumnNullInfo, ColumnProfile,
+    ColumnSummaries, ColumnSummary, CountEstimate, DataPage, DataProfile, DataWindow, FilterExpr,
+    FilterOperator, Histogram, HistogramBucket, LenientData, MetadataPrefetch, NullProfile,
+    ParquetMetadata, Percentile, PivotTable, PruneVerdict, PruningReport, QueryPlan, RowGroupPage,
+    RowGroupPruning, SchemaNode, SearchMatch, SearchResults, SelectedRows, SkippedRange, SortSpec,
+    SqlIssue, SqlValidation, TimeSeries, TimeSeriesPoint, ValueCount, ValueCounts,
+};
+use crate::services::disk_cache::{self, DiskCache, KIND_COLUMN_STATS, KIND_METADATA};
+use crate::services::pruning::{self, RowGroupMatch};
+use crate::services::reader::{FileSource, ReadMode};
+use crate::services::scope::PathScope;
+use crate::services::sql::{
+    build_order_by_clause, build_where_clause, build_where_clause_with, compile_literal,
+    quote_identifier, quote_literal, ColumnResolver,
+};
+use crate::services::tasks::TaskHandle;
+use crate::services::{inspect, schema, udf};
+use crate::utils::{
+    flatten_structs, render_for_display, restore_non_finite, BinaryDisplay, DisplayOptions,
+    NonFiniteDisplay,
+};
+
+/// Number of executed queries remembered per file for session export.
+const QUERY_HISTORY_LIMIT: usize = 100;
+
+/// Start of every id `create_workspace` hands out. Files are opened by
+/// absolute path, so no file key starts with it.
+const WORKSPACE_ID_PREFIX: &str = "session-";
+
+pub const DEFAULT_MAX_CACHED_FILES: usize = 32;
+pub const DEFAULT_CACHE_MEMORY_BUDGET: usize = 512 * 1024 * 1024;
+
+/// Rough fixed cost of a SessionContext (catalog, config, registered UDFs)
+/// on top of the footer it re-reads when planning.
+const SESSION_OVERHEAD_BYTES: usize = 256 * 1024;
+
+/// Recently read pages kept so scrolling back doesn't re-run their queries.
+const PAGE_CACHE_ENTRIES: usize = 16;
+/// Pages larger than this are never cached.
+const PAGE_CACHE_MAX_PAGE_BYTES: usize = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CacheLimits {
+    /// Maximum number of files with a cached session or metadata.
+    pub max_entries: usize,
+    /// Approximate memory budget in bytes across all cached files.
+    pub memory_budget: usize,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_CACHED_FILES,
+            memory_budget: DEFAULT_CACHE_MEMORY_BUDGET,
+        }
+    }
+}
+
+/// Runtime settings shared by every cached session.
+#[derive(Debug, Clone, Default)]
+pub struct EngineConfig {
+    /// Memory pool limit in bytes; sorts, joins and aggregations spill to disk
+    /// beyond it. `None` leaves memory unbounded.
+    pub memory_limit: Option<usize>,
+    /// Directory for spill files; `None` uses the OS temp directory.
+    pub spill_dir: Option<String>,
+    /// Partitions each query is split into, and so the cores it keeps busy.
+    /// `None` uses one per core.
+    pub target_partitions: Option<usize>,
+}
+
+/// Environment variable capping tokio's blocking thread pool, overriding
+/// `Settings::blocking_threads`. The pool is built before the app starts, so
+/// unlike `EngineConfig` it can't change at runtime.
+pub const BLOCKING_THREADS_ENV: &str = "PARQSEE_BLOCKING_THREADS";
+
+/// The blocking thread cap the runtime was built with, once it's chosen.
+static BLOCKING_THREADS: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Choose the blocking thread cap at startup: `BLOCKING_THREADS_ENV` if set,
+/// otherwise `saved` from the settings. `None` keeps tokio's default.
+pub fn init_blocking_threads(saved: Option<usize>) -> Option<usize> {
+    let from_env = std::env::var(BLOCKING_THREADS_ENV)
+        .ok()
+        .and_then(|threads| threads.trim().parse().ok());
+    let threads = from_env.or(saved).filter(|&threads| threads > 0);
+    *BLOCKING_THREADS.get_or_init(|| threads)
+}
+
+/// Blocking thread cap chosen at startup, or `None` for tokio's default.
+pub fn blocking_threads() -> Option<usize> {
+    BLOCKING_THREADS.get().copied().flatten()
+}
+
+impl EngineConfig {
+    fn session_config(&self) -> SessionConfig {
+        let config = SessionConfig::new();
+        match self.target_partitions {
+            Some(partitions) => config.with_target_partitions(partitions),
+            None => config,
+        }
+    }
+
+    fn runtime(&self) -> Result<Arc<RuntimeEnv>, String> {
+        let disk_manager = match &self.spill_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)
+                    .map_err(|e| format!("Cannot use spill directory {}: {}", dir, e))?;
+                DiskManagerConfig::NewSpecified(vec![dir.into()])
+            }
+            None => DiskManagerConfig::NewOs,
+        };
+        let mut config = RuntimeConfig::new().with_disk_manager(disk_manager);
+        if let Some(limit) = self.memory_limit {
+            config = config.with_memory_limit(limit, 1.0);
+        }
+        RuntimeEnv::new(config)
+            .map(Arc::new)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// A cached page: the file and the page query (filter, sort, offset, limit).
+struct CachedPage {
+    path: String,
+    query: String,
+    batches: Vec<RecordBatch>,
+}
+
+/// Bookkeeping for one cached file, used to pick LRU victims.
+#[derive(Debug, Default)]
+struct CacheUsage {
+    last_access: u64,
+    last_access_at: String,
+    hits: u64,
+    session_bytes: Option<usize>,
+    metadata_bytes: Option<usize>,
+}
+
+impl CacheUsage {
+    fn bytes(&self) -> usize {
+        self.session_bytes.unwrap_or(0) + self.metadata_bytes.unwrap_or(0)
+    }
+}
 
 /// Cache for DataFusion SessionContext and Parquet metadata.
 /// Stored as Tauri managed state to avoid re-creating sessions on every request.
 pub struct ParquetCache {
     sessions: Mutex<HashMap<String, datafusion::execution::context::SessionContext>>,
     metadata: Mutex<HashMap<String, ParquetMetadata>>,
+    history: Mutex<HashMap<String, VecDeque<String>>>,
+    /// Independent SQL workspaces keyed by session id, so tabs that register
+    /// tables or views on the same file don't clobber each other.
+    workspaces: Mutex<HashMap<String, datafusion::execution::context::SessionContext>>,
+    next_workspace_id: AtomicU64,
+    /// Whether `execute_sql` may run statements that write or register files.
+    allow_writes: AtomicBool,
+    /// Directories commands may touch, from the `allowed_directories` setting.
+    scope: Mutex<PathScope>,
+    usage: Mutex<HashMap<String, CacheUsage>>,
+    limits: Mutex<CacheLimits>,
+    access_clock: AtomicU64,
+    evictions: AtomicU64,
+    /// How timestamps and binary values are rendered for display.
+    display: Mutex<DisplayOptions>,
+    /// How files are read for metadata and direct page reads.
+    read_mode: Mutex<ReadMode>,
+    engine: Mutex<EngineConfig>,
+    /// Built from `engine`; one memory pool shared by all sessions.
+    runtime: Mutex<Arc<RuntimeEnv>>,
+    /// Recently read pages, most recently used last.
+    pages: Mutex<VecDeque<CachedPage>>,
+    /// Row counts by file and WHERE clause, so paging a filtered view counts once.
+    counts: Mutex<HashMap<(String, String), usize>>,
+    column_stats: Mutex<HashMap<String, ColumnSummaries>>,
+    /// Which row groups of a file decode, once a lenient read had to diagnose it.
+    salvage: Mutex<HashMap<String, SalvagePlan>>,
+    /// Whether opening a file starts computing its column stats in the background.
+    background_stats: AtomicBool,
+    /// Metadata and column stats kept across restarts, once attached.
+    disk: OnceLock<DiskCache>,
+}
+
+impl Default for ParquetCache {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ParquetCache {
@@ -18,59 +212,403 @@ impl ParquetCache {
         Self {
             sessions: Mutex::new(HashMap::new()),
             metadata: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+            workspaces: Mutex::new(HashMap::new()),
+            next_workspace_id: AtomicU64::new(1),
+            allow_writes: AtomicBool::new(false),
+            scope: Mutex::new(PathScope::default()),
+            usage: Mutex::new(HashMap::new()),
+            limits: Mutex::new(CacheLimits::default()),
+            access_clock: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            display: Mutex::new(DisplayOptions::default()),
+            read_mode: Mutex::new(ReadMode::default()),
+            engine: Mutex::new(EngineConfig::default()),
+            runtime: Mutex::new(Arc::new(RuntimeEnv::default())),
+            pages: Mutex::new(VecDeque::new()),
+            counts: Mutex::new(HashMap::new()),
+            column_stats: Mutex::new(HashMap::new()),
+            salvage: Mutex::new(HashMap::new()),
+            background_stats: AtomicBool::new(false),
+            disk: OnceLock::new(),
         }
     }
 
-    /// Get or create a SessionContext for the given file path.
+    /// Get or create a SessionContext for the given file path, which must be in scope.
     /// Returns a cloned SessionContext (SessionContext uses Arc internally, so cloning is cheap).
     pub async fn get_or_create_session(
         &self,
         path: &str,
     ) -> Result<datafusion::execution::context::SessionContext, String> {
+        self.check_path(path)?;
         // Check cache first
         {
             let sessions = self.sessions.lock().map_err(|e| e.to_string())?;
             if let Some(ctx) = sessions.get(path) {
-                return Ok(ctx.clone());
+                let ctx = ctx.clone();
+                drop(sessions);
+                self.touch(path, |_| {});
+                return Ok(ctx);
             }
         }
 
         // Create new session and register the parquet file
-        let ctx = datafusion::execution::context::SessionContext::new();
-        let options = datafusion::prelude::ParquetReadOptions::default();
-        ctx.register_parquet("t", path, options)
-            .await
-            .map_err(|e| format!("Failed to register parquet file: {}", e))?;
+        let ctx = new_session(self.session_context()?, path).await?;
 
         // Store in cache
         {
             let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
             sessions.insert(path.to_string(), ctx.clone());
         }
+        let bytes = SESSION_OVERHEAD_BYTES + footer_length(path).unwrap_or(0);
+        self.touch(path, |usage| usage.session_bytes = Some(bytes));
+        self.enforce_limits(path);
 
         Ok(ctx)
     }
 
+    /// Create a workspace with its own SessionContext for `path` and return its id.
+    pub async fn create_workspace(&self, path: &str) -> Result<String, String> {
+        self.check_path(path)?;
+        let ctx = new_session(self.session_context()?, path).await?;
+        let id = format!(
+            "{}{}",
+            WORKSPACE_ID_PREFIX,
+            self.next_workspace_id.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let mut workspaces = self.workspaces.lock().map_err(|e| e.to_string())?;
+        workspaces.insert(id.clone(), ctx);
+        Ok(id)
+    }
+
+    pub fn destroy_workspace(&self, id: &str) -> Result<(), String> {
+        let mut workspaces = self.workspaces.lock().map_err(|e| e.to_string())?;
+        workspaces
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| format!("Session not found: {}", id))
+    }
+
+    /// Resolve a session id created by `create_workspace`, or the shared
+    /// per-file session when `key` is a file path. An id of a session that
+    /// doesn't exist (e.g. one already destroyed) is an error, never a path.
+    pub async fn session_for(
+        &self,
+        key: &str,
+    ) -> Result<datafusion::execution::context::SessionContext, String> {
+        if key.starts_with(WORKSPACE_ID_PREFIX) {
+            let workspaces = self.workspaces.lock().map_err(|e| e.to_string())?;
+            return workspaces
+                .get(key)
+                .cloned()
+                .ok_or_else(|| format!("Unknown session: {}", key));
+        }
+        self.get_or_create_session(key).await
+    }
+
+    pub fn allow_writes(&self) -> bool {
+        self.allow_writes.load(Ordering::Relaxed)
+    }
+
+    pub fn set_allow_writes(&self, enabled: bool) {
+        self.allow_writes.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn set_path_scope(&self, scope: PathScope) {
+        if let Ok(mut current) = self.scope.lock() {
+            *current = scope;
+        }
+    }
+
+    /// Reject `path` unless it is inside the allowed directories.
+    pub fn check_path(&self, path: &str) -> Result<(), String> {
+        self.scope.lock().map_err(|e| e.to_string())?.check(path)
+    }
+
+    pub fn display_options(&self) -> DisplayOptions {
+        self.display
+            .lock()
+            .map(|display| *display)
+            .unwrap_or_default()
+    }
+
+    pub fn set_display_options(&self, display: DisplayOptions) -> Result<(), String> {
+        *self.display.lock().map_err(|e| e.to_string())? = display;
+        Ok(())
+    }
+
+    pub fn read_mode(&self) -> ReadMode {
+        self.read_mode.lock().map(|mode| *mode).unwrap_or_default()
+    }
+
+    pub fn set_read_mode(&self, mode: ReadMode) -> Result<(), String> {
+        *self.read_mode.lock().map_err(|e| e.to_string())? = mode;
+        Ok(())
+    }
+
+    /// `batches_to_rows` with timestamps and binary values rendered per the
+    /// display options, for rows shown to the user rather than fed back into queries.
+    pub(crate) fn display_rows(&self, batches: &[RecordBatch]) -> Result<Vec<Value>, String> {
+        display_rows(batches, self.display_options())
+    }
+
     /// Get cached metadata, or compute and cache it.
     pub fn get_or_create_metadata(&self, path: &str) -> Result<ParquetMetadata, String> {
+        self.get_or_create_metadata_with_progress(path, &|_| {})
+    }
+
+    /// Like `get_or_create_metadata`, reporting each `OPEN_STAGES` entry up to
+    /// `STAGE_STATISTICS_LOADED` as it completes. Cache hits report them all at once.
+    pub fn get_or_create_metadata_with_progress(
+        &self,
+        path: &str,
+        on_stage: &dyn Fn(&str),
+    ) -> Result<ParquetMetadata, String> {
         // Check cache first
         {
             let metadata_cache = self.metadata.lock().map_err(|e| e.to_string())?;
             if let Some(meta) = metadata_cache.get(path) {
-                return Ok(meta.clone());
+                let meta = meta.clone();
+                drop(metadata_cache);
+                self.touch(path, |_| {});
+                on_stage(STAGE_FOOTER_PARSED);
+                on_stage(STAGE_SCHEMA_LOADED);
+                on_stage(STAGE_STATISTICS_LOADED);
+                return Ok(meta);
             }
         }
 
+        // Then what an earlier run computed for the same file version
+        if let Some(meta) = self.persisted::<ParquetMetadata>(path, KIND_METADATA) {
+            self.store_metadata(path, &meta, footer_length(path).unwrap_or(0))?;
+            on_stage(STAGE_FOOTER_PARSED);
+            on_stage(STAGE_SCHEMA_LOADED);
+            on_stage(STAGE_STATISTICS_LOADED);
+            return Ok(meta);
+        }
+
         // Compute metadata
-        let meta = compute_metadata(path)?;
+        let version = self.disk_version(path);
+        let (meta, bytes) = compute_metadata(path, self.read_mode(), on_stage)?;
+        self.store_metadata(path, &meta, bytes)?;
+        self.persist(path, KIND_METADATA, version, &meta);
 
-        // Store in cache
+        Ok(meta)
+    }
+
+    /// Persist computed metadata and column stats to `disk` from now on.
+    pub fn attach_disk_cache(&self, disk: DiskCache) {
+        let _ = self.disk.set(disk);
+    }
+
+    /// Remove everything persisted so far, returning how many entries there were.
+    pub fn clear_disk_cache(&self) -> Result<usize, String> {
+        match self.disk.get() {
+            Some(disk) => disk.clear(),
+            None => Ok(0),
+        }
+    }
+
+    fn persisted<T: DeserializeOwned>(&self, path: &str, kind: &str) -> Option<T> {
+        self.disk.get()?.get(path, kind)
+    }
+
+    /// Version of `path` to persist a result under, taken before computing it.
+    fn disk_version(&self, path: &str) -> Option<String> {
+        self.disk.get()?;
+        disk_cache::file_version(path)
+    }
+
+    /// Failing to persist only costs a recompute after the next restart.
+    fn persist<T: Serialize>(&self, path: &str, kind: &str, version: Option<String>, value: &T) {
+        if let (Some(disk), Some(version)) = (self.disk.get(), version) {
+            if let Err(e) = disk.put(path, kind, &version, value) {
+                tracing::warn!("Not persisting {} of {}: {}", kind, path, e);
+            }
+        }
+    }
+
+    fn cached_metadata(&self, path: &str) -> Option<ParquetMetadata> {
+        self.metadata.lock().ok()?.get(path).cloned()
+    }
+
+    fn store_metadata(
+        &self,
+        path: &str,
+        meta: &ParquetMetadata,
+        bytes: usize,
+    ) -> Result<(), String> {
         {
             let mut metadata_cache = self.metadata.lock().map_err(|e| e.to_string())?;
             metadata_cache.insert(path.to_string(), meta.clone());
         }
+        self.touch(path, |usage| usage.metadata_bytes = Some(bytes));
+        self.enforce_limits(path);
+        Ok(())
+    }
+
+    pub fn engine_config(&self) -> EngineConfig {
+        self.engine
+            .lock()
+            .map(|engine| engine.clone())
+            .unwrap_or_default()
+    }
+
+    /// Apply a new engine config. Cached per-file sessions are dropped so
+    /// they are recreated under it; existing workspaces keep their runtime.
+    pub fn set_engine_config(&self, config: EngineConfig) -> Result<(), String> {
+        let runtime = config.runtime()?;
+        *self.runtime.lock().map_err(|e| e.to_string())? = runtime;
+        *self.engine.lock().map_err(|e| e.to_string())? = config;
+
+        let dropped: Vec<String> = {
+            let mut sessions = self.sessions.lock().map_err(|e| e.to_string())?;
+            sessions.drain().map(|(path, _)| path).collect()
+        };
+        if let Ok(mut usage) = self.usage.lock() {
+            for path in dropped {
+                if let Some(entry) = usage.get_mut(&path) {
+                    entry.session_bytes = None;
+                }
+            }
+        }
+        Ok(())
+    }
 
-        Ok(meta)
+    /// Bytes currently reserved from the shared memory pool.
+    pub fn engine_memory_used(&self) -> usize {
+        self.runtime
+            .lock()
+            .map(|runtime| runtime.memory_pool.reserved())
+            .unwrap_or(0)
+    }
+
+    /// Empty SessionContext on the shared runtime, configured per the engine config.
+    fn session_context(&self) -> Result<SessionContext, String> {
+        let config = self.engine_config().session_config();
+        let runtime = self
+            .runtime
+            .lock()
+            .map(|runtime| runtime.clone())
+            .map_err(|e| e.to_string())?;
+        Ok(SessionContext::new_with_config_rt(config, runtime))
+    }
+
+    pub fn limits(&self) -> CacheLimits {
+        self.limits.lock().map(|l| *l).unwrap_or_default()
+    }
+
+    /// Change the cache limits, evicting immediately if the cache is now over them.
+    pub fn set_limits(&self, limits: CacheLimits) -> Result<(), String> {
+        if limits.max_entries == 0 {
+            return Err("max_entries must be at least 1".to_string());
+        }
+        *self.limits.lock().map_err(|e| e.to_string())? = limits;
+        self.enforce_limits("");
+        Ok(())
+    }
+
+    /// Mark `path` as most recently used and let `update` adjust its sizes.
+    fn touch(&self, path: &str, update: impl FnOnce(&mut CacheUsage)) {
+        let tick = self.access_clock.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Ok(mut usage) = self.usage.lock() {
+            let entry = usage.entry(path.to_string()).or_default();
+            if entry.last_access > 0 {
+                entry.hits += 1;
+            }
+            entry.last_access = tick;
+            entry.last_access_at = chrono::Utc::now().to_rfc3339();
+            update(entry);
+        }
+    }
+
+    /// Evict least recently used files until the cache fits its limits.
+    /// `keep` is the file currently being loaded, which is never evicted.
+    fn enforce_limits(&self, keep: &str) {
+        let limits = self.limits();
+        loop {
+            let victim = {
+                let Ok(usage) = self.usage.lock() else {
+                    return;
+                };
+                let total: usize = usage.values().map(CacheUsage::bytes).sum();
+                if usage.len() <= limits.max_entries && total <= limits.memory_budget {
+                    return;
+                }
+                usage
+                    .iter()
+                    .filter(|(path, _)| path.as_str() != keep)
+                    .min_by_key(|(_, u)| u.last_access)
+                    .map(|(path, _)| path.clone())
+            };
+            match victim {
+                Some(path) => {
+                    self.evict(&path);
+                    self.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Snapshot of what is cached, most recently used first.
+    pub fn stats(&self) -> Result<CacheStats, String> {
+        let limits = self.limits();
+        let workspaces = self.workspaces.lock().map_err(|e| e.to_string())?.len();
+        let usage = self.usage.lock().map_err(|e| e.to_string())?;
+
+        let mut entries: Vec<(u64, CacheEntryStats)> = usage
+            .iter()
+            .map(|(path, u)| {
+                (
+                    u.last_access,
+                    CacheEntryStats {
+                        path: path.clone(),
+                        has_session: u.session_bytes.is_some(),
+                        has_metadata: u.metadata_bytes.is_some(),
+                        approx_bytes: u.bytes(),
+                        hits: u.hits,
+                        last_accessed: u.last_access_at.clone(),
+                    },
+                )
+            })
+            .collect();
+        entries.sort_by_key(|(tick, _)| std::cmp::Reverse(*tick));
+
+        Ok(CacheStats {
+            approx_total_bytes: entries.iter().map(|(_, e)| e.approx_bytes).sum(),
+            entries: entries.into_iter().map(|(_, e)| e).collect(),
+            workspace_count: workspaces,
+            max_entries: limits.max_entries,
+            memory_budget: limits.memory_budget,
+            evictions: self.evictions.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Remember a successfully executed query, dropping the oldest beyond the limit.
+    pub fn record_query(&self, path: &str, query: &str) {
+        if let Ok(mut history) = self.history.lock() {
+            let queries = history.entry(path.to_string()).or_default();
+            queries.push_back(query.to_string());
+            while queries.len() > QUERY_HISTORY_LIMIT {
+                queries.pop_front();
+            }
+        }
+    }
+
+    /// Most recent queries for the given file path, oldest first.
+    pub fn recent_queries(&self, path: &str, max: usize) -> Vec<String> {
+        self.history
+            .lock()
+            .ok()
+            .and_then(|history| {
+                history.get(path).map(|queries| {
+                    let skip = queries.len().saturating_sub(max);
+                    queries.iter().skip(skip).cloned().collect()
+                })
+            })
+            .unwrap_or_default()
     }
 
     /// Remove cached entries for a given file path.
@@ -81,10 +619,113 @@ impl ParquetCache {
         if let Ok(mut metadata_cache) = self.metadata.lock() {
             metadata_cache.remove(path);
         }
+        if let Ok(mut usage) = self.usage.lock() {
+            usage.remove(path);
+        }
+        if let Ok(mut pages) = self.pages.lock() {
+            pages.retain(|page| page.path != path);
+        }
+        if let Ok(mut counts) = self.counts.lock() {
+            counts.retain(|(counted, _), _| counted != path);
+        }
+        if let Ok(mut column_stats) = self.column_stats.lock() {
+            column_stats.remove(path);
+        }
+        if let Ok(mut salvage) = self.salvage.lock() {
+            salvage.remove(path);
+        }
+    }
+
+    pub fn background_stats(&self) -> bool {
+        self.background_stats.load(Ordering::Relaxed)
+    }
+
+    pub fn set_background_stats(&self, enabled: bool) {
+        self.background_stats.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Column stats computed earlier by `column_stats`, in this run or a
+    /// previous one, without computing them.
+    pub fn cached_column_stats(&self, path: &str) -> Option<ColumnSummaries> {
+        if let Some(stats) = self.column_stats.lock().ok()?.get(path) {
+            return Some(stats.clone());
+        }
+        let stats: ColumnSummaries = self.persisted(path, KIND_COLUMN_STATS)?;
+        self.column_stats
+            .lock()
+            .ok()?
+            .insert(path.to_string(), stats.clone());
+        Some(stats)
     }
+
+    fn cached_page(&self, path: &str, query: &str) -> Option<Vec<RecordBatch>> {
+        let mut pages = self.pages.lock().ok()?;
+        let index = pages
+            .iter()
+            .position(|page| page.path == path && page.query == query)?;
+        let page = pages.remove(index)?;
+        let batches = page.batches.clone();
+        pages.push_back(page);
+        Some(batches)
+    }
+
+    fn store_page(&self, path: &str, query: &str, batches: &[RecordBatch]) {
+        let bytes: usize = batches.iter().map(|b| b.get_array_memory_size()).sum();
+        if bytes > PAGE_CACHE_MAX_PAGE_BYTES {
+            return;
+        }
+        if let Ok(mut pages) = self.pages.lock() {
+            pages.retain(|page| page.path != path || page.query != query);
+            pages.push_back(CachedPage {
+                path: path.to_string(),
+                query: query.to_string(),
+                batches: batches.to_vec(),
+            });
+            while pages.len() > PAGE_CACHE_ENTRIES {
+                pages.pop_front();
+            }
+        }
+    }
+}
+
+/// Length of the thrift-encoded footer, read from the file's trailer.
+fn footer_length(path: &str) -> Option<usize> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = File::open(path).ok()?;
+    file.seek(SeekFrom::End(-8)).ok()?;
+    let mut trailer = [0u8; 4];
+    file.read_exact(&mut trailer).ok()?;
+    Some(u32::from_le_bytes(trailer) as usize)
+}
+
+pub const STAGE_FOOTER_PARSED: &str = "footer_parsed";
+pub const STAGE_SCHEMA_LOADED: &str = "schema_loaded";
+pub const STAGE_STATISTICS_LOADED: &str = "statistics_loaded";
+pub const STAGE_SESSION_REGISTERED: &str = "session_registered";
+
+/// Stages reported while opening a file, in the order they complete.
+pub const OPEN_STAGES: [&str; 4] = [
+    STAGE_FOOTER_PARSED,
+    STAGE_SCHEMA_LOADED,
+    STAGE_STATISTICS_LOADED,
+    STAGE_SESSION_REGISTERED,
+];
+
+/// `ctx` with UDFs and the parquet file at `path` registered as `t`.
+async fn new_session(
+    ctx: SessionContext,
+    path: &str,
+) -> Result<datafusion::execution::context::SessionContext, String> {
+    udf::register_udfs(&ctx);
+    let options = datafusion::prelude::ParquetReadOptions::default();
+    ctx.register_parquet("t", path, options)
+        .await
+        .map_err(|e| format!("Failed to register parquet file: {}", e))?;
+    Ok(ctx)
 }
 
-fn logical_type_to_string(logical_type: &parquet::basic::LogicalType) -> String {
+pub(crate) fn logical_type_to_string(logical_type: &parquet::basic::LogicalType) -> String {
     match logical_type {
         parquet::basic::LogicalType::String => "STRING".to_string(),
         parquet::basic::LogicalType::Map => "MAP".to_string(),
@@ -124,7 +765,7 @@ fn logical_type_to_string(logical_type: &parquet::basic::LogicalType) -> String
     }
 }
 
-fn converted_type_to_string(converted_type: parquet::basic::ConvertedType) -> String {
+pub(crate) fn converted_type_to_string(converted_type: parquet::basic::ConvertedType) -> String {
     match converted_type {
         parquet::basic::ConvertedType::UTF8 => "STRING".to_string(),
         parquet::basic::ConvertedType::MAP => "MAP".to_string(),
@@ -152,26 +793,173 @@ fn converted_type_to_string(converted_type: parquet::basic::ConvertedType) -> St
     }
 }
 
-fn compute_metadata(path: &str) -> Result<ParquetMetadata, String> {
-    let file = File::open(path).map_err(|e| e.to_string())?;
-    let reader = SerializedFileReader::new(file).map_err(|e| e.to_string())?;
+/// Footers read at the same time by `prefetch_metadata`.
+const PREFETCH_CONCURRENCY: usize = 8;
+
+/// Read the footers of `paths` in parallel, at most `PREFETCH_CONCURRENCY` at
+/// a time, and cache their metadata. `on_loaded` is called as each file
+/// finishes, in completion order. Files beyond the cache limits are evicted
+/// again in LRU order, so callers should keep what `on_loaded` hands them.
+pub async fn prefetch_metadata(
+    cache: &ParquetCache,
+    paths: Vec<String>,
+    on_loaded: &(dyn Fn(&str, Result<&ParquetMetadata, &str>) + Sync),
+    task: Option<&TaskHandle>,
+) -> Result<MetadataPrefetch, String> {
+    let total = paths.len();
+    let mut loaded = 0;
+    let mut failed = 0;
+
+    // Each footer comes with `Some((footer bytes, version if freshly computed))`
+    // unless it was already in memory
+    let mut footers = futures::stream::iter(paths)
+        .map(|path| async move {
+            if let Some(meta) = cache.cached_metadata(&path) {
+                return (path, Ok((meta, None)));
+            }
+            if let Some(meta) = cache.persisted::<ParquetMetadata>(&path, KIND_METADATA) {
+                let bytes = footer_length(&path).unwrap_or(0);
+                return (path, Ok((meta, Some((bytes, None)))));
+            }
+            let owned_path = path.clone();
+            let mode = cache.read_mode();
+            let version = cache.disk_version(&path);
+            let result =
+                tokio::task::spawn_blocking(move || compute_metadata(&owned_path, mode, &|_| {}))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| result)
+                    .map(|(meta, bytes)| (meta, Some((bytes, Some(version)))));
+            (path, result)
+        })
+        .buffer_unordered(PREFETCH_CONCURRENCY);
+
+    while let Some((path, result)) = footers.next().await {
+        match result {
+            Ok((meta, bytes)) => {
+                if let Some((bytes, version)) = bytes {
+                    cache.store_metadata(&path, &meta, bytes)?;
+                    if let Some(version) = version {
+                        cache.persist(&path, KIND_METADATA, version, &meta);
+                    }
+                }
+                loaded += 1;
+                on_loaded(&path, Ok(&meta));
+            }
+            Err(e) => {
+                failed += 1;
+                on_loaded(&path, Err(&e));
+            }
+        }
+        if let Some(task) = task {
+            task.check_cancelled()?;
+            task.set_progress((loaded + failed) as f64 / total as f64, None);
+        }
+    }
+
+    Ok(MetadataPrefetch {
+        total,
+        loaded,
+        failed,
+    })
+}
+
+fn physical_type_name(field: &parquet::schema::types::Type) -> Option<String> {
+    field
+        .is_primitive()
+        .then(|| format!("{:?}", field.get_physical_type()))
+}
+
+/// Logical type of a field, falling back to its legacy converted type.
+fn annotation_name(field: &parquet::schema::types::Type) -> Option<String> {
+    let info = field.get_basic_info();
+    if let Some(lt) = info.logical_type() {
+        Some(logical_type_to_string(&lt))
+    } else if info.converted_type() != parquet::basic::ConvertedType::NONE {
+        Some(converted_type_to_string(info.converted_type()))
+    } else {
+        None
+    }
+}
+
+/// Repetition of a schema field; the root carries none and counts as required.
+fn repetition_of(field: &parquet::schema::types::Type) -> parquet::basic::Repetition {
+    let info = field.get_basic_info();
+    if info.has_repetition() {
+        info.repetition()
+    } else {
+        parquet::basic::Repetition::REQUIRED
+    }
+}
+
+/// Schema tree below `field`, whose parent sits at the given definition and
+/// repetition levels.
+fn schema_node(
+    field: &parquet::schema::types::Type,
+    parent_path: &str,
+    parent_definition_level: i16,
+    parent_repetition_level: i16,
+) -> SchemaNode {
+    let repetition = repetition_of(field);
+    let (max_definition_level, max_repetition_level) = match repetition {
+        parquet::basic::Repetition::REQUIRED => (parent_definition_level, parent_repetition_level),
+        parquet::basic::Repetition::OPTIONAL => {
+            (parent_definition_level + 1, parent_repetition_level)
+        }
+        parquet::basic::Repetition::REPEATED => {
+            (parent_definition_level + 1, parent_repetition_level + 1)
+        }
+    };
+    let path = if parent_path.is_empty() {
+        field.name().to_string()
+    } else {
+        format!("{}.{}", parent_path, field.name())
+    };
+
+    let children = if field.is_group() {
+        field
+            .get_fields()
+            .iter()
+            .map(|child| schema_node(child, &path, max_definition_level, max_repetition_level))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    SchemaNode {
+        name: field.name().to_string(),
+        path,
+        repetition: format!("{:?}", repetition),
+        physical_type: physical_type_name(field),
+        logical_type: annotation_name(field),
+        max_definition_level,
+        max_repetition_level,
+        children,
+    }
+}
+
+/// Read the footer of `path`, returning its summary and the decoded footer's
+/// in-memory size.
+fn compute_metadata(
+    path: &str,
+    mode: ReadMode,
+    on_stage: &dyn Fn(&str),
+) -> Result<(ParquetMetadata, usize), String> {
+    let source = FileSource::open(path, mode)?;
+    let reader = SerializedFileReader::new(source).map_err(|e| e.to_string())?;
+    on_stage(STAGE_FOOTER_PARSED);
 
     let metadata = reader.metadata();
     let schema = metadata.file_metadata().schema();
+    let schema_descr = metadata.file_metadata().schema_descr();
 
-    let columns: Vec<ColumnInfo> = schema
+    let mut columns: Vec<ColumnInfo> = schema
         .get_fields()
         .iter()
         .map(|field| {
-            let physical_type = format!("{:?}", field.get_physical_type());
-            let logical_type = if let Some(lt) = field.get_basic_info().logical_type() {
-                Some(logical_type_to_string(&lt))
-            } else if field.get_basic_info().converted_type() != parquet::basic::ConvertedType::NONE
-            {
-                Some(converted_type_to_string(field.get_basic_info().converted_type()))
-            } else {
-                None
-            };
+            let physical_type = physical_type_name(field).unwrap_or_else(|| "GROUP".to_string());
+            let logical_type = annotation_name(field);
+            let repetition = repetition_of(field);
 
             ColumnInfo {
                 name: field.name().to_string(),
@@ -180,19 +968,85 @@ fn compute_metadata(path: &str) -> Result<ParquetMetadata, String> {
                     .unwrap_or_else(|| physical_type.clone()),
                 logical_type,
                 physical_type,
+                repetition: format!("{:?}", repetition),
+                nullable: repetition == parquet::basic::Repetition::OPTIONAL,
+                encodings: Vec::new(),
+                compression: Vec::new(),
+                statistics: None,
             }
         })
         .collect();
+    on_stage(STAGE_SCHEMA_LOADED);
+
+    for (column, field) in columns.iter_mut().zip(schema.get_fields()) {
+        // Nested fields span several leaf columns; report the union over them
+        let leaves: Vec<usize> = schema_descr
+            .columns()
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.path().parts().first().map(String::as_str) == Some(field.name()))
+            .map(|(i, _)| i)
+            .collect();
+        for row_group in metadata.row_groups() {
+            for &i in &leaves {
+                let chunk = row_group.column(i);
+                for encoding in chunk.encodings() {
+                    let encoding = encoding.to_string();
+                    if !column.encodings.contains(&encoding) {
+                        column.encodings.push(encoding);
+                    }
+                }
+                let codec = inspect::compression_to_string(chunk.compression());
+                if !column.compression.contains(&codec) {
+                    column.compression.push(codec);
+                }
+            }
+        }
+    }
+
+    for (column, field) in columns.iter_mut().zip(schema.get_fields()) {
+        // Statistics only exist for leaf columns, so nested fields have none
+        if !field.is_primitive() {
+            continue;
+        }
+        column.statistics = schema_descr
+            .columns()
+            .iter()
+            .position(|c| c.path().parts() == [field.name().to_string()])
+            .and_then(|i| {
+                let chunks: Vec<_> = metadata
+                    .row_groups()
+                    .iter()
+                    .map(|rg| rg.column(i))
+                    .collect();
+                inspect::aggregate_statistics(&chunks)
+            });
+    }
+    on_stage(STAGE_STATISTICS_LOADED);
 
-    Ok(ParquetMetadata {
+    let meta = ParquetMetadata {
         num_rows: metadata.file_metadata().num_rows(),
         num_columns: columns.len(),
         columns,
-    })
+        schema: schema
+            .get_fields()
+            .iter()
+            .map(|field| schema_node(field, "", 0, 0))
+            .collect(),
+    };
+    Ok((meta, metadata.memory_size()))
 }
 
+use arrow::array::{Array, AsArray, Float64Array, Int64Array, UInt32Array, UInt64Array};
+use arrow::compute::take_record_batch;
+use arrow::datatypes::{DataType, SchemaRef};
 use arrow::json::LineDelimitedWriter;
 use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use base64::{engine::general_purpose, Engine as _};
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 
 pub fn batches_to_json_bytes(batches: &[RecordBatch]) -> Result<Vec<u8>, String> {
     let mut buf = Vec::new();
@@ -210,112 +1064,2348 @@ pub fn batches_to_json_bytes(batches: &[RecordBatch]) -> Result<Vec<u8>, String>
     Ok(buf)
 }
 
-fn build_where_clause(filter: Option<String>) -> String {
-    if let Some(f) = filter {
-        if !f.trim().is_empty() {
-            format!("WHERE {}", f)
-        } else {
-            String::new()
+/// Row count for `filter` judged from row-group statistics alone, without
+/// reading data. Row groups the statistics can't decide are counted in full,
+/// so an approximate count is an upper bound.
+pub fn estimate_count(
+    cache: &ParquetCache,
+    path: &str,
+    filter: Option<&FilterExpr>,
+) -> Result<CountEstimate, String> {
+    // Reject the same malformed filters the exact count would
+    if filter.is_some() {
+        let columns = ColumnResolver::new(Arc::new(schema::arrow_schema(path)?));
+        build_where_clause(filter, &columns)?;
+    }
+
+    let source = FileSource::open(path, cache.read_mode())?;
+    let reader = SerializedFileReader::new(source).map_err(|e| e.to_string())?;
+
+    let mut count = 0;
+    let mut approximate = false;
+    for row_group in reader.metadata().row_groups() {
+        let matched = filter.map_or(RowGroupMatch::All, |f| {
+            pruning::classify_row_group(row_group, f)
+        });
+        match matched {
+            RowGroupMatch::All => count += row_group.num_rows().max(0) as usize,
+            RowGroupMatch::Partial => {
+                count += row_group.num_rows().max(0) as usize;
+                approximate = true;
+            }
+            RowGroupMatch::Empty => {}
         }
-    } else {
-        String::new()
     }
+    Ok(CountEstimate { count, approximate })
 }
 
-pub async fn read_data(
+/// Which row groups statistics let a reader skip for `filter` and which it
+/// has to scan, with the statistics of the filtered columns behind each
+/// verdict. Shows whether sorting a file by a key would make filters on it
+/// cheaper: row groups with overlapping ranges can't be pruned.
+pub fn pruning_report(
     cache: &ParquetCache,
     path: &str,
-    offset: usize,
-    limit: usize,
-    filter: Option<String>,
-) -> Result<Vec<Value>, String> {
-    let ctx = cache.get_or_create_session(path).await?;
+    filter: &FilterExpr,
+) -> Result<PruningReport, String> {
+    let columns = ColumnResolver::new(Arc::new(schema::arrow_schema(path)?));
+    build_where_clause(Some(filter), &columns)?;
 
-    let where_clause = build_where_clause(filter);
-    let query = format!(
-        "SELECT * FROM t {} LIMIT {} OFFSET {}",
-        where_clause, limit, offset
-    );
-
-    // Execute the query
-    let df = ctx
-        .sql(&query)
-        .await
-        .map_err(|e| format!("Query execution failed: {}", e))?;
+    let source = FileSource::open(path, cache.read_mode())?;
+    let reader = SerializedFileReader::new(source).map_err(|e| e.to_string())?;
+    let filter_columns = pruning::filter_columns(filter);
 
-    // Collect results
-    let batches = df
-        .collect()
-        .await
-        .map_err(|e| format!("Failed to collect results: {}", e))?;
+    let mut report = PruningReport {
+        row_groups: Vec::new(),
+        pruned_row_groups: 0,
+        scanned_row_groups: 0,
+        pruned_rows: 0,
+        scanned_rows: 0,
+        pruned_bytes: 0,
+        scanned_bytes: 0,
+    };
+    for (index, row_group) in reader.metadata().row_groups().iter().enumerate() {
+        let verdict = match pruning::classify_row_group(row_group, filter) {
+            RowGroupMatch::Empty => PruneVerdict::Pruned,
+            RowGroupMatch::Partial => PruneVerdict::Scan,
+            RowGroupMatch::All => PruneVerdict::AllMatch,
+        };
+        if verdict == PruneVerdict::Pruned {
+            report.pruned_row_groups += 1;
+            report.pruned_rows += row_group.num_rows();
+            report.pruned_bytes += row_group.compressed_size();
+        } else {
+            report.scanned_row_groups += 1;
+            report.scanned_rows += row_group.num_rows();
+            report.scanned_bytes += row_group.compressed_size();
+        }
 
-    let buf = batches_to_json_bytes(&batches)?;
-    let rows: Result<Vec<Value>, _> = serde_json::Deserializer::from_slice(&buf)
-        .into_iter::<Value>()
-        .collect();
-    rows.map_err(|e| format!("Failed to parse JSON results: {}", e))
+        let statistics = filter_columns
+            .iter()
+            .map(|column| {
+                let stats = row_group
+                    .columns()
+                    .iter()
+                    .find(|chunk| chunk.column_path().string() == *column)
+                    .and_then(|chunk| chunk.statistics())
+                    .map(inspect::statistics_to_model);
+                (column.clone(), stats)
+            })
+            .collect();
+        report.row_groups.push(RowGroupPruning {
+            index,
+            num_rows: row_group.num_rows(),
+            compressed_size: row_group.compressed_size(),
+            verdict,
+            statistics,
+        });
+    }
+    Ok(report)
 }
 
-pub async fn count_data(
-    cache: &ParquetCache,
-    path: &str,
-    filter: Option<String>,
-) -> Result<usize, String> {
-    let ctx = cache.get_or_create_session(path).await?;
-
-    let where_clause = build_where_clause(filter);
-    let query = format!("SELECT COUNT(*) FROM t {}", where_clause);
+/// How long a query may run when the caller gives no timeout.
+pub const DEFAULT_QUERY_TIMEOUT_MS: u64 = 60_000;
 
-    // Execute the query
-    let df = ctx
-        .sql(&query)
+/// Run a query future, abandoning it once `timeout_ms` (or the default) elapses.
+/// Dropping the future cancels the plan, though an operator already in the middle
+/// of computing a batch finishes that batch first.
+pub(crate) async fn with_timeout<T>(
+    timeout_ms: Option<u64>,
+    query: impl std::future::Future<Output = Result<T, String>>,
+) -> Result<T, String> {
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_QUERY_TIMEOUT_MS));
+    tokio::time::timeout(timeout, query)
         .await
-        .map_err(|e| format!("Query execution failed: {}", e))?;
+        .map_err(|_| format!("Query timed out after {} ms", timeout.as_millis()))?
+}
 
-    // Collect results
-    let batches = df
-        .collect()
-        .await
-        .map_err(|e| format!("Failed to collect results: {}", e))?;
+/// Column numbering the rows of `FILE_ORDER_TABLE` in file order.
+const FILE_ROW: &str = "__file_row";
 
+/// `t` with every row numbered in file order as `FILE_ROW`. The numbers only
+/// follow the file in a `file_order_session`.
+const FILE_ORDER_TABLE: &str = "(SELECT *, ROW_NUMBER() OVER () AS __file_row FROM t)";
+
+/// The session `ctx` scanning in a single partition, so rows reach a query in
+/// the order they are stored: row groups in order, files by path.
+pub(crate) fn file_order_session(ctx: &SessionContext) -> SessionContext {
+    let mut state = ctx.state();
+    state.config_mut().options_mut().execution.target_partitions = 1;
+    SessionContext::new_with_state(state)
+}
+
+/// ORDER BY clause for `sort` over `FILE_ORDER_TABLE`, breaking ties by file
+/// position so rows with equal keys always come out in the same order.
+fn file_order_by_clause(
+    sort: Option<Vec<SortSpec>>,
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    let order_by_clause = build_order_by_clause(sort, columns)?;
+    Ok(if order_by_clause.is_empty() {
+        format!("ORDER BY {}", FILE_ROW)
+    } else {
+        format!("{}, {}", order_by_clause, FILE_ROW)
+    })
+}
+
+/// The rows matching `filter` under `sort`, with ties (and every row when
+/// there is no sort) in file order, so views number rows the same way
+/// `find_row_positions` does. Run it on a `file_order_session`.
+pub(crate) fn select_view_query(
+    filter: Option<&FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    let where_clause = build_where_clause(filter, columns)?;
+    let order_by_clause = file_order_by_clause(sort, columns)?;
+    Ok(format!(
+        "SELECT * EXCLUDE ({}) FROM {} {} {}",
+        FILE_ROW, FILE_ORDER_TABLE, where_clause, order_by_clause
+    ))
+}
+
+/// Page of `select_view_query`. Run it on a `file_order_session`.
+fn select_page_query(
+    offset: usize,
+    limit: usize,
+    filter: Option<&FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    Ok(format!(
+        "{} LIMIT {} OFFSET {}",
+        select_view_query(filter, sort, columns)?,
+        limit,
+        offset
+    ))
+}
+
+/// Row groups holding rows `offset..offset + limit`, as
+/// `(row group, rows to skip in it, rows to take from it)`.
+fn row_group_spans(row_counts: &[i64], offset: usize, limit: usize) -> Vec<(usize, usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0usize;
+    let mut remaining = limit;
+    for (index, &count) in row_counts.iter().enumerate() {
+        let count = count.max(0) as usize;
+        let end = start + count;
+        if remaining > 0 && offset < end {
+            let skip = offset.saturating_sub(start);
+            let take = remaining.min(count - skip);
+            spans.push((index, skip, take));
+            remaining -= take;
+        }
+        start = end;
+    }
+    spans
+}
+
+/// Footer of `path` as the Arrow reader needs it.
+async fn reader_metadata(path: &str, mode: ReadMode) -> Result<ArrowReaderMetadata, String> {
+    let owned_path = path.to_string();
+    tokio::task::spawn_blocking(move || {
+        let source = FileSource::open(&owned_path, mode)?;
+        ArrowReaderMetadata::load(&source, Default::default()).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Unfiltered, unsorted page decoded straight from the row groups that hold it,
+/// reading them in parallel. Skips query planning and never touches the other
+/// row groups, which matters for deep pages of very large files.
+async fn read_page_direct(
+    path: &str,
+    mode: ReadMode,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<RecordBatch>, String> {
+    let metadata = reader_metadata(path, mode).await?;
+    let row_counts: Vec<i64> = metadata
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|rg| rg.num_rows())
+        .collect();
+    let spans = row_group_spans(&row_counts, offset, limit);
+    read_spans(path, mode, metadata, spans).await
+}
+
+/// Decode `(row group, rows to skip in it, rows to take from it)` spans of
+/// `path` in parallel, in order.
+async fn read_spans(
+    path: &str,
+    mode: ReadMode,
+    metadata: ArrowReaderMetadata,
+    spans: Vec<(usize, usize, usize)>,
+) -> Result<Vec<RecordBatch>, String> {
+    let reads = spans.into_iter().map(|(row_group, skip, take)| {
+        let path = path.to_string();
+        let metadata = metadata.clone();
+        tokio::task::spawn_blocking(move || -> Result<Vec<RecordBatch>, String> {
+            let source = FileSource::open(&path, mode)?;
+            ParquetRecordBatchReaderBuilder::new_with_metadata(source, metadata)
+                .with_row_groups(vec![row_group])
+                .with_row_selection(RowSelection::from(vec![
+                    RowSelector::skip(skip),
+                    RowSelector::select(take),
+                ]))
+                .with_batch_size(take.clamp(1, 8192))
+                .build()
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
+        })
+    });
+
+    let mut batches = Vec::new();
+    for read in futures::future::join_all(reads).await {
+        batches.extend(read.map_err(|e| e.to_string())??);
+    }
     if batches.is_empty() {
-        return Ok(0);
+        // Past the end: keep the schema for callers that serialize it
+        batches.push(RecordBatch::new_empty(metadata.schema().clone()));
     }
+    Ok(batches)
+}
 
-    // Extract count from the first batch
-    let batch = &batches[0];
-    if batch.num_rows() == 0 {
-        return Ok(0);
+/// A page of rows. With `flatten`, struct columns are expanded into dotted
+/// columns (`address.city`) while lists and maps stay single JSON values.
+#[allow(clippy::too_many_arguments)]
+pub async fn read_data(
+    cache: &ParquetCache,
+    path: &str,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    timeout_ms: Option<u64>,
+    flatten: bool,
+) -> Result<Vec<Value>, String> {
+    let batches = read_page_batches(cache, path, offset, limit, filter, sort, timeout_ms).await?;
+    page_rows(cache, &batches, flatten)
+}
+
+/// A page of rows together with the total and filtered row counts, all
+/// three computed concurrently and each bounded by `timeout_ms`. Counts are
+/// cached, so paging through the same view only counts once.
+#[allow(clippy::too_many_arguments)]
+pub async fn read_window(
+    cache: &ParquetCache,
+    path: &str,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    timeout_ms: Option<u64>,
+    flatten: bool,
+) -> Result<DataWindow, String> {
+    let filtered = async {
+        match &filter {
+            Some(filter) => {
+                let count = count_data(cache, path, Some(filter.clone()));
+                with_timeout(timeout_ms, count).await.map(Some)
+            }
+            None => Ok(None),
+        }
+    };
+    let (rows, total_rows, filtered_rows) = futures::try_join!(
+        read_data(
+            cache,
+            path,
+            offset,
+            limit,
+            filter.clone(),
+            sort,
+            timeout_ms,
+            flatten
+        ),
+        with_timeout(timeout_ms, count_data(cache, path, None)),
+        filtered,
+    )?;
+    Ok(DataWindow {
+        rows,
+        total_rows,
+        filtered_rows: filtered_rows.unwrap_or(total_rows),
+    })
+}
+
+/// Same page as `read_data`, encoded as an Arrow IPC stream so the frontend
+/// can decode it directly instead of parsing JSON. Display options apply to
+/// timestamps and binary values as they do for JSON rows.
+#[allow(clippy::too_many_arguments)]
+pub async fn read_data_ipc(
+    cache: &ParquetCache,
+    path: &str,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    timeout_ms: Option<u64>,
+    flatten: bool,
+) -> Result<Vec<u8>, String> {
+    let mut batches =
+        read_page_batches(cache, path, offset, limit, filter, sort, timeout_ms).await?;
+    if flatten {
+        batches = batches
+            .iter()
+            .map(flatten_structs)
+            .collect::<Result<Vec<_>, _>>()?;
     }
+    let display = cache.display_options();
+    let batches = batches
+        .iter()
+        .map(|batch| render_for_display(batch, display))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let column = batch.column(0);
-    let count = column
-        .as_any()
-        .downcast_ref::<arrow::array::Int64Array>()
-        .ok_or_else(|| "Failed to downcast count result".to_string())?
-        .value(0);
+    let schema = batches
+        .first()
+        .map(|batch| batch.schema())
+        .unwrap_or_else(|| Arc::new(arrow::datatypes::Schema::empty()));
+    let mut writer = arrow::ipc::writer::StreamWriter::try_new(Vec::new(), &schema)
+        .map_err(|e| e.to_string())?;
+    for batch in &batches {
+        writer.write(batch).map_err(|e| e.to_string())?;
+    }
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+async fn read_page_batches(
+    cache: &ParquetCache,
+    path: &str,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<RecordBatch>, String> {
+    let direct = filter.is_none() && sort.is_none() && std::path::Path::new(path).is_file();
+    // Plain pages name no columns, so only queries need the schema
+    let columns = if direct {
+        ColumnResolver::new(Arc::new(arrow::datatypes::Schema::empty()))
+    } else {
+        ColumnResolver::for_session(&cache.get_or_create_session(path).await?).await?
+    };
+    let query = select_page_query(offset, limit, filter.as_ref(), sort, &columns)?;
+    if let Some(batches) = cache.cached_page(path, &query) {
+        return Ok(batches);
+    }
 
-    Ok(count as usize)
+    // Plain pages of a single file map directly onto row groups
+    let batches = if direct {
+        with_timeout(
+            timeout_ms,
+            read_page_direct(path, cache.read_mode(), offset, limit),
+        )
+        .await?
+    } else {
+        query_page(cache, path, &query, timeout_ms).await?
+    };
+    cache.store_page(path, &query, &batches);
+    Ok(batches)
 }
 
-pub async fn execute_sql_with_cache(
+async fn query_page(
     cache: &ParquetCache,
-    file_path: &str,
+    path: &str,
     query: &str,
-) -> Result<(Vec<arrow::record_batch::RecordBatch>, arrow::datatypes::SchemaRef), String> {
-    let ctx = cache.get_or_create_session(file_path).await?;
+    timeout_ms: Option<u64>,
+) -> Result<Vec<RecordBatch>, String> {
+    let ctx = file_order_session(&cache.get_or_create_session(path).await?);
 
-    let df = ctx
-        .sql(query)
+    with_timeout(timeout_ms, async {
+        // Execute the query
+        let df = ctx
+            .sql(query)
+            .await
+            .map_err(|e| format!("Query execution failed: {}", e))?;
+
+        // Collect results
+        df.collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    })
+    .await
+}
+
+fn page_rows(
+    cache: &ParquetCache,
+    batches: &[RecordBatch],
+    flatten: bool,
+) -> Result<Vec<Value>, String> {
+    if flatten {
+        let flattened = batches
+            .iter()
+            .map(flatten_structs)
+            .collect::<Result<Vec<_>, _>>()?;
+        return cache.display_rows(&flattened);
+    }
+    cache.display_rows(batches)
+}
+
+/// Rows `offset..offset + limit` of a single row group, decoded directly from
+/// the file without going through a query, so one row group can be inspected
+/// without scanning the others.
+pub fn read_row_group(
+    path: &str,
+    row_group: usize,
+    offset: usize,
+    limit: usize,
+    display: DisplayOptions,
+    mode: ReadMode,
+) -> Result<RowGroupPage, String> {
+    let source = FileSource::open(path, mode)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(source).map_err(|e| e.to_string())?;
+
+    let row_groups = builder.metadata().row_groups();
+    if row_group >= row_groups.len() {
+        return Err(format!(
+            "Row group {} does not exist (file has {})",
+            row_group,
+            row_groups.len()
+        ));
+    }
+    let first_row: i64 = row_groups[..row_group].iter().map(|rg| rg.num_rows()).sum();
+    let num_rows = row_groups[row_group].num_rows();
+
+    let offset = offset.min(num_rows.max(0) as usize);
+    let selection = RowSelection::from(vec![RowSelector::skip(offset), RowSelector::select(limit)]);
+    let batches = builder
+        .with_row_groups(vec![row_group])
+        .with_row_selection(selection)
+        .with_batch_size(limit.clamp(1, 8192))
+        .build()
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(RowGroupPage {
+        row_group,
+        first_row,
+        num_rows,
+        rows: display_rows(&batches, display)?,
+    })
+}
+
+/// Row groups of a damaged file, split by whether they decode.
+#[derive(Clone)]
+struct SalvagePlan {
+    /// Readable row groups as `(index, rows)`, in file order.
+    readable: Vec<(usize, i64)>,
+    skipped: Vec<SkippedRange>,
+}
+
+/// Which row groups of `path` decode, diagnosed once and remembered until the
+/// file is evicted.
+async fn salvage_plan(cache: &ParquetCache, path: &str) -> Result<SalvagePlan, String> {
+    if let Some(plan) = cache.salvage.lock().map_err(|e| e.to_string())?.get(path) {
+        return Ok(plan.clone());
+    }
+    let owned_path = path.to_string();
+    let diagnosis = tokio::task::spawn_blocking(move || inspect::diagnose_parquet(&owned_path))
         .await
-        .map_err(|e| format!("SQL execution failed: {}", e))?;
+        .map_err(|e| e.to_string())??;
+    if !diagnosis.metadata_ok {
+        return Err(diagnosis.issues.join("; "));
+    }
 
-    let schema = df.schema().inner().clone();
+    let (readable, skipped): (Vec<_>, Vec<_>) =
+        diagnosis.row_groups.into_iter().partition(|rg| rg.readable);
+    let plan = SalvagePlan {
+        readable: readable.iter().map(|rg| (rg.index, rg.num_rows)).collect(),
+        skipped: skipped
+            .into_iter()
+            .map(|rg| SkippedRange {
+                row_group: rg.index,
+                first_row: rg.first_row,
+                num_rows: rg.num_rows,
+                error: rg.error,
+            })
+            .collect(),
+    };
+    cache
+        .salvage
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(path.to_string(), plan.clone());
+    Ok(plan)
+}
 
-    let batches = df
+/// Unfiltered, unsorted page of the readable row groups in `plan`, decoding
+/// only the ones the page covers.
+async fn salvage_page(
+    cache: &ParquetCache,
+    path: &str,
+    plan: &SalvagePlan,
+    offset: usize,
+    limit: usize,
+) -> Result<Vec<RecordBatch>, String> {
+    let mode = cache.read_mode();
+    let metadata = reader_metadata(path, mode).await?;
+    let row_counts: Vec<i64> = plan.readable.iter().map(|&(_, rows)| rows).collect();
+    let spans = row_group_spans(&row_counts, offset, limit)
+        .into_iter()
+        .map(|(index, skip, take)| (plan.readable[index].0, skip, take))
+        .collect();
+    read_spans(path, mode, metadata, spans).await
+}
+
+/// The readable row groups of a damaged file as a table partition, decoded
+/// batch by batch while a query runs instead of loaded up front.
+struct SalvageStream {
+    path: String,
+    mode: ReadMode,
+    metadata: ArrowReaderMetadata,
+    row_groups: Vec<usize>,
+}
+
+impl PartitionStream for SalvageStream {
+    fn schema(&self) -> &SchemaRef {
+        self.metadata.schema()
+    }
+
+    fn execute(&self, ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let mut builder = RecordBatchReceiverStreamBuilder::new(self.schema().clone(), 2);
+        let tx = builder.tx();
+        let path = self.path.clone();
+        let mode = self.mode;
+        let metadata = self.metadata.clone();
+        let row_groups = self.row_groups.clone();
+        let batch_size = ctx.session_config().batch_size();
+        builder.spawn_blocking(move || {
+            let source = FileSource::open(&path, mode).map_err(DataFusionError::Execution)?;
+            let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(source, metadata)
+                .with_row_groups(row_groups)
+                .with_batch_size(batch_size)
+                .build()?;
+            for batch in reader {
+                let batch = batch.map_err(DataFusionError::from);
+                // The receiver is gone once the query has what it needs
+                if tx.blocking_send(batch).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        });
+        builder.build()
+    }
+}
+
+/// Session over only the readable row groups in `plan`, configured like the
+/// cache's own sessions and read in the cache's read mode.
+async fn salvage_session(
+    cache: &ParquetCache,
+    path: &str,
+    plan: &SalvagePlan,
+) -> Result<SessionContext, String> {
+    let mode = cache.read_mode();
+    let stream = SalvageStream {
+        path: path.to_string(),
+        mode,
+        metadata: reader_metadata(path, mode).await?,
+        row_groups: plan.readable.iter().map(|&(index, _)| index).collect(),
+    };
+    let table = StreamingTable::try_new(stream.schema().clone(), vec![Arc::new(stream)])
+        .map_err(|e| e.to_string())?;
+    let ctx = cache.session_context()?;
+    udf::register_udfs(&ctx);
+    ctx.register_table("t", Arc::new(table))
+        .map_err(|e| e.to_string())?;
+
+    Ok(ctx)
+}
+
+/// Like `read_data`, but when the read fails because of damaged row groups,
+/// answers the same query from the row groups that are still readable and
+/// reports the ones that were skipped. Any other failure, such as a filter on
+/// an unknown column, is returned as it is.
+pub async fn read_data_lenient(
+    cache: &ParquetCache,
+    path: &str,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+) -> Result<LenientData, String> {
+    // Plan the query before reading, so a bad filter or sort is not taken for damage
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+    let query = select_page_query(offset, limit, filter.as_ref(), sort.clone(), &columns)?;
+    ctx.sql(&query)
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+    let known = cache
+        .salvage
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(path)
+        .cloned();
+    let plan = match known {
+        Some(plan) if !plan.skipped.is_empty() => plan,
+        _ => match read_data(
+            cache,
+            path,
+            offset,
+            limit,
+            filter.clone(),
+            sort.clone(),
+            None,
+            false,
+        )
+        .await
+        {
+            Ok(rows) => {
+                return Ok(LenientData {
+                    rows,
+                    skipped: Vec::new(),
+                })
+            }
+            Err(e) => {
+                let plan = salvage_plan(cache, path).await?;
+                if plan.skipped.is_empty() {
+                    return Err(e);
+                }
+                plan
+            }
+        },
+    };
+
+    let batches = if filter.is_none() && sort.is_none() {
+        salvage_page(cache, path, &plan, offset, limit).await?
+    } else {
+        let ctx = file_order_session(&salvage_session(cache, path, &plan).await?);
+        let columns = ColumnResolver::for_session(&ctx).await?;
+        let query = select_page_query(offset, limit, filter.as_ref(), sort, &columns)?;
+        collect_query(&ctx, &query).await?
+    };
+
+    Ok(LenientData {
+        rows: cache.display_rows(&batches)?,
+        skipped: plan.skipped,
+    })
+}
+
+/// Position after the last row of a page in keyset pagination: the sort key of
+/// that row and how many rows sharing exactly that key were already returned.
+#[derive(Debug, Serialize, Deserialize)]
+struct PageCursor {
+    keys: Vec<Value>,
+    ties: usize,
+}
+
+impl PageCursor {
+    fn encode(&self) -> Result<String, String> {
+        let json = serde_json::to_vec(self).map_err(|e| e.to_string())?;
+        Ok(general_purpose::URL_SAFE_NO_PAD.encode(json))
+    }
+
+    fn decode(cursor: &str) -> Result<Self, String> {
+        let json = general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| "Invalid cursor".to_string())?;
+        serde_json::from_slice(&json).map_err(|_| "Invalid cursor".to_string())
+    }
+}
+
+/// Name of the column holding the value of sort key `index` in a keyset page.
+fn sort_key_column(index: usize) -> String {
+    format!("__sort_key{}", index)
+}
+
+/// Sort key of a row of a keyset page, read from its `sort_key_column`s.
+fn sort_key_of(row: &Value, keys: usize) -> Vec<Value> {
+    (0..keys)
+        .map(|i| row.get(sort_key_column(i)).cloned().unwrap_or(Value::Null))
         .collect()
+}
+
+/// Predicate selecting rows whose sort key is equal to or after `keys`,
+/// honoring direction and null placement of every sort column.
+fn keyset_predicate(
+    sort: &[SortSpec],
+    keys: &[Value],
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    let mut equal_prefix: Vec<String> = Vec::new();
+    let mut branches: Vec<String> = Vec::new();
+
+    for (spec, key) in sort.iter().zip(keys) {
+        let col = columns.resolve(&spec.column)?;
+        // Engine default: nulls sort last ascending and first descending
+        let nulls_first = spec.nulls_first.unwrap_or(spec.descending);
+
+        let after = if key.is_null() {
+            if nulls_first {
+                Some(format!("{} IS NOT NULL", col))
+            } else {
+                None
+            }
+        } else {
+            let literal = compile_literal(key)?;
+            let cmp = format!(
+                "{} {} {}",
+                col,
+                if spec.descending { "<" } else { ">" },
+                literal
+            );
+            Some(if nulls_first {
+                cmp
+            } else {
+                format!("({} OR {} IS NULL)", cmp, col)
+            })
+        };
+
+        if let Some(after) = after {
+            let mut branch = equal_prefix.clone();
+            branch.push(after);
+            branches.push(format!("({})", branch.join(" AND ")));
+        }
+
+        equal_prefix.push(if key.is_null() {
+            format!("{} IS NULL", col)
+        } else {
+            format!("{} = {}", col, compile_literal(key)?)
+        });
+    }
+
+    branches.push(format!("({})", equal_prefix.join(" AND ")));
+    Ok(branches.join(" OR "))
+}
+
+/// Keyset pagination: instead of OFFSET, each page starts from the sort key
+/// stored in `cursor`, so deep pages cost the same as the first one. Rows with
+/// identical sort keys are skipped by count, so a unique sort key gives exact paging.
+pub async fn read_data_page(
+    cache: &ParquetCache,
+    path: &str,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    sort: Vec<SortSpec>,
+    cursor: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<DataPage, String> {
+    if sort.is_empty() {
+        return Err("Cursor pagination requires at least one sort column".to_string());
+    }
+
+    let cursor = cursor.as_deref().map(PageCursor::decode).transpose()?;
+    if let Some(c) = &cursor {
+        if c.keys.len() != sort.len() {
+            return Err("Cursor does not match the current sort".to_string());
+        }
+    }
+
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let where_clause = match &cursor {
+        Some(c) => build_where_clause_with(
+            filter.as_ref(),
+            &format!("({})", keyset_predicate(&sort, &c.keys, &columns)?),
+            &columns,
+        )?,
+        None => build_where_clause(filter.as_ref(), &columns)?,
+    };
+    let order_by_clause = build_order_by_clause(Some(sort.clone()), &columns)?;
+    // The sort keys are selected as the ORDER BY resolves them, so a struct
+    // field or a differently cased name still yields the row's actual key
+    let key_columns = sort
+        .iter()
+        .enumerate()
+        .map(|(i, spec)| {
+            Ok(format!(
+                "{} AS {}",
+                columns.resolve(&spec.column)?,
+                sort_key_column(i)
+            ))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let skip = cursor.as_ref().map_or(0, |c| c.ties);
+    let query = format!(
+        "SELECT *, {} FROM t {} {} LIMIT {} OFFSET {}",
+        key_columns.join(", "),
+        where_clause,
+        order_by_clause,
+        limit,
+        skip
+    );
+
+    let batches = with_timeout(timeout_ms, collect_query(&ctx, &query)).await?;
+    let (data, key_batches): (Vec<_>, Vec<_>) = batches
+        .iter()
+        .map(|batch| -> Result<_, arrow::error::ArrowError> {
+            let split = batch.num_columns() - sort.len();
+            let data = batch.project(&(0..split).collect::<Vec<_>>())?;
+            let keys = batch.project(&(split..batch.num_columns()).collect::<Vec<_>>())?;
+            Ok((data, keys))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .unzip();
+    // Cursor keys must stay in query form; only the returned rows are rendered
+    let rows = batches_to_rows(&key_batches)?;
+
+    let next_cursor = match rows.last() {
+        Some(last) if rows.len() == limit => {
+            let keys = sort_key_of(last, sort.len());
+            let trailing = rows
+                .iter()
+                .rev()
+                .take_while(|row| sort_key_of(row, sort.len()) == keys)
+                .count();
+            // A page made only of ties continues the run started by the previous cursor
+            let ties = match &cursor {
+                Some(c) if trailing == rows.len() && c.keys == keys => c.ties + trailing,
+                _ => trailing,
+            };
+            Some(PageCursor { keys, ties }.encode()?)
+        }
+        _ => None,
+    };
+
+    Ok(DataPage {
+        rows: cache.display_rows(&data)?,
+        next_cursor,
+    })
+}
+
+/// The single value produced by a `LIMIT 1` query, as a one-element array.
+async fn query_single_cell(
+    ctx: &datafusion::execution::context::SessionContext,
+    query: &str,
+    row: usize,
+) -> Result<arrow::array::ArrayRef, String> {
+    let batches = collect_query(ctx, query).await?;
+    batches
+        .iter()
+        .find(|b| b.num_rows() > 0)
+        .map(|b| b.column(0).slice(0, 1))
+        .ok_or_else(|| format!("Row {} is out of range", row))
+}
+
+/// Bytes of the value at `row` (position in the file) of `column`, which must be
+/// a binary or string column.
+pub async fn cell_bytes(
+    cache: &ParquetCache,
+    path: &str,
+    row: usize,
+    column: &str,
+) -> Result<Option<CellBytes>, String> {
+    let ctx = file_order_session(&cache.get_or_create_session(path).await?);
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let query = format!(
+        "SELECT {} FROM {} ORDER BY {} LIMIT 1 OFFSET {}",
+        columns.resolve(column)?,
+        FILE_ORDER_TABLE,
+        FILE_ROW,
+        row
+    );
+    let values = query_single_cell(&ctx, &query, row).await?;
+    if values.is_null(0) {
+        return Ok(None);
+    }
+
+    let bytes: &[u8] = match values.data_type() {
+        DataType::Binary => values.as_binary::<i32>().value(0),
+        DataType::LargeBinary => values.as_binary::<i64>().value(0),
+        DataType::FixedSizeBinary(_) => values.as_fixed_size_binary().value(0),
+        DataType::Utf8 => values.as_string::<i32>().value(0).as_bytes(),
+        DataType::LargeUtf8 => values.as_string::<i64>().value(0).as_bytes(),
+        other => {
+            return Err(format!(
+                "Column {} is not a binary or string column ({})",
+                column, other
+            ))
+        }
+    };
+
+    Ok(Some(CellBytes {
+        size: bytes.len(),
+        data: general_purpose::STANDARD.encode(bytes),
+    }))
+}
+
+/// Rows of the view described by `filter` and `sort` picked by `selection`,
+/// in view order. Positions are counted the way pages are, in file order on a
+/// single-partition scan, so they match the rows shown in the grid; keys match
+/// rows whose key columns equal them.
+pub async fn selected_rows(
+    cache: &ParquetCache,
+    path: &str,
+    selection: &SelectedRows,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+) -> Result<Vec<RecordBatch>, String> {
+    let ctx = file_order_session(&cache.get_or_create_session(path).await?);
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    match selection {
+        SelectedRows::Indices { indices } => {
+            let mut wanted = indices.clone();
+            wanted.sort_unstable();
+            wanted.dedup();
+            let Some(last) = wanted.last() else {
+                return Ok(Vec::new());
+            };
+            let query = select_page_query(0, last + 1, filter.as_ref(), sort, &columns)?;
+            let mut stream = ctx
+                .sql(&query)
+                .await
+                .map_err(|e| format!("Query execution failed: {}", e))?
+                .execute_stream()
+                .await
+                .map_err(|e| format!("Failed to execute query: {}", e))?;
+
+            let mut wanted = wanted.into_iter().peekable();
+            let mut position = 0;
+            let mut batches = Vec::new();
+            while let Some(batch) = stream.next().await {
+                let batch = batch.map_err(|e| format!("Failed to collect results: {}", e))?;
+                let end = position + batch.num_rows();
+                let mut take = Vec::new();
+                while let Some(index) = wanted.next_if(|index| *index < end) {
+                    take.push((index - position) as u32);
+                }
+                if !take.is_empty() {
+                    batches.push(
+                        take_record_batch(&batch, &UInt32Array::from(take))
+                            .map_err(|e| e.to_string())?,
+                    );
+                }
+                position = end;
+                if wanted.peek().is_none() {
+                    break;
+                }
+            }
+            match wanted.next() {
+                Some(index) => Err(format!("Row {} is out of range", index)),
+                None => Ok(batches),
+            }
+        }
+        SelectedRows::Keys {
+            columns: key_columns,
+            values,
+        } => {
+            if key_columns.is_empty() {
+                return Err("At least one key column is required".to_string());
+            }
+            let keys = values
+                .iter()
+                .map(|key| {
+                    if key.len() != key_columns.len() {
+                        return Err(format!(
+                            "Key {} has {} values for {} key columns",
+                            Value::Array(key.clone()),
+                            key.len(),
+                            key_columns.len()
+                        ));
+                    }
+                    let filters = key_columns
+                        .iter()
+                        .zip(key)
+                        .map(|(column, value)| FilterExpr::Condition {
+                            column: column.clone(),
+                            operator: if value.is_null() {
+                                FilterOperator::IsNull
+                            } else {
+                                FilterOperator::Eq
+                            },
+                            value: (!value.is_null()).then(|| value.clone()),
+                        })
+                        .collect();
+                    Ok(FilterExpr::And { filters })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            if keys.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            let selected = FilterExpr::And {
+                filters: filter
+                    .into_iter()
+                    .chain([FilterExpr::Or { filters: keys }])
+                    .collect(),
+            };
+            let query = format!(
+                "SELECT * EXCLUDE ({}) FROM {} {} {}",
+                FILE_ROW,
+                FILE_ORDER_TABLE,
+                build_where_clause(Some(&selected), &columns)?,
+                file_order_by_clause(sort, &columns)?
+            );
+            collect_query(&ctx, &query).await
+        }
+    }
+}
+
+/// Untruncated value of `column` at `row_index` of the view described by
+/// `filter` and `sort`: nested values as JSON, binary values as the full
+/// base64 payload and decimals as exact strings. Rows are numbered the way
+/// pages show them.
+pub async fn cell_value(
+    cache: &ParquetCache,
+    path: &str,
+    row_index: usize,
+    column: &str,
+    filter: Option<FilterExpr>,
+    sort: Option<Vec<SortSpec>>,
+) -> Result<CellValue, String> {
+    let ctx = file_order_session(&cache.get_or_create_session(path).await?);
+    let columns = ColumnResolver::for_session(&ctx).await?;
+    let (col, field) = columns.resolve_field(column)?;
+    let data_type = field.data_type().clone();
+
+    let where_clause = build_where_clause(filter.as_ref(), &columns)?;
+    let order_by_clause = file_order_by_clause(sort, &columns)?;
+    let query = format!(
+        "SELECT {} FROM {} {} {} LIMIT 1 OFFSET {}",
+        col, FILE_ORDER_TABLE, where_clause, order_by_clause, row_index
+    );
+    let values = query_single_cell(&ctx, &query, row_index).await?;
+
+    let value = if values.is_null(0) {
+        Value::Null
+    } else {
+        match &data_type {
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => {
+                Value::String(array_value_to_string(&values, 0).map_err(|e| e.to_string())?)
+            }
+            _ => {
+                let batch =
+                    RecordBatch::try_from_iter([("value", values)]).map_err(|e| e.to_string())?;
+                let display = DisplayOptions {
+                    binary: BinaryDisplay::Base64,
+                    ..cache.display_options()
+                };
+                let rendered = [render_for_display(&batch, display)?];
+                let mut rows = batches_to_rows(&rendered)?;
+                restore_non_finite(&rendered, &mut rows, NonFiniteDisplay::String);
+                rows.into_iter()
+                    .next()
+                    .and_then(|row| row.get("value").cloned())
+                    .unwrap_or(Value::Null)
+            }
+        }
+    };
+
+    Ok(CellValue {
+        column: column.to_string(),
+        data_type: data_type.to_string(),
+        value,
+    })
+}
+
+pub async fn count_data(
+    cache: &ParquetCache,
+    path: &str,
+    filter: Option<FilterExpr>,
+) -> Result<usize, String> {
+    // The footer already knows how many rows a single file has
+    if filter.is_none() && std::path::Path::new(path).is_file() {
+        let meta = cache.get_or_create_metadata(path)?;
+        return Ok(meta.num_rows.max(0) as usize);
+    }
+
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let where_clause = build_where_clause(filter.as_ref(), &columns)?;
+    let key = (path.to_string(), where_clause);
+    if let Some(&count) = cache.counts.lock().map_err(|e| e.to_string())?.get(&key) {
+        return Ok(count);
+    }
+    let query = format!("SELECT COUNT(*) FROM t {}", key.1);
+
+    // Execute the query
+    let df = ctx
+        .sql(&query)
         .await
-        .map_err(|e| format!("Failed to collect results: {}", e))?;
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+    // Collect results
+    let batches = df
+        .collect()
+        .await
+        .map_err(|e| format!("Failed to collect results: {}", e))?;
+
+    if batches.is_empty() {
+        return Ok(0);
+    }
+
+    // Extract count from the first batch
+    let batch = &batches[0];
+    if batch.num_rows() == 0 {
+        return Ok(0);
+    }
+
+    let column = batch.column(0);
+    let count = column
+        .as_any()
+        .downcast_ref::<arrow::array::Int64Array>()
+        .ok_or_else(|| "Failed to downcast count result".to_string())?
+        .value(0) as usize;
+
+    cache
+        .counts
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(key, count);
+    Ok(count)
+}
+
+/// Rows `execute_sql_with_cache` collects when the caller gives no cap.
+pub const DEFAULT_MAX_RESULT_ROWS: usize = 100_000;
+
+/// Result of `execute_sql_with_cache`, possibly cut off at the row cap.
+pub struct SqlOutput {
+    pub batches: Vec<RecordBatch>,
+    pub schema: arrow::datatypes::SchemaRef,
+    pub truncated: bool,
+    /// Exact row count when the result was complete, otherwise the planner's
+    /// estimate (if it has one).
+    pub total_rows_estimate: Option<usize>,
+}
+
+pub async fn execute_sql_with_cache(
+    cache: &ParquetCache,
+    file_path: &str,
+    query: &str,
+    timeout_ms: Option<u64>,
+    max_rows: Option<usize>,
+) -> Result<SqlOutput, String> {
+    let ctx = cache.session_for(file_path).await?;
+    let max_rows = max_rows.unwrap_or(DEFAULT_MAX_RESULT_ROWS);
+
+    with_timeout(timeout_ms, async {
+        let df = ctx
+            .sql(query)
+            .await
+            .map_err(|e| format!("SQL execution failed: {}", e))?;
+
+        let schema = df.schema().inner().clone();
+        let task_ctx = df.task_ctx();
+        let plan = df
+            .create_physical_plan()
+            .await
+            .map_err(|e| format!("SQL execution failed: {}", e))?;
+        let planned_rows = plan
+            .statistics()
+            .ok()
+            .and_then(|stats| stats.num_rows.get_value().copied());
+
+        let mut stream = datafusion::physical_plan::execute_stream(plan, Arc::new(task_ctx))
+            .map_err(|e| format!("Failed to execute query: {}", e))?;
+
+        // Stop pulling batches once the cap is reached so the rest is never computed
+        let mut batches = Vec::new();
+        let mut collected = 0;
+        let mut truncated = false;
+        while let Some(batch) = stream.next().await {
+            let batch = batch.map_err(|e| format!("Failed to collect results: {}", e))?;
+            if collected + batch.num_rows() > max_rows {
+                batches.push(batch.slice(0, max_rows - collected));
+                collected = max_rows;
+                truncated = true;
+                break;
+            }
+            collected += batch.num_rows();
+            batches.push(batch);
+        }
+
+        let total_rows_estimate = if truncated {
+            planned_rows.filter(|rows| *rows > collected)
+        } else {
+            Some(collected)
+        };
+
+        Ok(SqlOutput {
+            batches,
+            schema,
+            truncated,
+            total_rows_estimate,
+        })
+    })
+    .await
+}
+
+/// Start executing `query` and return its result as a stream of record batches.
+pub async fn execute_sql_stream(
+    cache: &ParquetCache,
+    file_path: &str,
+    query: &str,
+) -> Result<datafusion::physical_plan::SendableRecordBatchStream, String> {
+    let ctx = cache.session_for(file_path).await?;
+
+    let df = ctx
+        .sql(query)
+        .await
+        .map_err(|e| format!("SQL execution failed: {}", e))?;
+
+    df.execute_stream()
+        .await
+        .map_err(|e| format!("Failed to execute query: {}", e))
+}
+
+/// Position of the token the parser stopped at, found by re-running the SQL
+/// parser over located tokens. `found` is the token named in the error message.
+fn locate_parse_error(query: &str, found: &str) -> Option<(u64, u64)> {
+    use datafusion::sql::sqlparser::dialect::GenericDialect;
+    use datafusion::sql::sqlparser::parser::Parser;
+    use datafusion::sql::sqlparser::tokenizer::{Token, Tokenizer};
+
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, query)
+        .tokenize_with_location()
+        .ok()?;
+    let significant: Vec<_> = tokens
+        .iter()
+        .filter(|t| !matches!(t.token, Token::Whitespace(_)))
+        .collect();
+
+    if found == "EOF" {
+        // Point just past the last token
+        let last = significant.last()?;
+        let width = last.token.to_string().chars().count() as u64;
+        return Some((last.location.line, last.location.column + width));
+    }
+
+    let mut parser = Parser::new(&dialect).with_tokens_with_locations(tokens.clone());
+    let stopped_at = match parser.parse_statements() {
+        Ok(_) => tokens.len(),
+        Err(_) => parser.index().min(tokens.len()),
+    };
+
+    // The failing token is usually the one just consumed, so search backwards from there
+    tokens[..stopped_at]
+        .iter()
+        .rev()
+        .chain(tokens[stopped_at..].iter())
+        .find(|t| t.token.to_string() == found)
+        .map(|t| (t.location.line, t.location.column))
+}
+
+/// Turn a parser error message such as "Expected end of statement, found: foo"
+/// into an issue with the offending token and its position split out.
+pub(crate) fn parse_error_issue(query: &str, message: String) -> SqlIssue {
+    let token = message
+        .rfind("found: ")
+        .map(|start| message[start + "found: ".len()..].trim().to_string());
+    let position = token
+        .as_deref()
+        .and_then(|token| locate_parse_error(query, token));
+
+    SqlIssue {
+        message,
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+        token,
+    }
+}
+
+/// Parse and plan `query` without executing it, reporting syntax errors with
+/// their position and planning errors (unknown tables, columns, functions).
+pub async fn validate_sql(
+    cache: &ParquetCache,
+    file_path: &str,
+    query: &str,
+) -> Result<SqlValidation, String> {
+    let statements = match datafusion::sql::parser::DFParser::parse_sql(query) {
+        Ok(statements) => statements,
+        Err(e) => {
+            return Ok(SqlValidation {
+                valid: false,
+                issues: vec![parse_error_issue(query, e.to_string())],
+            })
+        }
+    };
+
+    let ctx = cache.session_for(file_path).await?;
+    let state = ctx.state();
+
+    let mut issues = Vec::new();
+    for statement in statements {
+        if let Err(e) = state.statement_to_plan(statement).await {
+            issues.push(SqlIssue {
+                message: e.to_string(),
+                line: None,
+                column: None,
+                token: None,
+            });
+        }
+    }
+
+    Ok(SqlValidation {
+        valid: issues.is_empty(),
+        issues,
+    })
+}
+
+/// Optimized logical and physical plans of `query`. With `analyze`, the query is
+/// also run through `EXPLAIN ANALYZE` to capture per-operator metrics.
+pub async fn explain_sql(
+    cache: &ParquetCache,
+    file_path: &str,
+    query: &str,
+    analyze: bool,
+) -> Result<QueryPlan, String> {
+    let ctx = cache.session_for(file_path).await?;
+
+    let df = ctx
+        .sql(query)
+        .await
+        .map_err(|e| format!("SQL execution failed: {}", e))?;
+
+    let logical = df
+        .clone()
+        .into_optimized_plan()
+        .map_err(|e| format!("Failed to optimize plan: {}", e))?;
+    let physical = df
+        .create_physical_plan()
+        .await
+        .map_err(|e| format!("Failed to create physical plan: {}", e))?;
+
+    let analyzed_plan = if analyze {
+        let batches = collect_query(&ctx, &format!("EXPLAIN ANALYZE {}", query)).await?;
+        // EXPLAIN output has plan_type and plan columns
+        let mut plan = String::new();
+        for batch in &batches {
+            for row in 0..batch.num_rows() {
+                plan.push_str(
+                    &array_value_to_string(batch.column(1), row).map_err(|e| e.to_string())?,
+                );
+            }
+        }
+        Some(plan)
+    } else {
+        None
+    };
+
+    let logical_plan = logical.display_indent().to_string();
+    let physical_plan = datafusion::physical_plan::displayable(physical.as_ref())
+        .indent(true)
+        .to_string();
+
+    Ok(QueryPlan {
+        logical_plan,
+        physical_plan,
+        analyzed_plan,
+    })
+}
+
+/// Quantiles reported by `describe`.
+const DESCRIBE_QUANTILES: [f64; 3] = [0.25, 0.5, 0.75];
+
+pub(crate) async fn collect_query(
+    ctx: &datafusion::execution::context::SessionContext,
+    query: &str,
+) -> Result<Vec<RecordBatch>, String> {
+    let df = ctx
+        .sql(query)
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+    df.collect()
+        .await
+        .map_err(|e| format!("Failed to collect results: {}", e))
+}
+
+pub(crate) fn first_row_i64(batch: &RecordBatch, index: usize) -> Option<i64> {
+    let column = batch.column(index);
+    let array = column.as_any().downcast_ref::<Int64Array>()?;
+    (!array.is_null(0)).then(|| array.value(0))
+}
+
+fn first_row_f64(batch: &RecordBatch, index: usize) -> Option<f64> {
+    let column = batch.column(index);
+    let array = column.as_any().downcast_ref::<Float64Array>()?;
+    (!array.is_null(0)).then(|| array.value(0))
+}
+
+fn first_row_string(batch: &RecordBatch, index: usize) -> Option<String> {
+    let column = batch.column(index);
+    if column.is_null(0) {
+        return None;
+    }
+    array_value_to_string(column, 0).ok()
+}
+
+/// Approximate quantiles of the numeric column expression `col`. Nulls are
+/// filtered in the WHERE clause because APPROX_PERCENTILE_CONT does not skip
+/// them reliably.
+async fn column_percentiles(
+    ctx: &datafusion::execution::context::SessionContext,
+    col: &str,
+) -> Result<Vec<Percentile>, String> {
+    let select: Vec<String> = DESCRIBE_QUANTILES
+        .iter()
+        .map(|q| format!("APPROX_PERCENTILE_CONT(CAST({} AS DOUBLE), {})", col, q))
+        .collect();
+    let query = format!(
+        "SELECT {} FROM t WHERE {} IS NOT NULL",
+        select.join(", "),
+        col
+    );
+
+    let batches = collect_query(ctx, &query).await?;
+    let batch = batches.iter().find(|b| b.num_rows() > 0);
+
+    Ok(DESCRIBE_QUANTILES
+        .iter()
+        .enumerate()
+        .map(|(i, &quantile)| Percentile {
+            quantile,
+            value: batch.and_then(|b| first_row_f64(b, i)),
+        })
+        .collect())
+}
+
+/// Summary statistics per column, computed with a single aggregate query.
+/// Min/max/distinct are skipped for nested columns; mean, stddev and
+/// percentiles are only computed for integer and floating point columns,
+/// with percentiles needing one extra query per such column.
+pub async fn describe(
+    cache: &ParquetCache,
+    path: &str,
+    columns: Option<Vec<String>>,
+) -> Result<DataProfile, String> {
+    let ctx = cache.get_or_create_session(path).await?;
+    let resolver = ColumnResolver::for_session(&ctx).await?;
+
+    let table = ctx
+        .table("t")
+        .await
+        .map_err(|e| format!("Failed to load table: {}", e))?;
+    let schema = table.schema().inner().clone();
+
+    // (name, SQL expression, field) of every profiled column
+    let fields: Vec<_> = match &columns {
+        Some(names) => names
+            .iter()
+            .map(|name| {
+                let (col, field) = resolver.resolve_field(name)?;
+                Ok((name.clone(), col, field.clone()))
+            })
+            .collect::<Result<_, String>>()?,
+        None => schema
+            .fields()
+            .iter()
+            .map(|f| {
+                (
+                    f.name().clone(),
+                    quote_identifier(f.name()),
+                    f.as_ref().clone(),
+                )
+            })
+            .collect(),
+    };
+
+    let mut select = vec!["COUNT(*)".to_string()];
+    for (_, col, field) in &fields {
+        let data_type = field.data_type();
+
+        select.push(format!("COUNT({})", col));
+        if !data_type.is_nested() {
+            select.push(format!("COUNT(DISTINCT {})", col));
+            select.push(format!("MIN({})", col));
+            select.push(format!("MAX({})", col));
+        }
+        if data_type.is_integer() || data_type.is_floating() {
+            select.push(format!("AVG(CAST({} AS DOUBLE))", col));
+            select.push(format!("STDDEV(CAST({} AS DOUBLE))", col));
+        }
+    }
+
+    let query = format!("SELECT {} FROM t", select.join(", "));
+    let batches = collect_query(&ctx, &query).await?;
+    let batch = batches
+        .iter()
+        .find(|b| b.num_rows() > 0)
+        .ok_or_else(|| "Describe query returned no rows".to_string())?;
+
+    let row_count = first_row_i64(batch, 0).unwrap_or(0);
+    let mut index = 1;
+    let mut profiles = Vec::with_capacity(fields.len());
+
+    for (name, col, field) in &fields {
+        let data_type = field.data_type();
+
+        let count = first_row_i64(batch, index).unwrap_or(0);
+        index += 1;
+
+        let (distinct_count, min, max) = if !data_type.is_nested() {
+            let stats = (
+                first_row_i64(batch, index),
+                first_row_string(batch, index + 1),
+                first_row_string(batch, index + 2),
+            );
+            index += 3;
+            stats
+        } else {
+            (None, None, None)
+        };
+
+        let (mean, stddev, percentiles) = if data_type.is_integer() || data_type.is_floating() {
+            let mean = first_row_f64(batch, index);
+            let stddev = first_row_f64(batch, index + 1);
+            index += 2;
+            let percentiles = column_percentiles(&ctx, col).await?;
+            (mean, stddev, percentiles)
+        } else {
+            (None, None, Vec::new())
+        };
+
+        profiles.push(ColumnProfile {
+            name: name.clone(),
+            data_type: data_type.to_string(),
+            count,
+            null_count: row_count - count,
+            distinct_count,
+            min,
+            max,
+            mean,
+            stddev,
+            percentiles,
+        });
+    }
+
+    Ok(DataProfile {
+        row_count,
+        columns: profiles,
+    })
+}
+
+/// `batches_to_rows` with timestamps, binary values and non-finite floats
+/// rendered per `display`.
+pub(crate) fn display_rows(
+    batches: &[RecordBatch],
+    display: DisplayOptions,
+) -> Result<Vec<Value>, String> {
+    let rendered = batches
+        .iter()
+        .map(|batch| render_for_display(batch, display))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut rows = batches_to_rows(&rendered)?;
+    restore_non_finite(&rendered, &mut rows, display.non_finite);
+    Ok(rows)
+}
+
+pub(crate) fn batches_to_rows(batches: &[RecordBatch]) -> Result<Vec<Value>, String> {
+    let buf = batches_to_json_bytes(batches)?;
+    let rows: Result<Vec<Value>, _> = serde_json::Deserializer::from_slice(&buf)
+        .into_iter::<Value>()
+        .collect();
+    rows.map_err(|e| format!("Failed to parse JSON results: {}", e))
+}
+
+/// Most frequent values of a column (nulls included) with their share of the
+/// rows matching `filter`.
+pub async fn value_counts(
+    cache: &ParquetCache,
+    path: &str,
+    column: &str,
+    top_n: usize,
+    filter: Option<FilterExpr>,
+) -> Result<ValueCounts, String> {
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let col = columns.resolve(column)?;
+    let where_clause = build_where_clause(filter.as_ref(), &columns)?;
+    let query = format!(
+        "SELECT {col} AS value, COUNT(*) AS count FROM t {where_clause} \
+         GROUP BY {col} ORDER BY count DESC, {col} LIMIT {top_n}"
+    );
+
+    let batches = collect_query(&ctx, &query).await?;
+    let total = count_data(cache, path, filter).await?;
+
+    let values = batches_to_rows(&batches)?
+        .into_iter()
+        .map(|row| {
+            let count = row.get("count").and_then(|c| c.as_i64()).unwrap_or(0);
+            ValueCount {
+                value: row.get("value").cloned().unwrap_or(Value::Null),
+                count,
+                percentage: if total > 0 {
+                    count as f64 * 100.0 / total as f64
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+
+    Ok(ValueCounts {
+        column: column.to_string(),
+        total,
+        values,
+    })
+}
+
+/// A finite `f64` as a SQL DOUBLE literal. `Display` writes plain decimal
+/// digits, which the SQL parser reads back exactly.
+fn float_literal(value: f64) -> String {
+    format!("CAST({} AS DOUBLE)", value)
+}
+
+/// Equi-width histogram of a numeric column. Bucket boundaries are derived
+/// from the min/max of the rows matching `filter`; nulls are counted separately.
+pub async fn histogram(
+    cache: &ParquetCache,
+    path: &str,
+    column: &str,
+    num_buckets: usize,
+    filter: Option<FilterExpr>,
+) -> Result<Histogram, String> {
+    if num_buckets == 0 {
+        return Err("Number of buckets must be greater than zero".to_string());
+    }
+
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let (col, field) = columns.resolve_field(column)?;
+    let data_type = field.data_type();
+    if !data_type.is_numeric() {
+        return Err(format!("Column {} is not numeric ({})", column, data_type));
+    }
+
+    let value = format!("CAST({} AS DOUBLE)", col);
+    // NaN and ±Infinity have no bucket and would make the bounds non-finite
+    let finite = format!("CASE WHEN isfinite({value}) THEN {value} END");
+    let where_clause = build_where_clause(filter.as_ref(), &columns)?;
+    let query = format!(
+        "SELECT MIN({finite}), MAX({finite}), COUNT(*) - COUNT({col}) FROM t {where_clause}"
+    );
+    let batches = collect_query(&ctx, &query).await?;
+    let batch = batches.iter().find(|b| b.num_rows() > 0);
+
+    let min = batch.and_then(|b| first_row_f64(b, 0));
+    let max = batch.and_then(|b| first_row_f64(b, 1));
+    let null_count = batch.and_then(|b| first_row_i64(b, 2)).unwrap_or(0);
+
+    let (min_value, max_value) = match (min, max) {
+        (Some(min), Some(max)) => (min, max),
+        _ => {
+            return Ok(Histogram {
+                column: column.to_string(),
+                min,
+                max,
+                null_count,
+                buckets: Vec::new(),
+            })
+        }
+    };
+
+    // Divided first so the span of values near f64::MAX doesn't overflow
+    let width = if max_value > min_value {
+        max_value / num_buckets as f64 - min_value / num_buckets as f64
+    } else {
+        1.0
+    };
+
+    // isfinite is NULL for nulls, so they are left out too
+    let where_clause =
+        build_where_clause_with(filter.as_ref(), &format!("isfinite({value})"), &columns)?;
+    let (lower, width_literal) = (float_literal(min_value), float_literal(width));
+    let query = format!(
+        "SELECT CAST(FLOOR(({value} - {lower}) / {width_literal}) AS BIGINT) AS bucket, \
+         COUNT(*) AS count FROM t {where_clause} GROUP BY bucket"
+    );
+    let batches = collect_query(&ctx, &query).await?;
+
+    let mut counts = vec![0i64; num_buckets];
+    for batch in &batches {
+        let buckets = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| "Failed to downcast bucket index".to_string())?;
+        let bucket_counts = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| "Failed to downcast bucket count".to_string())?;
+        for row in 0..batch.num_rows() {
+            // The max value lands exactly on the upper edge; fold it into the last bucket
+            let index = (buckets.value(row).max(0) as usize).min(num_buckets - 1);
+            counts[index] += bucket_counts.value(row);
+        }
+    }
+
+    let buckets = counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| HistogramBucket {
+            lower: min_value + width * i as f64,
+            upper: min_value + width * (i + 1) as f64,
+            count,
+        })
+        .collect();
+
+    Ok(Histogram {
+        column: column.to_string(),
+        min,
+        max,
+        null_count,
+        buckets,
+    })
+}
+
+/// Bucket a timestamp column by `interval` (minute/hour/day/week/month) and
+/// aggregate `value_column` per bucket. `count` may omit the value column to
+/// count rows.
+pub async fn time_series(
+    cache: &ParquetCache,
+    path: &str,
+    time_column: &str,
+    interval: &str,
+    value_column: Option<String>,
+    aggregation: &str,
+    filter: Option<FilterExpr>,
+) -> Result<TimeSeries, String> {
+    let interval = interval.to_lowercase();
+    if !["minute", "hour", "day", "week", "month"].contains(&interval.as_str()) {
+        return Err(format!("Unsupported interval: {}", interval));
+    }
+
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let aggregation = aggregation.to_lowercase();
+    let value_expr = match (aggregation.as_str(), &value_column) {
+        ("count", None) => "CAST(COUNT(*) AS DOUBLE)".to_string(),
+        ("count", Some(v)) => format!("CAST(COUNT({}) AS DOUBLE)", columns.resolve(v)?),
+        ("sum" | "avg" | "min" | "max", Some(v)) => format!(
+            "{}(CAST({} AS DOUBLE))",
+            aggregation.to_uppercase(),
+            columns.resolve(v)?
+        ),
+        ("sum" | "avg" | "min" | "max", None) => {
+            return Err(format!(
+                "Aggregation {} requires a value column",
+                aggregation
+            ))
+        }
+        _ => return Err(format!("Unsupported aggregation: {}", aggregation)),
+    };
+
+    let time = columns.resolve(time_column)?;
+    let where_clause =
+        build_where_clause_with(filter.as_ref(), &format!("{} IS NOT NULL", time), &columns)?;
+    let query = format!(
+        "SELECT DATE_TRUNC('{interval}', CAST({time} AS TIMESTAMP)) AS bucket, {value_expr} AS value \
+         FROM t {where_clause} GROUP BY bucket ORDER BY bucket"
+    );
+    let batches = collect_query(&ctx, &query).await?;
+
+    let mut points = Vec::new();
+    for batch in &batches {
+        let values = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .ok_or_else(|| "Failed to downcast aggregated value".to_string())?;
+        for row in 0..batch.num_rows() {
+            points.push(TimeSeriesPoint {
+                bucket: array_value_to_string(batch.column(0), row).map_err(|e| e.to_string())?,
+                value: (!values.is_null(row)).then(|| values.value(row)),
+            });
+        }
+    }
+
+    Ok(TimeSeries {
+        time_column: time_column.to_string(),
+        interval,
+        aggregation,
+        points,
+    })
+}
+
+/// Null count, approximate distinct count (HyperLogLog) and min/max of every
+/// column, computed in a single scan and cached until the file is evicted.
+/// Distinct counts and min/max are skipped for nested columns.
+pub async fn column_stats(cache: &ParquetCache, path: &str) -> Result<ColumnSummaries, String> {
+    if let Some(stats) = cache.cached_column_stats(path) {
+        return Ok(stats);
+    }
+
+    let version = cache.disk_version(path);
+    let ctx = cache.get_or_create_session(path).await?;
+    let table = ctx
+        .table("t")
+        .await
+        .map_err(|e| format!("Failed to load table: {}", e))?;
+    let schema = table.schema().inner().clone();
+
+    let mut select = vec!["COUNT(*)".to_string()];
+    for field in schema.fields() {
+        let col = quote_identifier(field.name());
+        select.push(format!("COUNT({})", col));
+        if !field.data_type().is_nested() {
+            select.push(format!(
+                "CAST(approx_distinct({}) AS BIGINT)",
+                hll_input(&col, field.data_type())
+            ));
+            select.push(format!("MIN({})", col));
+            select.push(format!("MAX({})", col));
+        }
+    }
+
+    let query = format!("SELECT {} FROM t", select.join(", "));
+    let batches = collect_query(&ctx, &query).await?;
+    let batch = batches
+        .iter()
+        .find(|b| b.num_rows() > 0)
+        .ok_or_else(|| "Column stats query returned no rows".to_string())?;
+
+    let row_count = first_row_i64(batch, 0).unwrap_or(0);
+    let mut index = 1;
+    let mut columns = Vec::with_capacity(schema.fields().len());
+    for field in schema.fields() {
+        let count = first_row_i64(batch, index).unwrap_or(0);
+        index += 1;
+        let (approx_distinct, min, max) = if !field.data_type().is_nested() {
+            let stats = (
+                first_row_i64(batch, index),
+                first_row_string(batch, index + 1),
+                first_row_string(batch, index + 2),
+            );
+            index += 3;
+            stats
+        } else {
+            (None, None, None)
+        };
+        columns.push(ColumnSummary {
+            name: field.name().clone(),
+            data_type: field.data_type().to_string(),
+            null_count: row_count - count,
+            approx_distinct,
+            min,
+            max,
+        });
+    }
+
+    let stats = ColumnSummaries { row_count, columns };
+    if let Ok(mut column_stats) = cache.column_stats.lock() {
+        column_stats.insert(path.to_string(), stats.clone());
+    }
+    cache.persist(path, KIND_COLUMN_STATS, version, &stats);
+    Ok(stats)
+}
+
+/// `col` in a type `approx_distinct` accepts: integers, strings and binary
+/// as they are, everything else by its text form.
+fn hll_input(col: &str, data_type: &DataType) -> String {
+    match data_type {
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Binary | DataType::LargeBinary => {
+            col.to_string()
+        }
+        DataType::FixedSizeBinary(_) => format!("CAST({} AS BYTEA)", col),
+        t if t.is_integer() => col.to_string(),
+        _ => format!("CAST({} AS VARCHAR)", col),
+    }
+}
+
+/// Null count and ratio of every column, computed in a single scan unless
+/// column stats are already cached.
+pub async fn null_profile(cache: &ParquetCache, path: &str) -> Result<NullProfile, String> {
+    if let Some(stats) = cache.cached_column_stats(path) {
+        let row_count = stats.row_count;
+        let columns = stats
+            .columns
+            .into_iter()
+            .map(|column| ColumnNullInfo {
+                name: column.name,
+                null_count: column.null_count,
+                null_ratio: if row_count > 0 {
+                    column.null_count as f64 / row_count as f64
+                } else {
+                    0.0
+                },
+            })
+            .collect();
+        return Ok(NullProfile { row_count, columns });
+    }
+
+    let ctx = cache.get_or_create_session(path).await?;
+
+    let table = ctx
+        .table("t")
+        .await
+        .map_err(|e| format!("Failed to load table: {}", e))?;
+    let schema = table.schema().inner().clone();
+
+    let mut select = vec!["COUNT(*)".to_string()];
+    select.extend(
+        schema
+            .fields()
+            .iter()
+            .map(|f| format!("COUNT({})", quote_identifier(f.name()))),
+    );
+
+    let query = format!("SELECT {} FROM t", select.join(", "));
+    let batches = collect_query(&ctx, &query).await?;
+    let batch = batches
+        .iter()
+        .find(|b| b.num_rows() > 0)
+        .ok_or_else(|| "Null profile query returned no rows".to_string())?;
+
+    let row_count = first_row_i64(batch, 0).unwrap_or(0);
+    let columns = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let null_count = row_count - first_row_i64(batch, i + 1).unwrap_or(0);
+            ColumnNullInfo {
+                name: field.name().clone(),
+                null_count,
+                null_ratio: if row_count > 0 {
+                    null_count as f64 / row_count as f64
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+
+    Ok(NullProfile { row_count, columns })
+}
+
+/// Columns of the primary table that can be compared as text, with requested
+/// names resolved to the columns they refer to.
+async fn searchable_columns(
+    ctx: &datafusion::execution::context::SessionContext,
+    columns: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let table = ctx
+        .table("t")
+        .await
+        .map_err(|e| format!("Failed to load table: {}", e))?;
+    let schema = table.schema().inner().clone();
+
+    let searchable: Vec<String> = schema
+        .fields()
+        .iter()
+        .filter(|f| !f.data_type().is_nested())
+        .map(|f| f.name().clone())
+        .collect();
+
+    match columns {
+        Some(columns) => {
+            let resolver = ColumnResolver::new(schema);
+            columns
+                .iter()
+                .map(|column| {
+                    // Struct fields resolve to an expression rather than a column
+                    let (expr, field) = resolver.resolve_field(column)?;
+                    if expr == quote_identifier(field.name()) && searchable.contains(field.name()) {
+                        Ok(field.name().clone())
+                    } else {
+                        Err(format!("Column cannot be searched: {}", column))
+                    }
+                })
+                .collect()
+        }
+        None => Ok(searchable),
+    }
+}
+
+fn cell_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Find rows where any of `columns` (all non-nested columns by default)
+/// contains `text`. Non-string columns are searched through their text cast.
+/// Matches come in file order and each reports which columns contained the text.
+/// Counting and paging are each bounded by `timeout_ms`.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_rows(
+    cache: &ParquetCache,
+    path: &str,
+    text: &str,
+    case_sensitive: bool,
+    columns: Option<Vec<String>>,
+    offset: usize,
+    limit: usize,
+    timeout_ms: Option<u64>,
+) -> Result<SearchResults, String> {
+    if text.is_empty() {
+        return Err("Search text must not be empty".to_string());
+    }
+
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = searchable_columns(&ctx, columns).await?;
+    if columns.is_empty() {
+        return Ok(SearchResults {
+            total: 0,
+            matches: Vec::new(),
+        });
+    }
+
+    // STRPOS avoids LIKE wildcards in the search text; LIKE has no ESCAPE support here
+    let needle = if case_sensitive {
+        quote_literal(text)
+    } else {
+        quote_literal(&text.to_lowercase())
+    };
+    let predicates: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            let value = format!("CAST({} AS VARCHAR)", quote_identifier(column));
+            if case_sensitive {
+                format!("STRPOS({}, {}) > 0", value, needle)
+            } else {
+                format!("STRPOS(LOWER({}), {}) > 0", value, needle)
+            }
+        })
+        .collect();
+    let where_clause = format!("WHERE {}", predicates.join(" OR "));
+
+    let count_query = format!("SELECT COUNT(*) FROM t {}", where_clause);
+    let batches = with_timeout(timeout_ms, collect_query(&ctx, &count_query)).await?;
+    let total = batches
+        .iter()
+        .find(|b| b.num_rows() > 0)
+        .and_then(|b| first_row_i64(b, 0))
+        .unwrap_or(0) as usize;
+
+    // Pages follow the file so successive offsets neither repeat nor skip matches
+    let query = format!(
+        "SELECT * EXCLUDE ({}) FROM {} {} ORDER BY {} LIMIT {} OFFSET {}",
+        FILE_ROW, FILE_ORDER_TABLE, where_clause, FILE_ROW, limit, offset
+    );
+    let ordered = file_order_session(&ctx);
+    let batches = with_timeout(timeout_ms, collect_query(&ordered, &query)).await?;
+
+    let needle = if case_sensitive {
+        text.to_string()
+    } else {
+        text.to_lowercase()
+    };
+    let matches = cache
+        .display_rows(&batches)?
+        .into_iter()
+        .map(|row| {
+            let matched_columns = columns
+                .iter()
+                .filter(|column| {
+                    row.get(column.as_str())
+                        .and_then(cell_text)
+                        .is_some_and(|cell| {
+                            if case_sensitive {
+                                cell.contains(&needle)
+                            } else {
+                                cell.to_lowercase().contains(&needle)
+                            }
+                        })
+                })
+                .cloned()
+                .collect();
+            SearchMatch {
+                row,
+                matched_columns,
+            }
+        })
+        .collect();
+
+    Ok(SearchResults { total, matches })
+}
+
+/// How long a regex search may run when the caller gives no timeout.
+const DEFAULT_REGEX_TIMEOUT_MS: u64 = 10_000;
+
+/// Rows whose `column` (compared as text) matches the regular expression
+/// `pattern`, in file order. The query is abandoned once `timeout_ms` elapses so a
+/// pathological pattern cannot tie up the session.
+#[allow(clippy::too_many_arguments)]
+pub async fn regex_search(
+    cache: &ParquetCache,
+    path: &str,
+    column: &str,
+    pattern: &str,
+    case_sensitive: bool,
+    offset: usize,
+    limit: usize,
+    filter: Option<FilterExpr>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<Value>, String> {
+    if pattern.is_empty() {
+        return Err("Pattern must not be empty".to_string());
+    }
+
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let value = format!("CAST({} AS VARCHAR)", columns.resolve(column)?);
+    let predicate = if case_sensitive {
+        format!("REGEXP_LIKE({}, {})", value, quote_literal(pattern))
+    } else {
+        format!("REGEXP_LIKE({}, {}, 'i')", value, quote_literal(pattern))
+    };
+    let where_clause = build_where_clause_with(filter.as_ref(), &predicate, &columns)?;
+    // Pages follow the file so successive offsets neither repeat nor skip matches
+    let query = format!(
+        "SELECT * EXCLUDE ({}) FROM {} {} ORDER BY {} LIMIT {} OFFSET {}",
+        FILE_ROW, FILE_ORDER_TABLE, where_clause, FILE_ROW, limit, offset
+    );
+
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_REGEX_TIMEOUT_MS);
+    let ctx = file_order_session(&ctx);
+    let batches = with_timeout(Some(timeout_ms), collect_query(&ctx, &query)).await?;
+
+    cache.display_rows(&batches)
+}
+
+/// Positions `find_row_positions` returns when the caller gives no cap.
+pub const DEFAULT_MAX_ROW_POSITIONS: usize = 10_000;
+
+/// Zero-based positions, under `sort`, of the first `limit` rows matching
+/// `filter`, so the grid can jump between matches page by page. Rows are
+/// numbered the way pages show them: in file order, with ties under `sort`
+/// kept in file order too.
+pub async fn find_row_positions(
+    cache: &ParquetCache,
+    path: &str,
+    filter: FilterExpr,
+    sort: Option<Vec<SortSpec>>,
+    limit: usize,
+) -> Result<Vec<u64>, String> {
+    let ctx = file_order_session(&cache.get_or_create_session(path).await?);
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let order_by_clause = file_order_by_clause(sort, &columns)?;
+    let where_clause = build_where_clause(Some(&filter), &columns)?;
+    let query = format!(
+        "SELECT __row FROM \
+         (SELECT *, ROW_NUMBER() OVER ({order_by_clause}) AS __row FROM {FILE_ORDER_TABLE}) \
+         {where_clause} ORDER BY __row LIMIT {limit}"
+    );
+    let batches = collect_query(&ctx, &query).await?;
+
+    let mut positions = Vec::new();
+    for batch in &batches {
+        let array = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .ok_or_else(|| "Failed to downcast row positions".to_string())?;
+        // ROW_NUMBER is one-based
+        positions.extend(array.iter().flatten().map(|row| row - 1));
+    }
+
+    Ok(positions)
+}
+
+/// Distinct values of a column in sort order, optionally restricted to values
+/// whose text starts with `search_prefix`. Feeds per-column filter pickers.
+pub async fn distinct_values(
+    cache: &ParquetCache,
+    path: &str,
+    column: &str,
+    limit: usize,
+    search_prefix: Option<String>,
+) -> Result<Vec<Value>, String> {
+    let ctx = cache.get_or_create_session(path).await?;
+    let col = ColumnResolver::for_session(&ctx).await?.resolve(column)?;
+
+    let where_clause = match search_prefix.as_deref() {
+        Some(prefix) if !prefix.is_empty() => format!(
+            "WHERE STARTS_WITH(CAST({} AS VARCHAR), {})",
+            col,
+            quote_literal(prefix)
+        ),
+        _ => String::new(),
+    };
+    let query = format!(
+        "SELECT DISTINCT {col} AS value FROM t {where_clause} ORDER BY value NULLS FIRST LIMIT {limit}"
+    );
+    let batches = collect_query(&ctx, &query).await?;
+
+    Ok(batches_to_rows(&batches)?
+        .into_iter()
+        .map(|row| row.get("value").cloned().unwrap_or(Value::Null))
+        .collect())
+}
+
+/// Upper bound on the number of distinct pivot values turned into columns.
+const MAX_PIVOT_COLUMNS: usize = 100;
+
+fn aggregate_expr(agg_func: &str, value: &str) -> Result<String, String> {
+    match agg_func {
+        "count" => Ok(format!("COUNT({})", value)),
+        "count_distinct" => Ok(format!("COUNT(DISTINCT {})", value)),
+        "sum" | "avg" | "min" | "max" => Ok(format!("{}({})", agg_func.to_uppercase(), value)),
+        _ => Err(format!("Unsupported aggregation: {}", agg_func)),
+    }
+}
+
+/// Grouped aggregation of `agg_column`. With `pivot_column`, each distinct value
+/// of that column becomes its own aggregate column (at most `MAX_PIVOT_COLUMNS`).
+pub async fn pivot(
+    cache: &ParquetCache,
+    path: &str,
+    group_by_columns: Vec<String>,
+    agg_column: &str,
+    agg_func: &str,
+    pivot_column: Option<String>,
+    filter: Option<FilterExpr>,
+) -> Result<PivotTable, String> {
+    let agg_func = agg_func.to_lowercase();
+    let ctx = cache.get_or_create_session(path).await?;
+    let resolver = ColumnResolver::for_session(&ctx).await?;
+    let where_clause = build_where_clause(filter.as_ref(), &resolver)?;
+    let value = resolver.resolve(agg_column)?;
+
+    let mut columns = group_by_columns.clone();
+    let mut select: Vec<String> = group_by_columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| Ok(format!("{} AS c{}", resolver.resolve(column)?, i)))
+        .collect::<Result<_, String>>()?;
+
+    match &pivot_column {
+        Some(pivot_column) => {
+            let pivot = resolver.resolve(pivot_column)?;
+            let query = format!(
+                "SELECT DISTINCT {pivot} AS value FROM t {where_clause} \
+                 ORDER BY value NULLS FIRST LIMIT {}",
+                MAX_PIVOT_COLUMNS + 1
+            );
+            let pivot_values = batches_to_rows(&collect_query(&ctx, &query).await?)?;
+            if pivot_values.len() > MAX_PIVOT_COLUMNS {
+                return Err(format!(
+                    "Pivot column {} has more than {} distinct values",
+                    pivot_column, MAX_PIVOT_COLUMNS
+                ));
+            }
+
+            for row in &pivot_values {
+                let key = row.get("value").cloned().unwrap_or(Value::Null);
+                let condition = if key.is_null() {
+                    format!("{} IS NULL", pivot)
+                } else {
+                    format!("{} = {}", pivot, compile_literal(&key)?)
+                };
+                let case = format!("CASE WHEN {} THEN {} END", condition, value);
+                select.push(format!(
+                    "{} AS c{}",
+                    aggregate_expr(&agg_func, &case)?,
+                    columns.len()
+                ));
+                columns.push(match key {
+                    Value::Null => "null".to_string(),
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                });
+            }
+        }
+        None => {
+            select.push(format!(
+                "{} AS c{}",
+                aggregate_expr(&agg_func, &value)?,
+                columns.len()
+            ));
+            columns.push(format!("{}({})", agg_func, agg_column));
+        }
+    }
+
+    let group_keys: Vec<String> = (0..group_by_columns.len())
+        .map(|i| format!("c{}", i))
+        .collect();
+    let (group_by, order_by) = if group_keys.is_empty() {
+        (String::new(), String::new())
+    } else {
+        (
+            format!("GROUP BY {}", group_keys.join(", ")),
+            format!("ORDER BY {}", group_keys.join(", ")),
+        )
+    };
+    let query = format!(
+        "SELECT {} FROM t {} {} {}",
+        select.join(", "),
+        where_clause,
+        group_by,
+        order_by
+    );
+    let batches = collect_query(&ctx, &query).await?;
+
+    let rows = batches_to_rows(&batches)?
+        .into_iter()
+        .map(|row| {
+            (0..columns.len())
+                .map(|i| row.get(format!("c{}", i)).cloned().unwrap_or(Value::Null))
+                .collect()
+        })
+        .collect();
 
-    Ok((batches, schema))
+    Ok(PivotTable { columns, rows })
 }
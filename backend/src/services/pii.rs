@@ -0,0 +1,221 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::models::{SensitiveColumn, SensitiveColumns, SensitiveKind};
+use crate::services::sample;
+
+/// Rows sampled per file. A fixed seed keeps repeated checks consistent.
+const SAMPLE_ROWS: usize = 1000;
+const SAMPLE_SEED: u64 = 0x5EED;
+
+/// Columns scoring below this aren't reported.
+const MIN_CONFIDENCE: f64 = 0.3;
+
+/// Share of the confidence that comes from the column name rather than values.
+const NAME_WEIGHT: f64 = 0.15;
+
+/// Checked in this order, so a column matching two kinds equally well (e.g.
+/// SSNs also look like phone numbers) gets the more specific one.
+const KINDS: [SensitiveKind; 4] = [
+    SensitiveKind::CreditCard,
+    SensitiveKind::NationalId,
+    SensitiveKind::Email,
+    SensitiveKind::Phone,
+];
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    if local.is_empty() || domain.contains('@') || s.chars().any(char::is_whitespace) {
+        return false;
+    }
+    let labels: Vec<&str> = domain.split('.').collect();
+    labels.len() >= 2
+        && labels
+            .iter()
+            .all(|l| !l.is_empty() && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        && labels
+            .last()
+            .is_some_and(|tld| tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+}
+
+/// International numbers with a leading `+`, or domestic ones written in at
+/// least three groups or with an area code in parentheses. Bare digit runs,
+/// dates and decimals are left alone.
+fn is_phone(s: &str) -> bool {
+    let s = s.trim();
+    if !s
+        .chars()
+        .all(|c| c.is_ascii_digit() || " -.()+".contains(c))
+        || s.rfind('+').is_some_and(|i| i > 0)
+    {
+        return false;
+    }
+    let digits = s.chars().filter(char::is_ascii_digit).count();
+    if let Some(rest) = s.strip_prefix('+') {
+        return (8..=15).contains(&digits) && rest.starts_with(|c: char| c.is_ascii_digit());
+    }
+    let groups = s
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|g| !g.is_empty())
+        .count();
+    (10..=11).contains(&digits) && (groups >= 3 || s.starts_with('('))
+}
+
+fn is_ssn(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    let [area, group, serial] = parts[..] else {
+        return false;
+    };
+    let digits = |p: &str, n: usize| p.len() == n && p.chars().all(|c| c.is_ascii_digit());
+    digits(area, 3)
+        && digits(group, 2)
+        && digits(serial, 4)
+        && area != "000"
+        && area != "666"
+        && !area.starts_with('9')
+        && group != "00"
+        && serial != "0000"
+}
+
+/// UK National Insurance number, e.g. `QQ 12 34 56 C`.
+fn is_nino(s: &str) -> bool {
+    let compact: Vec<char> = s
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    compact.len() == 9
+        && compact[..2]
+            .iter()
+            .all(|c| c.is_ascii_alphabetic() && !"DFIQUV".contains(*c))
+        && compact[2..8].iter().all(char::is_ascii_digit)
+        && ('A'..='D').contains(&compact[8])
+}
+
+/// 13-19 digits, optionally grouped by spaces or dashes, with a card network
+/// prefix and a valid Luhn check digit.
+fn is_credit_card(s: &str) -> bool {
+    if !s
+        .chars()
+        .all(|c| c.is_ascii_digit() || c == ' ' || c == '-')
+    {
+        return false;
+    }
+    let digits: Vec<u32> = s.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) || !(2..=6).contains(&digits[0]) {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| match (i % 2 == 1, d * 2) {
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+            (false, _) => d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn matches(kind: SensitiveKind, s: &str) -> bool {
+    match kind {
+        SensitiveKind::Email => is_email(s),
+        SensitiveKind::Phone => is_phone(s),
+        SensitiveKind::NationalId => is_ssn(s) || is_nino(s),
+        SensitiveKind::CreditCard => is_credit_card(s),
+    }
+}
+
+/// Whether the column name suggests `kind`, by whole words (`home_tel`) or
+/// by substrings long enough not to match by accident (`customerEmail`).
+fn name_suggests(kind: SensitiveKind, column: &str) -> bool {
+    let (words, substrings): (&[&str], &[&str]) = match kind {
+        SensitiveKind::Email => (&["mail"], &["email"]),
+        SensitiveKind::Phone => (&["tel", "mobile", "cell", "fax"], &["phone"]),
+        SensitiveKind::NationalId => (
+            &["ssn", "nino", "sin", "passport"],
+            &["socialsecurity", "nationalid", "nationalinsurance", "taxid"],
+        ),
+        SensitiveKind::CreditCard => (&["card", "cc", "pan"], &["creditcard", "cardnumber"]),
+    };
+    let lower = column.to_lowercase();
+    let compact: String = lower.chars().filter(|c| c.is_alphanumeric()).collect();
+    lower
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| words.contains(&word))
+        || substrings.iter().any(|s| compact.contains(s))
+}
+
+/// Per-kind match counts of one column's values.
+#[derive(Default)]
+struct Tally {
+    checked: usize,
+    matched: [usize; KINDS.len()],
+}
+
+/// Flag columns whose sampled values look like emails, phone numbers,
+/// national IDs or credit card numbers. Each column is reported at most once,
+/// with the kind it matches best.
+pub async fn detect_sensitive_columns(path: &str) -> Result<SensitiveColumns, String> {
+    let rows = sample::sample_rows(path, SAMPLE_ROWS, "random", Some(SAMPLE_SEED)).await?;
+
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for row in &rows {
+        let Value::Object(fields) = row else {
+            continue;
+        };
+        for (column, value) in fields {
+            // Nested values aren't checked; integers can hold card or phone numbers
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) if n.is_u64() || n.is_i64() => n.to_string(),
+                _ => continue,
+            };
+            let tally = tallies.entry(column.clone()).or_default();
+            tally.checked += 1;
+            for (i, kind) in KINDS.iter().enumerate() {
+                if matches(*kind, text.trim()) {
+                    tally.matched[i] += 1;
+                }
+            }
+        }
+    }
+
+    let mut columns = Vec::new();
+    for (column, tally) in tallies {
+        let mut best: Option<SensitiveColumn> = None;
+        for (i, kind) in KINDS.iter().enumerate() {
+            let matched = tally.matched[i];
+            if matched == 0 {
+                continue;
+            }
+            let name_hint = name_suggests(*kind, &column);
+            let ratio = matched as f64 / tally.checked as f64;
+            let score = ratio * (1.0 - NAME_WEIGHT) + if name_hint { NAME_WEIGHT } else { 0.0 };
+            let confidence = (score * 100.0).round() / 100.0;
+            if confidence < MIN_CONFIDENCE
+                || best.as_ref().is_some_and(|b| b.confidence >= confidence)
+            {
+                continue;
+            }
+            best = Some(SensitiveColumn {
+                column: column.clone(),
+                kind: *kind,
+                confidence,
+                matched,
+                checked: tally.checked,
+                name_hint,
+            });
+        }
+        columns.extend(best);
+    }
+    columns.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    Ok(SensitiveColumns {
+        sampled_rows: rows.len(),
+        columns,
+    })
+}
@@ -0,0 +1,232 @@
+use parquet::basic::{ConvertedType, LogicalType};
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::statistics::Statistics;
+use parquet::schema::types::ColumnDescriptor;
+use serde_json::Value;
+
+use crate::models::{FilterExpr, FilterOperator};
+
+/// What a row group's statistics say about the rows matching a filter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowGroupMatch {
+    /// Every row matches.
+    All,
+    /// Some rows may match; the row group has to be read to know.
+    Partial,
+    /// No row can match.
+    Empty,
+}
+
+/// Judge `filter` against the min/max and null counts of one row group.
+/// Only plain signed integer and string columns are compared; anything else,
+/// floats included (NaN is left out of their statistics), is `Partial`.
+/// Null counts are trusted as written, so files without them may be misjudged.
+pub fn classify_row_group(row_group: &RowGroupMetaData, filter: &FilterExpr) -> RowGroupMatch {
+    match filter {
+        FilterExpr::Condition {
+            column,
+            operator,
+            value,
+        } => classify_condition(row_group, column, operator, value.as_ref()),
+        FilterExpr::And { filters } => {
+            let matches: Vec<_> = filters
+                .iter()
+                .map(|f| classify_row_group(row_group, f))
+                .collect();
+            if matches.contains(&RowGroupMatch::Empty) {
+                RowGroupMatch::Empty
+            } else if matches.iter().all(|m| *m == RowGroupMatch::All) {
+                RowGroupMatch::All
+            } else {
+                RowGroupMatch::Partial
+            }
+        }
+        FilterExpr::Or { filters } => {
+            let matches: Vec<_> = filters
+                .iter()
+                .map(|f| classify_row_group(row_group, f))
+                .collect();
+            if matches.contains(&RowGroupMatch::All) {
+                RowGroupMatch::All
+            } else if matches.iter().all(|m| *m == RowGroupMatch::Empty) {
+                RowGroupMatch::Empty
+            } else {
+                RowGroupMatch::Partial
+            }
+        }
+        // NOT of a predicate that is false for every row is still null, not
+        // true, for rows where the predicate was null, so only `All` inverts
+        FilterExpr::Not { filter } => match classify_row_group(row_group, filter) {
+            RowGroupMatch::All => RowGroupMatch::Empty,
+            _ => RowGroupMatch::Partial,
+        },
+    }
+}
+
+/// Columns named anywhere in `filter`, each once, in order of appearance.
+pub fn filter_columns(filter: &FilterExpr) -> Vec<String> {
+    fn collect(filter: &FilterExpr, out: &mut Vec<String>) {
+        match filter {
+            FilterExpr::Condition { column, .. } => {
+                if !out.contains(column) {
+                    out.push(column.clone());
+                }
+            }
+            FilterExpr::And { filters } | FilterExpr::Or { filters } => {
+                for filter in filters {
+                    collect(filter, out);
+                }
+            }
+            FilterExpr::Not { filter } => collect(filter, out),
+        }
+    }
+    let mut columns = Vec::new();
+    collect(filter, &mut columns);
+    columns
+}
+
+/// A min/max bound, comparable with filter values of the same column.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+enum Bound {
+    Int(i64),
+    Text(Vec<u8>),
+}
+
+impl Bound {
+    /// `value` as a bound of the same kind as `like`.
+    fn from_json(value: &Value, like: &Bound) -> Option<Bound> {
+        match like {
+            // A quoted number is compared as text by the query engine
+            Bound::Int(_) => value.as_i64().map(Bound::Int),
+            Bound::Text(_) => value.as_str().map(|s| Bound::Text(s.as_bytes().to_vec())),
+        }
+    }
+}
+
+struct ChunkStats {
+    nulls: u64,
+    /// Min and max, absent when the chunk holds only nulls or the writer skipped them.
+    range: Option<(Bound, Bound)>,
+    /// Whether min and max are the actual values rather than truncated bounds.
+    exact: bool,
+}
+
+fn is_plain_integer(descr: &ColumnDescriptor) -> bool {
+    match descr.logical_type() {
+        Some(LogicalType::Integer { is_signed, .. }) => is_signed,
+        Some(_) => false,
+        None => descr.converted_type() == ConvertedType::NONE,
+    }
+}
+
+fn is_string(descr: &ColumnDescriptor) -> bool {
+    matches!(descr.logical_type(), Some(LogicalType::String))
+        || descr.converted_type() == ConvertedType::UTF8
+}
+
+/// Statistics of the top-level column `column`, if its type can be compared.
+fn chunk_stats(row_group: &RowGroupMetaData, column: &str) -> Option<ChunkStats> {
+    let chunk = row_group
+        .columns()
+        .iter()
+        .find(|chunk| chunk.column_descr().path().parts() == [column])?;
+    let descr = chunk.column_descr();
+    let stats = chunk.statistics()?;
+
+    let range = match stats {
+        Statistics::Int32(v) if is_plain_integer(descr) => v
+            .has_min_max_set()
+            .then(|| (Bound::Int(*v.min() as i64), Bound::Int(*v.max() as i64))),
+        Statistics::Int64(v) if is_plain_integer(descr) => v
+            .has_min_max_set()
+            .then(|| (Bound::Int(*v.min()), Bound::Int(*v.max()))),
+        Statistics::ByteArray(v) if is_string(descr) => v.has_min_max_set().then(|| {
+            (
+                Bound::Text(v.min().data().to_vec()),
+                Bound::Text(v.max().data().to_vec()),
+            )
+        }),
+        _ => return None,
+    };
+
+    Some(ChunkStats {
+        nulls: stats.null_count(),
+        range,
+        exact: stats.min_is_exact() && stats.max_is_exact(),
+    })
+}
+
+fn classify_condition(
+    row_group: &RowGroupMetaData,
+    column: &str,
+    operator: &FilterOperator,
+    value: Option<&Value>,
+) -> RowGroupMatch {
+    let Some(stats) = chunk_stats(row_group, column) else {
+        return RowGroupMatch::Partial;
+    };
+    let rows = row_group.num_rows().max(0) as u64;
+    let by_count = |matching: u64| {
+        if matching == 0 {
+            RowGroupMatch::Empty
+        } else if matching >= rows {
+            RowGroupMatch::All
+        } else {
+            RowGroupMatch::Partial
+        }
+    };
+
+    match operator {
+        FilterOperator::IsNull => return by_count(stats.nulls),
+        FilterOperator::IsNotNull => return by_count(rows.saturating_sub(stats.nulls)),
+        FilterOperator::Like | FilterOperator::NotLike => return RowGroupMatch::Partial,
+        _ => {}
+    }
+
+    // Comparisons are never true for nulls
+    if stats.nulls >= rows {
+        return RowGroupMatch::Empty;
+    }
+    let Some((min, max)) = &stats.range else {
+        return RowGroupMatch::Partial;
+    };
+    let values: Option<Vec<Bound>> = match (operator, value) {
+        (FilterOperator::In | FilterOperator::NotIn, Some(Value::Array(items))) => items
+            .iter()
+            .map(|item| Bound::from_json(item, min))
+            .collect(),
+        (_, Some(value)) => Bound::from_json(value, min).map(|v| vec![v]),
+        (_, None) => None,
+    };
+    let Some(values) = values.filter(|values| !values.is_empty()) else {
+        return RowGroupMatch::Partial;
+    };
+
+    let no_nulls = stats.nulls == 0;
+    // Truncated bounds still enclose every value, so only "every value equals
+    // v" needs them to be exact
+    let outside = |v: &Bound| v < min || v > max;
+    let only = |v: &Bound| stats.exact && min == max && min == v;
+    let decide = |empty: bool, all: bool| {
+        if empty {
+            RowGroupMatch::Empty
+        } else if all && no_nulls {
+            RowGroupMatch::All
+        } else {
+            RowGroupMatch::Partial
+        }
+    };
+
+    let v = &values[0];
+    match operator {
+        FilterOperator::Eq => decide(outside(v), only(v)),
+        FilterOperator::NotEq => decide(only(v), outside(v)),
+        FilterOperator::Gt => decide(max <= v, min > v),
+        FilterOperator::GtEq => decide(max < v, min >= v),
+        FilterOperator::Lt => decide(min >= v, max < v),
+        FilterOperator::LtEq => decide(min > v, max <= v),
+        FilterOperator::In => decide(values.iter().all(outside), values.iter().any(only)),
+        FilterOperator::NotIn => decide(values.iter().any(only), values.iter().all(outside)),
+        _ => RowGroupMatch::Partial,
+    }
+}
@@ -0,0 +1,98 @@
+use bytes::Bytes;
+use memmap2::Mmap;
+use parquet::file::reader::{ChunkReader, Length};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+
+/// Read buffer used in `ReadMode::Buffered`.
+const READ_BUFFER_BYTES: usize = 1024 * 1024;
+
+/// How Parquet files are read from disk.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ReadMode {
+    /// Plain file reads with the parquet crate's default buffering.
+    #[default]
+    Direct,
+    /// File reads through a large buffer, so spinning disks and network
+    /// mounts see fewer, bigger requests.
+    Buffered,
+    /// The whole file memory-mapped; pages are faulted in by the OS.
+    Mmap,
+}
+
+impl ReadMode {
+    /// Parse `"direct"`, `"buffered"` or `"mmap"`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "direct" => Ok(ReadMode::Direct),
+            "buffered" => Ok(ReadMode::Buffered),
+            "mmap" => Ok(ReadMode::Mmap),
+            other => Err(format!("Unsupported read mode: {}", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReadMode::Direct => "direct",
+            ReadMode::Buffered => "buffered",
+            ReadMode::Mmap => "mmap",
+        }
+    }
+}
+
+/// A Parquet file opened according to a `ReadMode`, usable wherever the
+/// parquet crate takes a `ChunkReader`.
+pub enum FileSource {
+    Direct(File),
+    Buffered(File),
+    Mapped(Bytes),
+}
+
+impl FileSource {
+    pub fn open(path: &str, mode: ReadMode) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        Ok(match mode {
+            ReadMode::Direct => FileSource::Direct(file),
+            ReadMode::Buffered => FileSource::Buffered(file),
+            ReadMode::Mmap => {
+                // Safety: the mapping is read-only. A file truncated while mapped
+                // faults on access; the watcher evicts changed files so they are
+                // reopened rather than read through a stale mapping.
+                let map = unsafe { Mmap::map(&file) }.map_err(|e| e.to_string())?;
+                FileSource::Mapped(Bytes::from_owner(map))
+            }
+        })
+    }
+}
+
+impl Length for FileSource {
+    fn len(&self) -> u64 {
+        match self {
+            FileSource::Direct(file) | FileSource::Buffered(file) => file.len(),
+            FileSource::Mapped(bytes) => bytes.len() as u64,
+        }
+    }
+}
+
+impl ChunkReader for FileSource {
+    type T = Box<dyn Read + Send>;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        Ok(match self {
+            FileSource::Direct(file) => Box::new(file.get_read(start)?),
+            FileSource::Buffered(file) => {
+                let mut file = file.try_clone()?;
+                file.seek(SeekFrom::Start(start))?;
+                Box::new(BufReader::with_capacity(READ_BUFFER_BYTES, file))
+            }
+            FileSource::Mapped(bytes) => Box::new(bytes.get_read(start)?),
+        })
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        match self {
+            FileSource::Direct(file) | FileSource::Buffered(file) => file.get_bytes(start, length),
+            FileSource::Mapped(bytes) => bytes.get_bytes(start, length),
+        }
+    }
+}
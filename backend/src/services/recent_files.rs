@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::RecentFile;
+use crate::services::store;
+
+const STORE_FILE: &str = "recent_files.json";
+
+/// Unpinned files kept; the oldest are dropped first. Pinned files are never dropped.
+const MAX_RECENT_FILES: usize = 50;
+
+fn store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STORE_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<Vec<RecentFile>, String> {
+    Ok(store::read_json(&store_path(data_dir), "recent files store")?.unwrap_or_default())
+}
+
+fn store(data_dir: &Path, files: &[RecentFile]) -> Result<(), String> {
+    store::atomic_write_json(&store_path(data_dir), files)
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// Record that `path` was opened, refreshing its size and row count.
+pub fn add_recent_file(
+    data_dir: &Path,
+    path: &str,
+    row_count: Option<i64>,
+) -> Result<RecentFile, String> {
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Cannot open {}: {}", path, e))?
+        .len();
+
+    store::locked(|| {
+        let mut files = load(data_dir)?;
+        let pinned = files.iter().any(|f| f.path == path && f.pinned);
+        files.retain(|f| f.path != path);
+
+        let recent = RecentFile {
+            path: path.to_string(),
+            name: file_name(path),
+            pinned,
+            last_opened: now(),
+            size: Some(size),
+            row_count,
+            exists: true,
+        };
+        files.insert(0, recent.clone());
+
+        // Drop the oldest unpinned files beyond the limit
+        let mut unpinned = 0;
+        files.retain(|f| {
+            if f.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= MAX_RECENT_FILES
+        });
+
+        store(data_dir, &files)?;
+        Ok(recent)
+    })
+}
+
+/// Pinned files first, then the rest by when they were last opened, each
+/// checked for whether it still exists.
+pub fn list_recent_files(data_dir: &Path) -> Result<Vec<RecentFile>, String> {
+    let mut files = load(data_dir)?;
+    for file in &mut files {
+        file.exists = Path::new(&file.path).is_file();
+    }
+    // RFC 3339 timestamps in UTC sort chronologically as strings
+    files.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| b.last_opened.cmp(&a.last_opened))
+    });
+    Ok(files)
+}
+
+/// Pin or unpin `path`, adding it to the list if it isn't there yet.
+pub fn pin_file(data_dir: &Path, path: &str, pinned: bool) -> Result<RecentFile, String> {
+    store::locked(|| {
+        let mut files = load(data_dir)?;
+        let index = match files.iter().position(|f| f.path == path) {
+            Some(index) => index,
+            None => {
+                if !Path::new(path).is_file() {
+                    return Err(format!("File not found: {}", path));
+                }
+                files.push(RecentFile {
+                    path: path.to_string(),
+                    name: file_name(path),
+                    pinned,
+                    last_opened: now(),
+                    size: fs::metadata(path).ok().map(|m| m.len()),
+                    row_count: None,
+                    exists: true,
+                });
+                files.len() - 1
+            }
+        };
+        let file = &mut files[index];
+        file.pinned = pinned;
+        file.exists = Path::new(path).is_file();
+        let file = file.clone();
+
+        store(data_dir, &files)?;
+        Ok(file)
+    })
+}
+
+pub fn remove_recent(data_dir: &Path, path: &str) -> Result<(), String> {
+    store::locked(|| {
+        let mut files = load(data_dir)?;
+        let before = files.len();
+        files.retain(|f| f.path != path);
+        if files.len() == before {
+            return Err(format!("Not a recent file: {}", path));
+        }
+        store(data_dir, &files)
+    })
+}
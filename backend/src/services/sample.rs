@@ -0,0 +1,171 @@
+use arrow::array::UInt32Array;
+use arrow::compute::take_record_batch;
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReaderBuilder, RowSelection, RowSelector};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::services::parquet::batches_to_rows;
+
+/// Small seedable generator (SplitMix64) so a sample can be reproduced from its seed.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        SplitMix64(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+fn open_reader(path: &str) -> Result<ParquetRecordBatchReaderBuilder<File>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    ParquetRecordBatchReaderBuilder::try_new(file).map_err(|e| e.to_string())
+}
+
+/// The first `n` rows of the file.
+fn sample_first(path: &str, n: usize) -> Result<Vec<Value>, String> {
+    let reader = open_reader(path)?
+        .with_batch_size(n.clamp(1, 8192))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut batches = Vec::new();
+    let mut remaining = n;
+    for batch in reader {
+        if remaining == 0 {
+            break;
+        }
+        let batch = batch.map_err(|e| e.to_string())?;
+        let take = remaining.min(batch.num_rows());
+        batches.push(batch.slice(0, take));
+        remaining -= take;
+    }
+
+    batches_to_rows(&batches)
+}
+
+/// `n` distinct rows chosen uniformly (Floyd's algorithm) from the row count in
+/// the footer. Only the chosen rows are decoded; everything else is skipped.
+fn sample_random(path: &str, n: usize, seed: Option<u64>) -> Result<Vec<Value>, String> {
+    let builder = open_reader(path)?;
+    let total = builder.metadata().file_metadata().num_rows().max(0) as u64;
+    let n = (n as u64).min(total);
+
+    let mut rng = SplitMix64::new(seed);
+    let mut chosen = BTreeSet::new();
+    for upper in (total - n)..total {
+        let candidate = rng.below(upper + 1);
+        if !chosen.insert(candidate) {
+            chosen.insert(upper);
+        }
+    }
+
+    let mut selectors = Vec::new();
+    let mut position = 0;
+    for row in chosen {
+        if row > position {
+            selectors.push(RowSelector::skip((row - position) as usize));
+        }
+        selectors.push(RowSelector::select(1));
+        position = row + 1;
+    }
+    if total > position {
+        selectors.push(RowSelector::skip((total - position) as usize));
+    }
+
+    let reader = builder
+        .with_row_selection(RowSelection::from(selectors))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    batches_to_rows(&batches)
+}
+
+/// Single-pass reservoir sampling (Algorithm R). Reads the whole file but keeps
+/// at most `n` rows in memory.
+fn sample_reservoir(path: &str, n: usize, seed: Option<u64>) -> Result<Vec<Value>, String> {
+    let reader = open_reader(path)?.build().map_err(|e| e.to_string())?;
+
+    let mut rng = SplitMix64::new(seed);
+    let mut reservoir: Vec<Value> = Vec::with_capacity(n);
+    let mut seen: u64 = 0;
+
+    for batch in reader {
+        let batch = batch.map_err(|e| e.to_string())?;
+
+        // Decide slot per row first, then decode only the rows that are kept
+        let mut indices = Vec::new();
+        let mut slots = Vec::new();
+        for i in 0..batch.num_rows() {
+            if reservoir.len() + slots.len() < n {
+                indices.push(i as u32);
+                slots.push(None);
+            } else {
+                let j = rng.below(seen + 1);
+                if j < n as u64 {
+                    indices.push(i as u32);
+                    slots.push(Some(j as usize));
+                }
+            }
+            seen += 1;
+        }
+        if indices.is_empty() {
+            continue;
+        }
+
+        let kept =
+            take_record_batch(&batch, &UInt32Array::from(indices)).map_err(|e| e.to_string())?;
+        for (row, slot) in batches_to_rows(&[kept])?.into_iter().zip(slots) {
+            match slot {
+                Some(j) => reservoir[j] = row,
+                None => reservoir.push(row),
+            }
+        }
+    }
+
+    Ok(reservoir)
+}
+
+/// Pull a sample of `n` rows. `method` is "first", "random" (uniform rows read
+/// via row selection) or "reservoir" (single streaming pass). `seed` makes the
+/// random methods reproducible.
+pub async fn sample_rows(
+    path: &str,
+    n: usize,
+    method: &str,
+    seed: Option<u64>,
+) -> Result<Vec<Value>, String> {
+    let path = path.to_string();
+    let method = method.to_lowercase();
+
+    tokio::task::spawn_blocking(move || match method.as_str() {
+        "first" => sample_first(&path, n),
+        "random" => sample_random(&path, n, seed),
+        "reservoir" => sample_reservoir(&path, n, seed),
+        other => Err(format!("Unsupported sampling method: {}", other)),
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+
+use crate::models::SavedQuery;
+use crate::services::store;
+
+const STORE_FILE: &str = "saved_queries.json";
+
+fn store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STORE_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<Vec<SavedQuery>, String> {
+    Ok(store::read_json(&store_path(data_dir), "saved query store")?.unwrap_or_default())
+}
+
+fn store(data_dir: &Path, queries: &[SavedQuery]) -> Result<(), String> {
+    store::atomic_write_json(&store_path(data_dir), queries)
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+pub fn save_query(
+    data_dir: &Path,
+    name: &str,
+    query: &str,
+    file_path: Option<String>,
+    tags: Vec<String>,
+) -> Result<SavedQuery, String> {
+    if name.trim().is_empty() {
+        return Err("Query name must not be empty".to_string());
+    }
+
+    store::locked(|| {
+        let mut queries = load(data_dir)?;
+        let id = queries.iter().map(|q| q.id).max().unwrap_or(0) + 1;
+        let timestamp = now();
+        let saved = SavedQuery {
+            id,
+            name: name.trim().to_string(),
+            query: query.to_string(),
+            file_path,
+            tags,
+            created_at: timestamp.clone(),
+            updated_at: timestamp,
+        };
+        queries.push(saved.clone());
+        store(data_dir, &queries)?;
+        Ok(saved)
+    })
+}
+
+/// Saved queries, optionally only those associated with `file_path` (or with no
+/// file at all) and carrying `tag`.
+pub fn list_queries(
+    data_dir: &Path,
+    file_path: Option<&str>,
+    tag: Option<&str>,
+) -> Result<Vec<SavedQuery>, String> {
+    let mut queries: Vec<SavedQuery> = load(data_dir)?
+        .into_iter()
+        .filter(|q| match (file_path, q.file_path.as_deref()) {
+            (Some(wanted), Some(associated)) => wanted == associated,
+            _ => true,
+        })
+        .filter(|q| tag.is_none_or(|tag| q.tags.iter().any(|t| t == tag)))
+        .collect();
+    queries.sort_by_key(|q| q.name.to_lowercase());
+    Ok(queries)
+}
+
+pub fn rename_query(data_dir: &Path, id: u64, name: &str) -> Result<SavedQuery, String> {
+    if name.trim().is_empty() {
+        return Err("Query name must not be empty".to_string());
+    }
+
+    store::locked(|| {
+        let mut queries = load(data_dir)?;
+        let saved = queries
+            .iter_mut()
+            .find(|q| q.id == id)
+            .ok_or_else(|| format!("Saved query not found: {}", id))?;
+        saved.name = name.trim().to_string();
+        saved.updated_at = now();
+        let saved = saved.clone();
+
+        store(data_dir, &queries)?;
+        Ok(saved)
+    })
+}
+
+pub fn delete_query(data_dir: &Path, id: u64) -> Result<(), String> {
+    store::locked(|| {
+        let mut queries = load(data_dir)?;
+        let before = queries.len();
+        queries.retain(|q| q.id != id);
+        if queries.len() == before {
+            return Err(format!("Saved query not found: {}", id));
+        }
+        store(data_dir, &queries)
+    })
+}
@@ -0,0 +1,847 @@
+use arrow::datatypes::IntervalUnit;
+use arrow::datatypes::{DataType, Field, Fields, Schema, TimeUnit};
+use parquet::arrow::parquet_to_arrow_schema;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::models::{SchemaDocument, TableDdl};
+use crate::services::inspect::read_footer;
+
+/// The Arrow schema of a file, as the query engine sees it.
+pub(crate) fn arrow_schema(path: &str) -> Result<Schema, String> {
+    let footer = read_footer(path)?;
+    let file_metadata = footer.file_metadata();
+    parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// SQL engines `generate_ddl` can write for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dialect {
+    Spark,
+    Trino,
+    BigQuery,
+    Snowflake,
+    Postgres,
+    DuckDb,
+}
+
+impl Dialect {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "spark" => Ok(Dialect::Spark),
+            "trino" | "presto" => Ok(Dialect::Trino),
+            "bigquery" => Ok(Dialect::BigQuery),
+            "snowflake" => Ok(Dialect::Snowflake),
+            "postgres" | "postgresql" => Ok(Dialect::Postgres),
+            "duckdb" => Ok(Dialect::DuckDb),
+            other => Err(format!("Unsupported SQL dialect: {}", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dialect::Spark => "spark",
+            Dialect::Trino => "trino",
+            Dialect::BigQuery => "bigquery",
+            Dialect::Snowflake => "snowflake",
+            Dialect::Postgres => "postgres",
+            Dialect::DuckDb => "duckdb",
+        }
+    }
+
+    fn quote(&self, name: &str) -> String {
+        match self {
+            Dialect::Spark => format!("`{}`", name.replace('`', "``")),
+            Dialect::BigQuery => format!("`{}`", name.replace('`', "\\`")),
+            _ => format!("\"{}\"", name.replace('"', "\"\"")),
+        }
+    }
+
+    /// Type used for values the dialect has no equivalent for.
+    fn text_type(&self) -> &'static str {
+        match self {
+            Dialect::Spark | Dialect::BigQuery => "STRING",
+            Dialect::Postgres => "TEXT",
+            _ => "VARCHAR",
+        }
+    }
+
+    /// Widest DECIMAL precision the dialect accepts.
+    fn max_decimal_precision(&self) -> u8 {
+        match self {
+            Dialect::BigQuery => 76,
+            Dialect::Postgres => 255,
+            _ => 38,
+        }
+    }
+}
+
+/// Maps Arrow types to one dialect's column types, noting lossy mappings.
+struct TypeMapper {
+    dialect: Dialect,
+    warnings: Vec<String>,
+}
+
+impl TypeMapper {
+    fn fallback(&mut self, column: &str, data_type: &DataType, to: &str) -> String {
+        // Nested types print every child field in full, so only name the kind
+        let label = match data_type {
+            DataType::Struct(_) => "struct".to_string(),
+            DataType::Map(_, _) => "map".to_string(),
+            dt if is_list(dt) => "list".to_string(),
+            dt => dt.to_string(),
+        };
+        self.warnings.push(format!(
+            "{}: {} has no {} equivalent, declared as {}",
+            column,
+            label,
+            self.dialect.name(),
+            to
+        ));
+        to.to_string()
+    }
+
+    fn sql_type(&mut self, column: &str, data_type: &DataType) -> String {
+        use Dialect::*;
+        let d = self.dialect;
+        let s = |t: &str| t.to_string();
+
+        match data_type {
+            DataType::Boolean => s(if d == BigQuery { "BOOL" } else { "BOOLEAN" }),
+            DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64
+                if d == BigQuery =>
+            {
+                s("INT64")
+            }
+            DataType::Int8 if d == Postgres => s("SMALLINT"),
+            DataType::Int8 => s("TINYINT"),
+            DataType::Int16 => s("SMALLINT"),
+            DataType::Int32 if d == Spark => s("INT"),
+            DataType::Int32 => s("INTEGER"),
+            DataType::Int64 => s("BIGINT"),
+            // Unsigned types widen to the next signed type that holds every value
+            DataType::UInt8 | DataType::UInt16 | DataType::UInt32 if d == BigQuery => s("INT64"),
+            DataType::UInt8 if d == DuckDb => s("UTINYINT"),
+            DataType::UInt16 if d == DuckDb => s("USMALLINT"),
+            DataType::UInt32 if d == DuckDb => s("UINTEGER"),
+            DataType::UInt64 if d == DuckDb => s("UBIGINT"),
+            DataType::UInt8 => s("SMALLINT"),
+            DataType::UInt16 if d == Spark => s("INT"),
+            DataType::UInt16 => s("INTEGER"),
+            DataType::UInt32 => s("BIGINT"),
+            DataType::UInt64 => match d {
+                BigQuery => s("NUMERIC"),
+                Snowflake => s("NUMBER(20, 0)"),
+                Postgres => s("NUMERIC(20, 0)"),
+                _ => s("DECIMAL(20, 0)"),
+            },
+            DataType::Float16 | DataType::Float32 => match d {
+                Spark | Snowflake | DuckDb => s("FLOAT"),
+                BigQuery => s("FLOAT64"),
+                Trino | Postgres => s("REAL"),
+            },
+            DataType::Float64 => match d {
+                BigQuery => s("FLOAT64"),
+                Postgres => s("DOUBLE PRECISION"),
+                _ => s("DOUBLE"),
+            },
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                if *precision > d.max_decimal_precision() {
+                    return self.fallback(column, data_type, d.text_type());
+                }
+                match d {
+                    BigQuery if *precision > 29 || *scale > 9 => {
+                        format!("BIGNUMERIC({}, {})", precision, scale)
+                    }
+                    BigQuery => format!("NUMERIC({}, {})", precision, scale),
+                    Snowflake => format!("NUMBER({}, {})", precision, scale),
+                    Postgres => format!("NUMERIC({}, {})", precision, scale),
+                    _ => format!("DECIMAL({}, {})", precision, scale),
+                }
+            }
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => s(d.text_type()),
+            DataType::Binary
+            | DataType::LargeBinary
+            | DataType::BinaryView
+            | DataType::FixedSizeBinary(_) => match d {
+                Spark | Snowflake => s("BINARY"),
+                Trino => s("VARBINARY"),
+                BigQuery => s("BYTES"),
+                Postgres => s("BYTEA"),
+                DuckDb => s("BLOB"),
+            },
+            DataType::Date32 | DataType::Date64 => s("DATE"),
+            DataType::Timestamp(unit, tz) => self.timestamp_type(unit, tz.is_some()),
+            DataType::Time32(unit) | DataType::Time64(unit) => match d {
+                Spark => self.fallback(column, data_type, d.text_type()),
+                Trino | Snowflake => format!("TIME({})", precision(unit)),
+                _ => s("TIME"),
+            },
+            DataType::Duration(_) => self.fallback(column, data_type, "BIGINT"),
+            DataType::Dictionary(_, value) => self.sql_type(column, value),
+            DataType::List(item)
+            | DataType::LargeList(item)
+            | DataType::ListView(item)
+            | DataType::LargeListView(item)
+            | DataType::FixedSizeList(item, _) => self.list_type(column, data_type, item),
+            DataType::Struct(fields) => self.struct_type(column, data_type, fields),
+            DataType::Map(entries, _) => self.map_type(column, data_type, entries),
+            _ => self.fallback(column, data_type, d.text_type()),
+        }
+    }
+
+    /// Timestamps with a time zone in Parquet are UTC instants; without one
+    /// they are wall-clock times.
+    fn timestamp_type(&mut self, unit: &TimeUnit, instant: bool) -> String {
+        let p = precision(unit);
+        match (self.dialect, instant) {
+            (Dialect::Spark, true) => "TIMESTAMP".to_string(),
+            (Dialect::Spark, false) => "TIMESTAMP_NTZ".to_string(),
+            (Dialect::Trino, true) => format!("TIMESTAMP({}) WITH TIME ZONE", p),
+            (Dialect::Trino, false) => format!("TIMESTAMP({})", p),
+            (Dialect::BigQuery, true) => "TIMESTAMP".to_string(),
+            (Dialect::BigQuery, false) => "DATETIME".to_string(),
+            (Dialect::Snowflake, true) => format!("TIMESTAMP_LTZ({})", p),
+            (Dialect::Snowflake, false) => format!("TIMESTAMP_NTZ({})", p),
+            (Dialect::Postgres, true) => "TIMESTAMPTZ".to_string(),
+            (Dialect::Postgres, false) => "TIMESTAMP".to_string(),
+            (Dialect::DuckDb, true) => "TIMESTAMPTZ".to_string(),
+            (Dialect::DuckDb, false) => match unit {
+                TimeUnit::Second => "TIMESTAMP_S".to_string(),
+                TimeUnit::Millisecond => "TIMESTAMP_MS".to_string(),
+                TimeUnit::Microsecond => "TIMESTAMP".to_string(),
+                TimeUnit::Nanosecond => "TIMESTAMP_NS".to_string(),
+            },
+        }
+    }
+
+    fn list_type(&mut self, column: &str, data_type: &DataType, item: &Field) -> String {
+        match self.dialect {
+            // Untyped semi-structured array
+            Dialect::Snowflake => "ARRAY".to_string(),
+            Dialect::BigQuery if is_list(item.data_type()) => {
+                self.fallback(column, data_type, "JSON")
+            }
+            Dialect::Postgres if !is_scalar(item.data_type()) => {
+                self.fallback(column, data_type, "JSONB")
+            }
+            dialect => {
+                let item = self.sql_type(column, item.data_type());
+                match dialect {
+                    Dialect::Spark | Dialect::BigQuery => format!("ARRAY<{}>", item),
+                    Dialect::Trino => format!("ARRAY({})", item),
+                    _ => format!("{}[]", item),
+                }
+            }
+        }
+    }
+
+    fn struct_type(&mut self, column: &str, data_type: &DataType, fields: &Fields) -> String {
+        let d = self.dialect;
+        match d {
+            Dialect::Snowflake => return "OBJECT".to_string(),
+            Dialect::Postgres => return self.fallback(column, data_type, "JSONB"),
+            _ => {}
+        }
+        let members: Vec<String> = fields
+            .iter()
+            .map(|f| {
+                let name = d.quote(f.name());
+                let ty = self.sql_type(&format!("{}.{}", column, f.name()), f.data_type());
+                match d {
+                    Dialect::Spark => format!("{}: {}", name, ty),
+                    _ => format!("{} {}", name, ty),
+                }
+            })
+            .collect();
+        match d {
+            Dialect::Spark | Dialect::BigQuery => format!("STRUCT<{}>", members.join(", ")),
+            Dialect::Trino => format!("ROW({})", members.join(", ")),
+            _ => format!("STRUCT({})", members.join(", ")),
+        }
+    }
+
+    fn map_type(&mut self, column: &str, data_type: &DataType, entries: &Field) -> String {
+        let DataType::Struct(kv) = entries.data_type() else {
+            return self.fallback(column, data_type, self.dialect.text_type());
+        };
+        if kv.len() != 2 {
+            return self.fallback(column, data_type, self.dialect.text_type());
+        }
+        match self.dialect {
+            Dialect::Snowflake => "OBJECT".to_string(),
+            Dialect::Postgres => self.fallback(column, data_type, "JSONB"),
+            // BigQuery loads Parquet maps as repeated key/value records
+            Dialect::BigQuery => {
+                let key = self.sql_type(column, kv[0].data_type());
+                let value = self.sql_type(column, kv[1].data_type());
+                format!("ARRAY<STRUCT<key {}, value {}>>", key, value)
+            }
+            d => {
+                let key = self.sql_type(column, kv[0].data_type());
+                let value = self.sql_type(column, kv[1].data_type());
+                match d {
+                    Dialect::Spark => format!("MAP<{}, {}>", key, value),
+                    _ => format!("MAP({}, {})", key, value),
+                }
+            }
+        }
+    }
+}
+
+/// Fractional second digits of a time unit.
+fn precision(unit: &TimeUnit) -> u8 {
+    match unit {
+        TimeUnit::Second => 0,
+        TimeUnit::Millisecond => 3,
+        TimeUnit::Microsecond => 6,
+        TimeUnit::Nanosecond => 9,
+    }
+}
+
+fn is_list(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::List(_)
+            | DataType::LargeList(_)
+            | DataType::ListView(_)
+            | DataType::LargeListView(_)
+            | DataType::FixedSizeList(_, _)
+    )
+}
+
+fn is_scalar(data_type: &DataType) -> bool {
+    !is_list(data_type) && !matches!(data_type, DataType::Struct(_) | DataType::Map(_, _))
+}
+
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("parquet_table")
+        .to_string()
+}
+
+/// A `CREATE TABLE` statement for `dialect` declaring the file's columns,
+/// named after the file. Required top-level columns are `NOT NULL`.
+pub fn generate_ddl(path: &str, dialect: &str) -> Result<TableDdl, String> {
+    let dialect = Dialect::parse(dialect)?;
+    let schema = arrow_schema(path)?;
+    let table = file_stem(path);
+
+    let mut mapper = TypeMapper {
+        dialect,
+        warnings: Vec::new(),
+    };
+    let columns: Vec<String> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let ty = mapper.sql_type(field.name(), field.data_type());
+            let not_null = if field.is_nullable() { "" } else { " NOT NULL" };
+            format!("  {} {}{}", dialect.quote(field.name()), ty, not_null)
+        })
+        .collect();
+
+    let storage = match dialect {
+        Dialect::Spark => "\nUSING PARQUET",
+        Dialect::Trino => "\nWITH (format = 'PARQUET')",
+        _ => "",
+    };
+    let statement = format!(
+        "CREATE TABLE {} (\n{}\n){};",
+        dialect.quote(&table),
+        columns.join(",\n"),
+        storage
+    );
+
+    Ok(TableDdl {
+        dialect: dialect.name().to_string(),
+        table,
+        statement,
+        warnings: mapper.warnings,
+    })
+}
+
+/// Machine-readable schema formats `export_schema` can write.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaFormat {
+    /// The JSON schema representation of the Arrow integration tests.
+    Arrow,
+    Avro,
+    JsonSchema,
+}
+
+impl SchemaFormat {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "arrow" => Ok(SchemaFormat::Arrow),
+            "avro" | "avsc" => Ok(SchemaFormat::Avro),
+            "json_schema" | "jsonschema" => Ok(SchemaFormat::JsonSchema),
+            other => Err(format!("Unsupported schema format: {}", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SchemaFormat::Arrow => "arrow",
+            SchemaFormat::Avro => "avro",
+            SchemaFormat::JsonSchema => "json_schema",
+        }
+    }
+}
+
+fn unit_name(unit: &TimeUnit) -> &'static str {
+    match unit {
+        TimeUnit::Second => "SECOND",
+        TimeUnit::Millisecond => "MILLISECOND",
+        TimeUnit::Microsecond => "MICROSECOND",
+        TimeUnit::Nanosecond => "NANOSECOND",
+    }
+}
+
+fn metadata_json(metadata: &HashMap<String, String>) -> Value {
+    let mut entries: Vec<_> = metadata.iter().collect();
+    entries.sort();
+    Value::Array(
+        entries
+            .into_iter()
+            .map(|(key, value)| json!({ "key": key, "value": value }))
+            .collect(),
+    )
+}
+
+fn arrow_field_json(field: &Field) -> Value {
+    let (data_type, dictionary) = match field.data_type() {
+        DataType::Dictionary(key, value) => (
+            value.as_ref(),
+            Some(json!({
+                "id": field.dict_id().unwrap_or(0),
+                "indexType": arrow_type_json(key),
+                "isOrdered": field.dict_is_ordered().unwrap_or(false),
+            })),
+        ),
+        data_type => (data_type, None),
+    };
+    let children: Vec<Value> = match data_type {
+        DataType::List(item)
+        | DataType::LargeList(item)
+        | DataType::ListView(item)
+        | DataType::LargeListView(item)
+        | DataType::FixedSizeList(item, _)
+        | DataType::Map(item, _) => vec![arrow_field_json(item)],
+        DataType::Struct(fields) => fields.iter().map(|f| arrow_field_json(f)).collect(),
+        _ => Vec::new(),
+    };
+
+    let mut out = Map::new();
+    out.insert("name".into(), json!(field.name()));
+    out.insert("nullable".into(), json!(field.is_nullable()));
+    out.insert("type".into(), arrow_type_json(data_type));
+    out.insert("children".into(), Value::Array(children));
+    if let Some(dictionary) = dictionary {
+        out.insert("dictionary".into(), dictionary);
+    }
+    if !field.metadata().is_empty() {
+        out.insert("metadata".into(), metadata_json(field.metadata()));
+    }
+    Value::Object(out)
+}
+
+fn arrow_type_json(data_type: &DataType) -> Value {
+    let int =
+        |bits: u32, signed: bool| json!({ "name": "int", "bitWidth": bits, "isSigned": signed });
+    let float = |precision: &str| json!({ "name": "floatingpoint", "precision": precision });
+    match data_type {
+        DataType::Null => json!({ "name": "null" }),
+        DataType::Boolean => json!({ "name": "bool" }),
+        DataType::Int8 => int(8, true),
+        DataType::Int16 => int(16, true),
+        DataType::Int32 => int(32, true),
+        DataType::Int64 => int(64, true),
+        DataType::UInt8 => int(8, false),
+        DataType::UInt16 => int(16, false),
+        DataType::UInt32 => int(32, false),
+        DataType::UInt64 => int(64, false),
+        DataType::Float16 => float("HALF"),
+        DataType::Float32 => float("SINGLE"),
+        DataType::Float64 => float("DOUBLE"),
+        DataType::Utf8 => json!({ "name": "utf8" }),
+        DataType::LargeUtf8 => json!({ "name": "largeutf8" }),
+        DataType::Utf8View => json!({ "name": "utf8view" }),
+        DataType::Binary => json!({ "name": "binary" }),
+        DataType::LargeBinary => json!({ "name": "largebinary" }),
+        DataType::BinaryView => json!({ "name": "binaryview" }),
+        DataType::FixedSizeBinary(width) => {
+            json!({ "name": "fixedsizebinary", "byteWidth": width })
+        }
+        DataType::Decimal128(precision, scale) => {
+            json!({ "name": "decimal", "precision": precision, "scale": scale, "bitWidth": 128 })
+        }
+        DataType::Decimal256(precision, scale) => {
+            json!({ "name": "decimal", "precision": precision, "scale": scale, "bitWidth": 256 })
+        }
+        DataType::Date32 => json!({ "name": "date", "unit": "DAY" }),
+        DataType::Date64 => json!({ "name": "date", "unit": "MILLISECOND" }),
+        DataType::Time32(unit) => {
+            json!({ "name": "time", "unit": unit_name(unit), "bitWidth": 32 })
+        }
+        DataType::Time64(unit) => {
+            json!({ "name": "time", "unit": unit_name(unit), "bitWidth": 64 })
+        }
+        DataType::Timestamp(unit, tz) => match tz {
+            Some(tz) => {
+                json!({ "name": "timestamp", "unit": unit_name(unit), "timezone": tz.as_ref() })
+            }
+            None => json!({ "name": "timestamp", "unit": unit_name(unit) }),
+        },
+        DataType::Duration(unit) => json!({ "name": "duration", "unit": unit_name(unit) }),
+        DataType::Interval(unit) => json!({
+            "name": "interval",
+            "unit": match unit {
+                IntervalUnit::YearMonth => "YEAR_MONTH",
+                IntervalUnit::DayTime => "DAY_TIME",
+                IntervalUnit::MonthDayNano => "MONTH_DAY_NANO",
+            },
+        }),
+        DataType::List(_) => json!({ "name": "list" }),
+        DataType::LargeList(_) => json!({ "name": "largelist" }),
+        DataType::ListView(_) => json!({ "name": "listview" }),
+        DataType::LargeListView(_) => json!({ "name": "largelistview" }),
+        DataType::FixedSizeList(_, size) => json!({ "name": "fixedsizelist", "listSize": size }),
+        DataType::Struct(_) => json!({ "name": "struct" }),
+        DataType::Map(_, sorted) => json!({ "name": "map", "keysSorted": sorted }),
+        other => json!({ "name": other.to_string() }),
+    }
+}
+
+fn arrow_schema_json(schema: &Schema) -> Value {
+    let mut out = Map::new();
+    out.insert(
+        "fields".into(),
+        Value::Array(
+            schema
+                .fields()
+                .iter()
+                .map(|f| arrow_field_json(f))
+                .collect(),
+        ),
+    );
+    if !schema.metadata().is_empty() {
+        out.insert("metadata".into(), metadata_json(schema.metadata()));
+    }
+    Value::Object(out)
+}
+
+/// `name` as a valid Avro name: letters, digits and underscores, not
+/// starting with a digit.
+fn avro_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// `avro_name(name)`, suffixed with `_2`, `_3`, ... if that's already in
+/// `used`, and added to it.
+fn unique_avro_name(name: &str, used: &mut HashSet<String>) -> String {
+    let base = avro_name(name);
+    let mut unique = base.clone();
+    let mut n = 2;
+    while used.contains(&unique) {
+        unique = format!("{}_{}", base, n);
+        n += 1;
+    }
+    used.insert(unique.clone());
+    unique
+}
+
+/// Builds an Avro schema, giving nested records names unique within it.
+struct AvroWriter {
+    warnings: Vec<String>,
+    /// Names of the records and fixed types written so far.
+    type_names: HashSet<String>,
+}
+
+impl AvroWriter {
+    /// Start a schema whose top-level record is called `name`.
+    fn new(name: &str) -> Self {
+        let mut type_names = HashSet::new();
+        type_names.insert(avro_name(name));
+        Self {
+            warnings: Vec::new(),
+            type_names,
+        }
+    }
+
+    fn fallback(&mut self, column: &str, data_type: &DataType, to: Value) -> Value {
+        self.warnings.push(format!(
+            "{}: {} has no Avro equivalent, written as {}",
+            column, data_type, to
+        ));
+        to
+    }
+
+    fn logical(base: &str, logical: &str) -> Value {
+        json!({ "type": base, "logicalType": logical })
+    }
+
+    /// `column` is the dotted path of the field, used for record names and warnings.
+    fn avro_type(&mut self, column: &str, data_type: &DataType) -> Value {
+        match data_type {
+            DataType::Null => json!("null"),
+            DataType::Boolean => json!("boolean"),
+            DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::UInt8
+            | DataType::UInt16 => {
+                json!("int")
+            }
+            DataType::Int64 | DataType::UInt32 => json!("long"),
+            DataType::UInt64 => self.fallback(column, data_type, json!("long")),
+            DataType::Float16 | DataType::Float32 => json!("float"),
+            DataType::Float64 => json!("double"),
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => json!("string"),
+            DataType::Binary | DataType::LargeBinary | DataType::BinaryView => json!("bytes"),
+            DataType::FixedSizeBinary(size) => {
+                let name = unique_avro_name(column, &mut self.type_names);
+                json!({ "type": "fixed", "name": name, "size": size })
+            }
+            DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+                json!({
+                    "type": "bytes",
+                    "logicalType": "decimal",
+                    "precision": precision,
+                    "scale": scale,
+                })
+            }
+            DataType::Date32 | DataType::Date64 => Self::logical("int", "date"),
+            DataType::Time32(TimeUnit::Millisecond) => Self::logical("int", "time-millis"),
+            DataType::Time64(TimeUnit::Microsecond) => Self::logical("long", "time-micros"),
+            DataType::Timestamp(unit, tz) => {
+                let prefix = if tz.is_some() {
+                    "timestamp"
+                } else {
+                    "local-timestamp"
+                };
+                match unit {
+                    TimeUnit::Millisecond => Self::logical("long", &format!("{}-millis", prefix)),
+                    TimeUnit::Microsecond => Self::logical("long", &format!("{}-micros", prefix)),
+                    TimeUnit::Nanosecond => Self::logical("long", &format!("{}-nanos", prefix)),
+                    TimeUnit::Second => self.fallback(column, data_type, json!("long")),
+                }
+            }
+            DataType::Dictionary(_, value) => self.avro_type(column, value),
+            DataType::List(item)
+            | DataType::LargeList(item)
+            | DataType::ListView(item)
+            | DataType::LargeListView(item)
+            | DataType::FixedSizeList(item, _) => {
+                json!({ "type": "array", "items": self.avro_field_type(column, item) })
+            }
+            DataType::Struct(fields) => self.record(column, fields),
+            DataType::Map(entries, _) => match entries.data_type() {
+                DataType::Struct(kv) if kv.len() == 2 => {
+                    if !matches!(
+                        kv[0].data_type(),
+                        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+                    ) {
+                        self.warnings.push(format!(
+                            "{}: Avro map keys are strings, keys of type {} are written as strings",
+                            column,
+                            kv[0].data_type()
+                        ));
+                    }
+                    json!({ "type": "map", "values": self.avro_field_type(column, &kv[1]) })
+                }
+                _ => self.fallback(column, data_type, json!("string")),
+            },
+            _ => self.fallback(column, data_type, json!("string")),
+        }
+    }
+
+    /// The type of `field`, as a union with null when it's nullable.
+    fn avro_field_type(&mut self, column: &str, field: &Field) -> Value {
+        let ty = self.avro_type(column, field.data_type());
+        if field.is_nullable() && ty != json!("null") {
+            json!(["null", ty])
+        } else {
+            ty
+        }
+    }
+
+    /// The record for `fields` at path `name`; the top-level record has an
+    /// empty path and is named by the caller.
+    fn record(&mut self, name: &str, fields: &Fields) -> Value {
+        let mut field_names = HashSet::new();
+        let fields: Vec<Value> = fields
+            .iter()
+            .map(|f| {
+                let column = if name.is_empty() {
+                    f.name().to_string()
+                } else {
+                    format!("{}.{}", name, f.name())
+                };
+                let field_name = unique_avro_name(f.name(), &mut field_names);
+                let mut out = Map::new();
+                out.insert("name".into(), json!(field_name));
+                out.insert("type".into(), self.avro_field_type(&column, f));
+                if f.is_nullable() {
+                    out.insert("default".into(), Value::Null);
+                }
+                if field_name != *f.name() {
+                    out.insert("doc".into(), json!(format!("Original name: {}", f.name())));
+                }
+                Value::Object(out)
+            })
+            .collect();
+        if name.is_empty() {
+            return json!({ "type": "record", "fields": fields });
+        }
+        let name = unique_avro_name(name, &mut self.type_names);
+        json!({ "type": "record", "name": name, "fields": fields })
+    }
+}
+
+fn json_schema_type(column: &str, field: &Field, warnings: &mut Vec<String>) -> Value {
+    let mut ty = match field.data_type() {
+        DataType::Dictionary(_, value) => {
+            return json_schema_type(
+                column,
+                &field.clone().with_data_type(value.as_ref().clone()),
+                warnings,
+            );
+        }
+        DataType::Null => json!({ "type": "null" }),
+        DataType::Boolean => json!({ "type": "boolean" }),
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            json!({ "type": "integer" })
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            json!({ "type": "integer", "minimum": 0 })
+        }
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => json!({ "type": "number" }),
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => json!({ "type": "number" }),
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => json!({ "type": "string" }),
+        DataType::Binary
+        | DataType::LargeBinary
+        | DataType::BinaryView
+        | DataType::FixedSizeBinary(_) => {
+            json!({ "type": "string", "contentEncoding": "base64" })
+        }
+        DataType::Date32 | DataType::Date64 => json!({ "type": "string", "format": "date" }),
+        DataType::Time32(_) | DataType::Time64(_) => json!({ "type": "string", "format": "time" }),
+        DataType::Timestamp(_, _) => json!({ "type": "string", "format": "date-time" }),
+        DataType::List(item)
+        | DataType::LargeList(item)
+        | DataType::ListView(item)
+        | DataType::LargeListView(item)
+        | DataType::FixedSizeList(item, _) => {
+            json!({ "type": "array", "items": json_schema_type(column, item, warnings) })
+        }
+        DataType::Struct(fields) => json_schema_object(column, fields, warnings),
+        DataType::Map(entries, _) => match entries.data_type() {
+            DataType::Struct(kv) if kv.len() == 2 => json!({
+                "type": "object",
+                "additionalProperties": json_schema_type(column, &kv[1], warnings),
+            }),
+            _ => json!({}),
+        },
+        other => {
+            warnings.push(format!(
+                "{}: {} has no JSON Schema equivalent, left unconstrained",
+                column, other
+            ));
+            json!({})
+        }
+    };
+    if field.is_nullable() {
+        if let Some(Value::String(name)) = ty.get("type").cloned() {
+            if name != "null" {
+                ty["type"] = json!([name, "null"]);
+            }
+        }
+    }
+    ty
+}
+
+fn json_schema_object(column: &str, fields: &Fields, warnings: &mut Vec<String>) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for f in fields {
+        let path = if column.is_empty() {
+            f.name().to_string()
+        } else {
+            format!("{}.{}", column, f.name())
+        };
+        properties.insert(f.name().clone(), json_schema_type(&path, f, warnings));
+        if !f.is_nullable() {
+            required.push(json!(f.name()));
+        }
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// The file's schema as an Arrow schema JSON, an Avro schema (`.avsc`) or a
+/// JSON Schema describing one row, optionally written to `dest`.
+pub fn export_schema(
+    path: &str,
+    format: &str,
+    dest: Option<&str>,
+) -> Result<SchemaDocument, String> {
+    let format = SchemaFormat::parse(format)?;
+    let schema = arrow_schema(path)?;
+    let name = file_stem(path);
+
+    let mut warnings = Vec::new();
+    let document = match format {
+        SchemaFormat::Arrow => arrow_schema_json(&schema),
+        SchemaFormat::Avro => {
+            let mut writer = AvroWriter::new(&name);
+            let mut record = writer.record("", schema.fields());
+            record["name"] = json!(avro_name(&name));
+            warnings = writer.warnings;
+            record
+        }
+        SchemaFormat::JsonSchema => {
+            let mut object = json_schema_object("", schema.fields(), &mut warnings);
+            let mut out = Map::new();
+            out.insert(
+                "$schema".into(),
+                json!("https://json-schema.org/draft/2020-12/schema"),
+            );
+            out.insert("title".into(), json!(name));
+            if let Value::Object(fields) = object.take() {
+                out.extend(fields);
+            }
+            Value::Object(out)
+        }
+    };
+    let content = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+
+    if let Some(dest) = dest {
+        fs::write(dest, &content).map_err(|e| e.to_string())?;
+    }
+
+    Ok(SchemaDocument {
+        format: format.name().to_string(),
+        content,
+        path: dest.map(|d| d.to_string()),
+        warnings,
+    })
+}
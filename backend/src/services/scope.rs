@@ -0,0 +1,84 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Directories the backend may read and write below. An unrestricted scope
+/// (the default) allows every path.
+#[derive(Debug, Clone, Default)]
+pub struct PathScope {
+    /// Canonical allowed directories, or `None` when unrestricted.
+    roots: Option<Vec<PathBuf>>,
+}
+
+/// `path` with symlinks and `..` resolved. For a path that doesn't exist yet
+/// (e.g. an export destination) the nearest existing ancestor is resolved and
+/// the rest appended, so `allowed/../outside/new.csv` can't slip through.
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map_err(|e| e.to_string())?
+            .join(path)
+    };
+
+    let mut existing = absolute.as_path();
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            let mut resolved = resolved;
+            for component in missing.iter().rev() {
+                match component {
+                    Component::ParentDir => {
+                        resolved.pop();
+                    }
+                    Component::Normal(name) => resolved.push(name),
+                    _ => {}
+                }
+            }
+            return Ok(resolved);
+        }
+        let Some(parent) = existing.parent() else {
+            return Err(format!("Cannot resolve path: {}", path.display()));
+        };
+        if let Some(last) = existing.components().next_back() {
+            missing.push(last);
+        }
+        existing = parent;
+    }
+}
+
+impl PathScope {
+    /// A scope allowing only paths below `dirs`, or every path when `dirs` is
+    /// empty. Directories that don't exist are left out, without lifting the
+    /// restriction.
+    pub fn new(dirs: &[String]) -> Self {
+        if dirs.is_empty() {
+            return Self::default();
+        }
+        let roots = dirs
+            .iter()
+            .filter_map(|dir| Path::new(dir).canonicalize().ok())
+            .filter(|dir| dir.is_dir())
+            .collect();
+        Self { roots: Some(roots) }
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.roots.is_some()
+    }
+
+    /// Reject `path` unless it lies within an allowed directory.
+    pub fn check(&self, path: &str) -> Result<(), String> {
+        let Some(roots) = &self.roots else {
+            return Ok(());
+        };
+        let resolved = resolve(Path::new(path))?;
+        if roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Access denied: {} is outside the allowed directories",
+                path
+            ))
+        }
+    }
+}
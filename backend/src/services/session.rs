@@ -0,0 +1,606 @@
+use datafusion::catalog::schema::SchemaProvider;
+use datafusion::common::TableReference;
+use datafusion::datasource::listing::ListingTable;
+use datafusion::datasource::view::ViewTable;
+use datafusion::execution::context::SessionContext;
+use datafusion::prelude::{CsvReadOptions, ParquetReadOptions};
+use datafusion::sql::parser::{DFParser, Statement as DFStatement};
+use datafusion::sql::sqlparser::ast::{Expr, Statement, Value};
+use datafusion::sql::sqlparser::parser::ParserError;
+use datafusion::sql::sqlparser::tokenizer::Token;
+use std::fs;
+use std::sync::Arc;
+use url::Url;
+
+use crate::commands::query::check_read_only;
+use crate::models::{
+    CompletionColumn, CompletionInfo, CompletionTable, SessionSetting, SessionSpec, SessionTable,
+    SessionView,
+};
+use crate::services::parquet::ParquetCache;
+use crate::services::sql::quote_literal;
+
+/// Version of the session spec format written by `export_session`.
+const SESSION_SPEC_VERSION: u32 = 1;
+
+/// Name of the table every file session registers its file under.
+const PRIMARY_TABLE: &str = "t";
+
+/// Capture the tables, views, changed settings and recent queries of the
+/// session associated with `path`.
+pub async fn snapshot(
+    cache: &ParquetCache,
+    path: &str,
+    max_queries: usize,
+) -> Result<SessionSpec, String> {
+    let ctx = cache.session_for(path).await?;
+
+    let (tables, views) = collect_tables(&ctx).await?;
+    let settings = changed_settings(&ctx);
+    let queries = cache.recent_queries(path, max_queries);
+
+    Ok(SessionSpec {
+        version: SESSION_SPEC_VERSION,
+        tables,
+        views,
+        settings,
+        queries,
+    })
+}
+
+fn default_schema(ctx: &SessionContext) -> Result<Arc<dyn SchemaProvider>, String> {
+    let options = ctx.copied_config();
+    let catalog_options = &options.options().catalog;
+    ctx.catalog(&catalog_options.default_catalog)
+        .and_then(|catalog| catalog.schema(&catalog_options.default_schema))
+        .ok_or_else(|| "Default schema not found".to_string())
+}
+
+async fn collect_tables(
+    ctx: &SessionContext,
+) -> Result<(Vec<SessionTable>, Vec<SessionView>), String> {
+    let schema = default_schema(ctx)?;
+
+    let mut names = schema.table_names();
+    names.sort();
+
+    let mut tables = Vec::new();
+    let mut views = Vec::new();
+
+    for name in names {
+        let provider = match schema.table(&name).await.map_err(|e| e.to_string())? {
+            Some(provider) => provider,
+            None => continue,
+        };
+
+        if let Some(listing) = provider.as_any().downcast_ref::<ListingTable>() {
+            let path = listing
+                .table_paths()
+                .first()
+                .map(|url| table_url_to_path(url.as_ref()))
+                .unwrap_or_default();
+            let format = listing
+                .options()
+                .file_extension
+                .trim_start_matches('.')
+                .to_string();
+            tables.push(SessionTable { name, path, format });
+        } else if let Some(view) = provider.as_any().downcast_ref::<ViewTable>() {
+            if let Some(definition) = view.definition() {
+                views.push(SessionView {
+                    name,
+                    definition: definition.clone(),
+                });
+            }
+        }
+    }
+
+    // Keep the primary table first so replaying the spec registers it before anything else
+    tables.sort_by_key(|table| table.name != PRIMARY_TABLE);
+
+    Ok((tables, views))
+}
+
+fn table_url_to_path(url: &Url) -> String {
+    url.to_file_path()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// Settings whose value differs from a fresh session.
+fn changed_settings(ctx: &SessionContext) -> Vec<SessionSetting> {
+    let defaults = SessionContext::new().copied_config();
+    let defaults = defaults.options().entries();
+    let current = ctx.copied_config();
+
+    current
+        .options()
+        .entries()
+        .into_iter()
+        .filter(|entry| {
+            !defaults
+                .iter()
+                .any(|d| d.key == entry.key && d.value == entry.value)
+        })
+        .filter_map(|entry| {
+            entry.value.map(|value| SessionSetting {
+                key: entry.key,
+                value,
+            })
+        })
+        .collect()
+}
+
+/// Views created in the session of `session_id` (e.g. by `CREATE VIEW` through
+/// `execute_sql`) with their definitions.
+pub async fn list_views(
+    cache: &ParquetCache,
+    session_id: &str,
+) -> Result<Vec<SessionView>, String> {
+    let ctx = cache.session_for(session_id).await?;
+    let (_, views) = collect_tables(&ctx).await?;
+    Ok(views)
+}
+
+/// Aggregates still built into the planner rather than registered as UDAFs.
+const BUILTIN_AGGREGATE_FUNCTIONS: [&str; 3] = ["array_agg", "max", "min"];
+
+/// Window functions built into the planner rather than registered as UDWFs.
+const BUILTIN_WINDOW_FUNCTIONS: [&str; 11] = [
+    "cume_dist",
+    "dense_rank",
+    "first_value",
+    "lag",
+    "last_value",
+    "lead",
+    "nth_value",
+    "ntile",
+    "percent_rank",
+    "rank",
+    "row_number",
+];
+
+fn sorted_names<'a>(names: impl Iterator<Item = &'a String>, builtin: &[&str]) -> Vec<String> {
+    let mut names: Vec<String> = names
+        .map(|name| name.to_lowercase())
+        .chain(builtin.iter().map(|name| name.to_string()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Tables and views with their columns, plus the function names the session
+/// knows, for SQL editor autocomplete.
+pub async fn get_sql_completion_info(
+    cache: &ParquetCache,
+    session_id: &str,
+) -> Result<CompletionInfo, String> {
+    let ctx = cache.session_for(session_id).await?;
+    let schema = default_schema(&ctx)?;
+
+    let mut names = schema.table_names();
+    names.sort();
+
+    let mut tables = Vec::new();
+    for name in names {
+        let provider = match schema.table(&name).await.map_err(|e| e.to_string())? {
+            Some(provider) => provider,
+            None => continue,
+        };
+        let columns = provider
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| CompletionColumn {
+                name: field.name().clone(),
+                data_type: field.data_type().to_string(),
+            })
+            .collect();
+        tables.push(CompletionTable { name, columns });
+    }
+
+    let state = ctx.state();
+    Ok(CompletionInfo {
+        tables,
+        scalar_functions: sorted_names(state.scalar_functions().keys(), &[]),
+        aggregate_functions: sorted_names(
+            state.aggregate_functions().keys(),
+            &BUILTIN_AGGREGATE_FUNCTIONS,
+        ),
+        window_functions: sorted_names(state.window_functions().keys(), &BUILTIN_WINDOW_FUNCTIONS),
+    })
+}
+
+/// Register `path` as table `name`, replacing any table already using that name.
+async fn register_file(
+    ctx: &SessionContext,
+    name: &str,
+    path: &str,
+    format: &str,
+) -> Result<(), String> {
+    if ctx.table_exist(name).map_err(|e| e.to_string())? {
+        ctx.deregister_table(name).map_err(|e| e.to_string())?;
+    }
+    match format.to_lowercase().as_str() {
+        "parquet" => {
+            ctx.register_parquet(name, path, ParquetReadOptions::default())
+                .await
+        }
+        "csv" => ctx.register_csv(name, path, CsvReadOptions::new()).await,
+        other => return Err(format!("Unsupported table format: {}", other)),
+    }
+    .map_err(|e| format!("Failed to register table {}: {}", name, e))
+}
+
+/// Table format implied by a file's extension; anything unknown is read as Parquet.
+fn format_of(path: &str) -> &'static str {
+    match std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .as_deref()
+    {
+        Some("csv") => "csv",
+        _ => "parquet",
+    }
+}
+
+/// Reject `alias` if it names the primary table once DataFusion has
+/// normalized it, so neither `t` nor `T` can replace or drop it.
+fn check_alias(alias: &str) -> Result<(), String> {
+    if TableReference::parse_str(alias) == TableReference::bare(PRIMARY_TABLE) {
+        return Err(format!("Table name '{}' is reserved", PRIMARY_TABLE));
+    }
+    Ok(())
+}
+
+/// Make another Parquet or CSV file queryable as `alias` in the session of
+/// `session_id` (a workspace id, or the path of the file the session was opened
+/// for), so `execute_sql` can join it against the primary table.
+pub async fn register_table(
+    cache: &ParquetCache,
+    session_id: &str,
+    alias: &str,
+    path: &str,
+) -> Result<(), String> {
+    check_alias(alias)?;
+    let ctx = cache.session_for(session_id).await?;
+    register_file(&ctx, alias, path, format_of(path)).await
+}
+
+pub async fn unregister_table(
+    cache: &ParquetCache,
+    session_id: &str,
+    alias: &str,
+) -> Result<(), String> {
+    check_alias(alias)?;
+    let ctx = cache.session_for(session_id).await?;
+    ctx.deregister_table(alias)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Table not found: {}", alias))?;
+    Ok(())
+}
+
+/// Rename the table registered as `alias` in the session of `session_id` to
+/// `new_alias`, keeping its data source. Views that refer to the old name are
+/// not rewritten.
+pub async fn rename_table(
+    cache: &ParquetCache,
+    session_id: &str,
+    alias: &str,
+    new_alias: &str,
+) -> Result<(), String> {
+    check_alias(alias)?;
+    check_alias(new_alias)?;
+    let ctx = cache.session_for(session_id).await?;
+    if ctx.table_exist(new_alias).map_err(|e| e.to_string())? {
+        return Err(format!("Table already exists: {}", new_alias));
+    }
+    let provider = ctx
+        .deregister_table(alias)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Table not found: {}", alias))?;
+    if let Err(e) = ctx.register_table(new_alias, provider.clone()) {
+        // Put the table back rather than lose it
+        let _ = ctx.register_table(alias, provider);
+        return Err(format!("Failed to register table {}: {}", new_alias, e));
+    }
+    Ok(())
+}
+
+/// First line of a session script, followed by the spec version and `)`.
+const SCRIPT_HEADER: &str = "-- Parqsee session (spec version ";
+const TABLES_HEADER: &str = "-- Tables";
+const VIEWS_HEADER: &str = "-- Views";
+const QUERIES_HEADER: &str = "-- Queries";
+
+/// Render a spec as a SQL script that can be replayed statement by statement.
+pub fn to_sql_script(spec: &SessionSpec) -> String {
+    let mut script = String::new();
+
+    script.push_str(&format!("{}{})\n", SCRIPT_HEADER, spec.version));
+
+    for setting in &spec.settings {
+        script.push_str(&format!(
+            "SET {} = {};\n",
+            setting.key,
+            quote_literal(&setting.value)
+        ));
+    }
+
+    if !spec.tables.is_empty() {
+        script.push_str(&format!("\n{}\n", TABLES_HEADER));
+    }
+    for table in &spec.tables {
+        script.push_str(&format!(
+            "CREATE EXTERNAL TABLE \"{}\" STORED AS {} LOCATION {};\n",
+            table.name.replace('"', "\"\""),
+            table.format.to_uppercase(),
+            quote_literal(&table.path)
+        ));
+    }
+
+    if !spec.views.is_empty() {
+        script.push_str(&format!("\n{}\n", VIEWS_HEADER));
+    }
+    for view in &spec.views {
+        script.push_str(view.definition.trim_end_matches(';'));
+        script.push_str(";\n");
+    }
+
+    if !spec.queries.is_empty() {
+        script.push_str(&format!("\n{}\n", QUERIES_HEADER));
+    }
+    for query in &spec.queries {
+        script.push_str(query.trim().trim_end_matches(';'));
+        script.push_str(";\n");
+    }
+
+    script
+}
+
+/// Statements of `sql`, parsed one at a time: `DFParser::parse_sql` rejects
+/// whatever follows `CREATE EXTERNAL TABLE ...;`, as that statement's parser
+/// consumes the `;`.
+fn parse_script(sql: &str) -> Result<Vec<DFStatement>, String> {
+    let invalid = |e: ParserError| format!("Invalid session script: {}", e);
+    let mut parser = DFParser::new(sql).map_err(invalid)?;
+    let mut statements = Vec::new();
+    loop {
+        while parser.parser.consume_token(&Token::SemiColon) {}
+        if parser.parser.peek_token() == Token::EOF {
+            return Ok(statements);
+        }
+        statements.push(parser.parse_statement().map_err(invalid)?);
+    }
+}
+
+/// Read a spec back from a script written by `to_sql_script`. Its sections are
+/// told apart by their header comments, so a `CREATE VIEW` among the queries
+/// stays a query.
+pub fn from_sql_script(script: &str) -> Result<SessionSpec, String> {
+    let mut version = SESSION_SPEC_VERSION;
+    let mut sections: [String; 4] = Default::default();
+    let mut current = 0;
+    for line in script.lines() {
+        if let Some(rest) = line.strip_prefix(SCRIPT_HEADER) {
+            version = rest
+                .trim_end_matches(')')
+                .parse()
+                .map_err(|_| format!("Invalid session script header: {}", line))?;
+            continue;
+        }
+        match line.trim_end() {
+            TABLES_HEADER => current = 1,
+            VIEWS_HEADER => current = 2,
+            QUERIES_HEADER => current = 3,
+            _ => {
+                sections[current].push_str(line);
+                sections[current].push('\n');
+            }
+        }
+    }
+    let [settings, tables, views, queries] = sections.map(|section| parse_script(&section));
+
+    let mut spec = SessionSpec {
+        version,
+        tables: Vec::new(),
+        views: Vec::new(),
+        settings: Vec::new(),
+        queries: Vec::new(),
+    };
+    for statement in settings? {
+        match statement {
+            DFStatement::Statement(statement) => match *statement {
+                Statement::SetVariable {
+                    variables, value, ..
+                } if value.len() == 1 => spec.settings.push(SessionSetting {
+                    key: variables.to_string(),
+                    value: match &value[0] {
+                        Expr::Value(Value::SingleQuotedString(value)) => value.clone(),
+                        other => other.to_string(),
+                    },
+                }),
+                other => return Err(format!("Expected a SET statement, found: {}", other)),
+            },
+            other => return Err(format!("Expected a SET statement, found: {}", other)),
+        }
+    }
+    for statement in tables? {
+        match statement {
+            DFStatement::CreateExternalTable(table) => spec.tables.push(SessionTable {
+                name: TableReference::parse_str(&table.name).table().to_string(),
+                path: table.location,
+                format: table.file_type.to_lowercase(),
+            }),
+            other => {
+                return Err(format!(
+                    "Expected a CREATE EXTERNAL TABLE statement, found: {}",
+                    other
+                ))
+            }
+        }
+    }
+    for statement in views? {
+        let name = match &statement {
+            DFStatement::Statement(view) => match view.as_ref() {
+                Statement::CreateView { name, .. } => {
+                    name.0.last().map(|ident| ident.value.clone())
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match name {
+            Some(name) => spec.views.push(SessionView {
+                name,
+                definition: statement.to_string(),
+            }),
+            None => {
+                return Err(format!(
+                    "Expected a CREATE VIEW statement, found: {}",
+                    statement
+                ))
+            }
+        }
+    }
+    spec.queries = queries?.iter().map(|query| query.to_string()).collect();
+
+    Ok(spec)
+}
+
+pub async fn export_session(
+    cache: &ParquetCache,
+    path: &str,
+    export_path: &str,
+    format: &str,
+    max_queries: usize,
+) -> Result<String, String> {
+    let spec = snapshot(cache, path, max_queries).await?;
+
+    let contents = match format.to_lowercase().as_str() {
+        "sql" => to_sql_script(&spec),
+        "json" => serde_json::to_string_pretty(&spec).map_err(|e| e.to_string())?,
+        _ => return Err(format!("Unsupported session format: {}", format)),
+    };
+
+    fs::write(export_path, contents).map_err(|e| e.to_string())?;
+
+    Ok(format!(
+        "Successfully exported session with {} queries to {}",
+        spec.queries.len(),
+        export_path
+    ))
+}
+
+/// Rebuild a session from a file written by `export_session`, either a JSON
+/// spec or a SQL script. The primary table's path becomes the session key;
+/// other tables, views and settings are applied on top of it and the queries
+/// are restored into the history (not executed).
+pub async fn import_session(
+    cache: &ParquetCache,
+    import_path: &str,
+) -> Result<SessionSpec, String> {
+    let contents = fs::read_to_string(import_path).map_err(|e| e.to_string())?;
+    // A JSON spec is an object; anything else is read as a script
+    let spec: SessionSpec = if contents.trim_start().starts_with('{') {
+        serde_json::from_str(&contents).map_err(|e| format!("Invalid session spec: {}", e))?
+    } else {
+        from_sql_script(&contents)?
+    };
+
+    if spec.version > SESSION_SPEC_VERSION {
+        return Err(format!(
+            "Unsupported session spec version: {}",
+            spec.version
+        ));
+    }
+
+    let primary = primary_path(&spec)?;
+    let ctx = cache.get_or_create_session(primary).await?;
+    apply_spec(cache, &ctx, &spec).await?;
+
+    for query in &spec.queries {
+        cache.record_query(primary, query);
+    }
+
+    Ok(spec)
+}
+
+/// Path of the spec's primary table, which the session is opened for.
+pub(crate) fn primary_path(spec: &SessionSpec) -> Result<&str, String> {
+    spec.tables
+        .iter()
+        .find(|table| table.name == PRIMARY_TABLE)
+        .map(|table| table.path.as_str())
+        .ok_or_else(|| format!("Session spec has no table named '{}'", PRIMARY_TABLE))
+}
+
+/// Reject a view whose definition is anything but a single read-only
+/// `CREATE VIEW` of that view, such as a `CREATE EXTERNAL TABLE` slipped into
+/// a session file.
+fn check_view(view: &SessionView) -> Result<(), String> {
+    let statements = DFParser::parse_sql(&view.definition)
+        .map_err(|e| format!("Invalid definition of view {}: {}", view.name, e))?;
+    let created = match statements.front() {
+        Some(DFStatement::Statement(statement)) if statements.len() == 1 => {
+            match statement.as_ref() {
+                Statement::CreateView { name, .. } => {
+                    Some(TableReference::parse_str(&name.to_string()))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+    if created != Some(TableReference::parse_str(&view.name)) {
+        return Err(format!(
+            "Definition of view {} is not a CREATE VIEW of that view",
+            view.name
+        ));
+    }
+    check_read_only(&view.definition)
+}
+
+/// Apply the settings, extra tables and views of `spec` to a session already
+/// opened on its primary table. Nothing is applied unless every table of the
+/// spec lies within `cache`'s path scope and every view only creates itself.
+pub(crate) async fn apply_spec(
+    cache: &ParquetCache,
+    ctx: &SessionContext,
+    spec: &SessionSpec,
+) -> Result<(), String> {
+    for table in &spec.tables {
+        cache.check_path(&table.path)?;
+    }
+    for view in &spec.views {
+        check_view(view)?;
+    }
+
+    for setting in &spec.settings {
+        let statement = format!("SET {} = {}", setting.key, quote_literal(&setting.value));
+        ctx.sql(&statement)
+            .await
+            .map_err(|e| format!("Failed to apply setting {}: {}", setting.key, e))?;
+    }
+
+    for table in spec.tables.iter().filter(|t| t.name != PRIMARY_TABLE) {
+        register_file(ctx, &table.name, &table.path, &table.format).await?;
+    }
+
+    for view in &spec.views {
+        if ctx
+            .table_exist(view.name.as_str())
+            .map_err(|e| e.to_string())?
+        {
+            ctx.deregister_table(view.name.as_str())
+                .map_err(|e| e.to_string())?;
+        }
+        ctx.sql(&view.definition)
+            .await
+            .map_err(|e| format!("Failed to create view {}: {}", view.name, e))?;
+    }
+
+    Ok(())
+}
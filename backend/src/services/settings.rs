@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::models::{Settings, SettingsUpdate};
+use crate::services::parquet::{ParquetCache, DEFAULT_MAX_RESULT_ROWS};
+use crate::services::store;
+use crate::utils::{DisplayTimezone, TimestampFormat, DEFAULT_TIMESTAMP_FORMAT};
+
+const STORE_FILE: &str = "settings.json";
+
+/// Bounds of `Settings::default_page_size`, matching the settings dialog.
+const MIN_PAGE_SIZE: usize = 10;
+const MAX_PAGE_SIZE: usize = 500;
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            default_page_size: 50,
+            display_timezone: DisplayTimezone::default().name(),
+            timestamp_format: DEFAULT_TIMESTAMP_FORMAT.to_string(),
+            max_result_rows: DEFAULT_MAX_RESULT_ROWS,
+            theme: Default::default(),
+            telemetry_opt_in: false,
+            allowed_directories: Vec::new(),
+            blocking_threads: None,
+        }
+    }
+}
+
+fn store_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(STORE_FILE)
+}
+
+fn load(data_dir: &Path) -> Result<Settings, String> {
+    Ok(store::read_json(&store_path(data_dir), "settings file")?.unwrap_or_default())
+}
+
+fn store(data_dir: &Path, settings: &Settings) -> Result<(), String> {
+    store::atomic_write_json(&store_path(data_dir), settings)
+}
+
+fn validate(settings: &Settings) -> Result<(), String> {
+    if !(MIN_PAGE_SIZE..=MAX_PAGE_SIZE).contains(&settings.default_page_size) {
+        return Err(format!(
+            "Page size must be between {} and {}",
+            MIN_PAGE_SIZE, MAX_PAGE_SIZE
+        ));
+    }
+    if settings.max_result_rows == 0 {
+        return Err("Max result rows must be greater than 0".to_string());
+    }
+    DisplayTimezone::parse(&settings.display_timezone)?;
+    TimestampFormat::parse(&settings.timestamp_format)?;
+    if let Some(dir) = settings
+        .allowed_directories
+        .iter()
+        .find(|dir| !Path::new(dir).is_dir())
+    {
+        return Err(format!("Allowed directory not found: {}", dir));
+    }
+    Ok(())
+}
+
+/// The settings saved in `data_dir`, for those needed before the app starts.
+pub fn saved_settings(data_dir: &Path) -> Settings {
+    load(data_dir).unwrap_or_default()
+}
+
+/// Show timestamps in `cache` in the timezone and format `settings` name. A
+/// saved value this build can't use falls back to its default.
+pub fn apply_display_settings(cache: &ParquetCache, settings: &Settings) -> Result<(), String> {
+    let timezone = DisplayTimezone::parse(&settings.display_timezone).unwrap_or_else(|e| {
+        tracing::warn!("Showing timestamps in UTC: {}", e);
+        DisplayTimezone::default()
+    });
+    let timestamp_format = TimestampFormat::parse(&settings.timestamp_format).unwrap_or_else(|e| {
+        tracing::warn!("Showing timestamps in the default format: {}", e);
+        TimestampFormat::default()
+    });
+    let mut display = cache.display_options();
+    display.timezone = timezone;
+    display.timestamp_format = timestamp_format;
+    cache.set_display_options(display)
+}
+
+/// The app's settings, loaded once at startup and written through on update.
+#[derive(Default)]
+pub struct SettingsStore {
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the settings saved in `data_dir`. Unreadable settings are replaced
+    /// by the defaults rather than keeping the app from starting.
+    pub fn load(&self, data_dir: &Path) {
+        let settings = load(data_dir).unwrap_or_else(|e| {
+            tracing::warn!("Using default settings: {}", e);
+            Settings::default()
+        });
+        if let Ok(mut current) = self.settings.lock() {
+            *current = settings;
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Apply `update`, save the result to `data_dir` and return it. Nothing
+    /// changes if any value is invalid or the settings can't be saved.
+    pub fn update(&self, data_dir: &Path, update: SettingsUpdate) -> Result<Settings, String> {
+        let mut current = self.settings.lock().map_err(|e| e.to_string())?;
+        let mut settings = current.clone();
+        if let Some(page_size) = update.default_page_size {
+            settings.default_page_size = page_size;
+        }
+        if let Some(timezone) = update.display_timezone {
+            settings.display_timezone = timezone;
+        }
+        if let Some(format) = update.timestamp_format {
+            settings.timestamp_format = format;
+        }
+        if let Some(max_rows) = update.max_result_rows {
+            settings.max_result_rows = max_rows;
+        }
+        if let Some(theme) = update.theme {
+            settings.theme = theme;
+        }
+        if let Some(opt_in) = update.telemetry_opt_in {
+            settings.telemetry_opt_in = opt_in;
+        }
+        if let Some(dirs) = update.allowed_directories {
+            settings.allowed_directories = dirs;
+        }
+        if let Some(threads) = update.blocking_threads {
+            settings.blocking_threads = (threads > 0).then_some(threads);
+        }
+        validate(&settings)?;
+
+        store(data_dir, &settings)?;
+        *current = settings.clone();
+        Ok(settings)
+    }
+}
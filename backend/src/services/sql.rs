@@ -0,0 +1,621 @@
+use arrow::datatypes::{DataType, Field, Fields, SchemaRef};
+use datafusion::execution::context::SessionContext;
+use datafusion::sql::parser::DFParser;
+use datafusion::sql::sqlparser::dialect::GenericDialect;
+use datafusion::sql::sqlparser::keywords::Keyword;
+use datafusion::sql::sqlparser::tokenizer::{Token, Tokenizer};
+use serde_json::Value;
+
+use crate::models::{FilterExpr, FilterInput, FilterOperator, SortSpec, SqlIssue, SqlValidation};
+use crate::services::parquet::{parse_error_issue, ParquetCache};
+
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+pub(crate) fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Resolves the column names of filters and sorts against a table's schema,
+/// so a name that isn't spelled exactly as in the file still reaches the
+/// right column:
+///
+/// - an exact match wins, then a unique case-insensitive one
+/// - `a.b` refers to field `b` of struct column `a` when there is no column
+///   called `a.b`
+///
+/// Resolved names come back quoted, so spaces, dots, upper case and reserved
+/// words never reach the SQL parser bare.
+pub(crate) struct ColumnResolver {
+    schema: SchemaRef,
+}
+
+impl ColumnResolver {
+    pub(crate) fn new(schema: SchemaRef) -> Self {
+        Self { schema }
+    }
+
+    /// Resolver for the table `t` registered in `ctx`.
+    pub(crate) async fn for_session(ctx: &SessionContext) -> Result<Self, String> {
+        let table = ctx
+            .table("t")
+            .await
+            .map_err(|e| format!("Failed to load table: {}", e))?;
+        Ok(Self::new(table.schema().inner().clone()))
+    }
+
+    /// SQL expression for `name`.
+    pub(crate) fn resolve(&self, name: &str) -> Result<String, String> {
+        self.resolve_field(name).map(|(expr, _)| expr)
+    }
+
+    /// SQL expression for `name` and the field it refers to.
+    pub(crate) fn resolve_field(&self, name: &str) -> Result<(String, &Field), String> {
+        if let Some(field) = find_field(self.schema.fields(), name)? {
+            return Ok((quote_identifier(field.name()), field));
+        }
+
+        // Walk struct fields, trying the longest column name first so a
+        // column that itself contains dots is preferred
+        let parts: Vec<&str> = name.split('.').collect();
+        'split: for split in (1..parts.len()).rev() {
+            let Some(column) = find_field(self.schema.fields(), &parts[..split].join("."))? else {
+                continue;
+            };
+            let mut expr = quote_identifier(column.name());
+            let mut current: &Field = column;
+            for part in &parts[split..] {
+                let DataType::Struct(children) = current.data_type() else {
+                    continue 'split;
+                };
+                let Some(child) = find_field(children, part)? else {
+                    continue 'split;
+                };
+                expr = format!("get_field({}, {})", expr, quote_literal(child.name()));
+                current = child;
+            }
+            return Ok((expr, current));
+        }
+
+        Err(match self.suggest(name) {
+            Some(suggestion) => format!(
+                "Column '{}' not found, did you mean '{}'?",
+                name, suggestion
+            ),
+            None => format!("Column '{}' not found", name),
+        })
+    }
+
+    /// The column or struct field path spelled most like `name`, if any is
+    /// close enough to be a likely typo.
+    pub(crate) fn suggest(&self, name: &str) -> Option<String> {
+        let mut paths = Vec::new();
+        collect_paths(self.schema.fields(), "", &mut paths);
+        let target = name.to_lowercase();
+        let max_distance = target.chars().count().div_ceil(3);
+        paths
+            .into_iter()
+            .map(|path| (edit_distance(&path.to_lowercase(), &target), path))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, path)| path)
+    }
+}
+
+/// Every column and struct field as a dotted path.
+fn collect_paths(fields: &Fields, prefix: &str, out: &mut Vec<String>) {
+    for field in fields {
+        let path = format!("{}{}", prefix, field.name());
+        if let DataType::Struct(children) = field.data_type() {
+            collect_paths(children, &format!("{}.", path), out);
+        }
+        out.push(path);
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// The field called `name`, or the only one called so ignoring case.
+fn find_field<'a>(fields: &'a Fields, name: &str) -> Result<Option<&'a Field>, String> {
+    if let Some((_, field)) = fields.find(name) {
+        return Ok(Some(field));
+    }
+    let matches: Vec<&Field> = fields
+        .iter()
+        .filter(|f| f.name().eq_ignore_ascii_case(name))
+        .map(|f| f.as_ref())
+        .collect();
+    match matches[..] {
+        [] => Ok(None),
+        [field] => Ok(Some(field)),
+        _ => Err(format!(
+            "Column {} is ambiguous: {}",
+            name,
+            matches
+                .iter()
+                .map(|f| f.name().as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+pub(crate) fn compile_literal(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Null => Ok("NULL".to_string()),
+        Value::Bool(b) => Ok(if *b { "TRUE" } else { "FALSE" }.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::String(s) => Ok(quote_literal(s)),
+        Value::Array(_) | Value::Object(_) => Err(format!("Unsupported filter value: {}", value)),
+    }
+}
+
+/// Compile a filter tree to a SQL predicate. Columns are resolved against the
+/// schema and quoted, and values are rendered as escaped literals, never
+/// spliced in verbatim.
+fn compile_filter(filter: &FilterExpr, columns: &ColumnResolver) -> Result<String, String> {
+    match filter {
+        FilterExpr::Condition {
+            column,
+            operator,
+            value,
+        } => {
+            let col = columns.resolve(column)?;
+            let require_value = || {
+                value
+                    .as_ref()
+                    .ok_or_else(|| format!("Filter on {} requires a value", column))
+            };
+
+            let sql = match operator {
+                FilterOperator::IsNull => format!("{} IS NULL", col),
+                FilterOperator::IsNotNull => format!("{} IS NOT NULL", col),
+                FilterOperator::Like | FilterOperator::NotLike => {
+                    let pattern = match require_value()? {
+                        Value::String(s) => quote_literal(s),
+                        other => quote_literal(&other.to_string()),
+                    };
+                    let not = if matches!(operator, FilterOperator::NotLike) {
+                        "NOT "
+                    } else {
+                        ""
+                    };
+                    format!("CAST({} AS VARCHAR) {}LIKE {}", col, not, pattern)
+                }
+                FilterOperator::In | FilterOperator::NotIn => {
+                    let items = match require_value()? {
+                        Value::Array(items) if !items.is_empty() => items,
+                        _ => {
+                            return Err(format!(
+                                "Filter on {} requires a non-empty list of values",
+                                column
+                            ))
+                        }
+                    };
+                    let items = items
+                        .iter()
+                        .map(compile_literal)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let not = if matches!(operator, FilterOperator::NotIn) {
+                        "NOT "
+                    } else {
+                        ""
+                    };
+                    format!("{} {}IN ({})", col, not, items.join(", "))
+                }
+                FilterOperator::Eq
+                | FilterOperator::NotEq
+                | FilterOperator::Gt
+                | FilterOperator::GtEq
+                | FilterOperator::Lt
+                | FilterOperator::LtEq => {
+                    let symbol = match operator {
+                        FilterOperator::Eq => "=",
+                        FilterOperator::NotEq => "!=",
+                        FilterOperator::Gt => ">",
+                        FilterOperator::GtEq => ">=",
+                        FilterOperator::Lt => "<",
+                        _ => "<=",
+                    };
+                    format!("{} {} {}", col, symbol, compile_literal(require_value()?)?)
+                }
+            };
+            Ok(sql)
+        }
+        FilterExpr::And { filters } => compile_group(filters, "AND", "TRUE", columns),
+        FilterExpr::Or { filters } => compile_group(filters, "OR", "FALSE", columns),
+        FilterExpr::Not { filter } => Ok(format!("NOT ({})", compile_filter(filter, columns)?)),
+    }
+}
+
+fn compile_group(
+    filters: &[FilterExpr],
+    joiner: &str,
+    empty: &str,
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    if filters.is_empty() {
+        return Ok(empty.to_string());
+    }
+    let parts = filters
+        .iter()
+        .map(|f| compile_filter(f, columns).map(|sql| format!("({})", sql)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(parts.join(&format!(" {} ", joiner)))
+}
+
+pub(crate) fn build_where_clause(
+    filter: Option<&FilterExpr>,
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    match filter {
+        Some(f) => Ok(format!("WHERE {}", compile_filter(f, columns)?)),
+        None => Ok(String::new()),
+    }
+}
+
+pub(crate) fn build_order_by_clause(
+    sort: Option<Vec<SortSpec>>,
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    let keys = sort
+        .unwrap_or_default()
+        .iter()
+        .map(|spec| {
+            let mut key = format!(
+                "{} {}",
+                columns.resolve(&spec.column)?,
+                if spec.descending { "DESC" } else { "ASC" }
+            );
+            match spec.nulls_first {
+                Some(true) => key.push_str(" NULLS FIRST"),
+                Some(false) => key.push_str(" NULLS LAST"),
+                None => {}
+            }
+            Ok(key)
+        })
+        .collect::<Result<Vec<String>, String>>()?;
+
+    if keys.is_empty() {
+        Ok(String::new())
+    } else {
+        Ok(format!("ORDER BY {}", keys.join(", ")))
+    }
+}
+
+/// WHERE clause combining the user filter with an extra predicate.
+pub(crate) fn build_where_clause_with(
+    filter: Option<&FilterExpr>,
+    predicate: &str,
+    columns: &ColumnResolver,
+) -> Result<String, String> {
+    match filter {
+        Some(f) => Ok(format!(
+            "WHERE ({}) AND {}",
+            compile_filter(f, columns)?,
+            predicate
+        )),
+        None => Ok(format!("WHERE {}", predicate)),
+    }
+}
+
+fn issue(message: String, token: Option<&str>) -> SqlIssue {
+    SqlIssue {
+        message,
+        line: None,
+        column: None,
+        token: token.map(|t| t.to_string()),
+    }
+}
+
+/// Short name of a type for messages; nested types print every child in full.
+fn type_label(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Struct(_) => "Struct".to_string(),
+        DataType::Map(_, _) => "Map".to_string(),
+        DataType::List(_) | DataType::LargeList(_) | DataType::FixedSizeList(_, _) => {
+            "List".to_string()
+        }
+        other => other.to_string(),
+    }
+}
+
+fn is_date(s: &str) -> bool {
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()
+}
+
+fn is_timestamp(s: &str) -> bool {
+    is_date(s)
+        || chrono::DateTime::parse_from_rfc3339(s).is_ok()
+        || [
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y-%m-%dT%H:%M:%S%.f",
+            "%Y-%m-%d %H:%M",
+        ]
+        .iter()
+        .any(|format| chrono::NaiveDateTime::parse_from_str(s, format).is_ok())
+}
+
+/// Why `value` can't be compared with `column` of type `data_type`, if it can't.
+fn value_problem(column: &str, data_type: &DataType, value: &Value) -> Option<String> {
+    let data_type = match data_type {
+        DataType::Dictionary(_, value) => value.as_ref(),
+        other => other,
+    };
+    let expected = match data_type {
+        t if t.is_numeric() => match value {
+            Value::Number(_) => return None,
+            Value::String(s) if s.trim().parse::<f64>().is_ok() => return None,
+            _ => "a number",
+        },
+        DataType::Boolean => match value {
+            Value::Bool(_) => return None,
+            Value::String(s) if matches!(s.to_lowercase().as_str(), "true" | "false") => {
+                return None
+            }
+            _ => "true or false",
+        },
+        DataType::Date32 | DataType::Date64 => match value {
+            Value::String(s) if is_date(s.trim()) => return None,
+            _ => "a date (YYYY-MM-DD)",
+        },
+        DataType::Timestamp(_, _) => match value {
+            Value::String(s) if is_timestamp(s.trim()) => return None,
+            _ => "a timestamp (YYYY-MM-DD HH:MM:SS)",
+        },
+        DataType::Struct(_)
+        | DataType::Map(_, _)
+        | DataType::List(_)
+        | DataType::LargeList(_)
+        | DataType::FixedSizeList(_, _) => {
+            return Some(format!(
+                "Column '{}' is a {} and can't be compared with a value",
+                column,
+                type_label(data_type)
+            ))
+        }
+        _ => return None,
+    };
+    Some(format!(
+        "Column '{}' is {}, but {} is not {}",
+        column,
+        type_label(data_type),
+        value,
+        expected
+    ))
+}
+
+fn check_condition(
+    column: &str,
+    data_type: &DataType,
+    operator: &FilterOperator,
+    value: Option<&Value>,
+    issues: &mut Vec<SqlIssue>,
+) {
+    let mut problem = |message: String| issues.push(issue(message, Some(column)));
+    match (operator, value) {
+        (FilterOperator::IsNull | FilterOperator::IsNotNull, _) => {}
+        (_, None) => problem(format!("Filter on '{}' requires a value", column)),
+        (FilterOperator::Like | FilterOperator::NotLike, Some(Value::String(_))) => {}
+        (FilterOperator::Like | FilterOperator::NotLike, Some(other)) => problem(format!(
+            "LIKE on '{}' needs a text pattern, not {}",
+            column, other
+        )),
+        (FilterOperator::In | FilterOperator::NotIn, Some(Value::Array(items)))
+            if !items.is_empty() =>
+        {
+            for item in items {
+                if let Some(message) = value_problem(column, data_type, item) {
+                    problem(message);
+                }
+            }
+        }
+        (FilterOperator::In | FilterOperator::NotIn, Some(_)) => problem(format!(
+            "Filter on '{}' requires a non-empty list of values",
+            column
+        )),
+        (_, Some(Value::Null)) => problem(format!(
+            "Comparing '{}' with null never matches; use is_null instead",
+            column
+        )),
+        (_, Some(Value::Array(_) | Value::Object(_))) => problem(format!(
+            "Filter on '{}' needs a single value, not {}",
+            column,
+            value.unwrap_or(&Value::Null)
+        )),
+        (_, Some(value)) => {
+            if let Some(message) = value_problem(column, data_type, value) {
+                problem(message);
+            }
+        }
+    }
+}
+
+fn check_filter(filter: &FilterExpr, columns: &ColumnResolver, issues: &mut Vec<SqlIssue>) {
+    match filter {
+        FilterExpr::Condition {
+            column,
+            operator,
+            value,
+        } => match columns.resolve_field(column) {
+            Ok((_, field)) => {
+                check_condition(column, field.data_type(), operator, value.as_ref(), issues)
+            }
+            Err(message) => issues.push(issue(message, Some(column))),
+        },
+        FilterExpr::And { filters } | FilterExpr::Or { filters } => {
+            for filter in filters {
+                check_filter(filter, columns, issues);
+            }
+        }
+        FilterExpr::Not { filter } => check_filter(filter, columns, issues),
+    }
+}
+
+/// Unknown or misspelled column names in the text of a condition, found from
+/// its tokens so each issue points at the name. Unquoted names are folded to
+/// lower case by the query engine, so they must match a lower-case column.
+fn check_condition_text(text: &str, columns: &ColumnResolver) -> Result<Vec<SqlIssue>, String> {
+    let dialect = GenericDialect {};
+    let tokens = Tokenizer::new(&dialect, text)
+        .tokenize_with_location()
+        .map_err(|e| e.to_string())?;
+    let significant: Vec<_> = tokens
+        .iter()
+        .filter(|t| !matches!(t.token, Token::Whitespace(_)))
+        .collect();
+
+    let fields = columns.schema.fields();
+    let mut issues = Vec::new();
+    for (i, token) in significant.iter().enumerate() {
+        let Token::Word(word) = &token.token else {
+            continue;
+        };
+        let next = significant.get(i + 1).map(|t| &t.token);
+        let previous = i.checked_sub(1).map(|i| &significant[i].token);
+        // Function names, and qualifiers or members around a dot
+        if matches!(next, Some(Token::LParen | Token::Period))
+            || matches!(previous, Some(Token::Period))
+        {
+            continue;
+        }
+
+        let name = match word.quote_style {
+            Some(_) => word.value.clone(),
+            None => word.value.to_lowercase(),
+        };
+        if let Some((_, field)) = fields.find(&name) {
+            // A plain `column <op> literal` comparison
+            let literal = match (next, significant.get(i + 2).map(|t| &t.token)) {
+                (
+                    Some(
+                        Token::Eq | Token::Neq | Token::Lt | Token::LtEq | Token::Gt | Token::GtEq,
+                    ),
+                    Some(literal),
+                ) => match literal {
+                    Token::SingleQuotedString(s) => Some(Value::String(s.clone())),
+                    Token::Number(n, _) => n.parse::<serde_json::Number>().ok().map(Value::Number),
+                    _ => None,
+                },
+                _ => None,
+            };
+            if let Some(message) =
+                literal.and_then(|v| value_problem(field.name(), field.data_type(), &v))
+            {
+                issues.push(SqlIssue {
+                    message,
+                    line: Some(token.location.line),
+                    column: Some(token.location.column),
+                    token: Some(word.value.clone()),
+                });
+            }
+            continue;
+        }
+        let differs_in_case = fields.iter().find(|f| f.name().eq_ignore_ascii_case(&name));
+        let message = match (word.quote_style, differs_in_case) {
+            (None, Some(field)) => format!(
+                "Column '{}' has upper-case letters and must be quoted: {}",
+                field.name(),
+                quote_identifier(field.name())
+            ),
+            (Some(_), Some(field)) => format!(
+                "Column '{}' not found, did you mean '{}'? Quoted names are case-sensitive",
+                word.value,
+                field.name()
+            ),
+            // Keywords such as AND, NULL or TRUE
+            (None, None) if word.keyword != Keyword::NoKeyword => continue,
+            _ => match columns.suggest(&word.value) {
+                Some(suggestion) => format!(
+                    "Column '{}' not found, did you mean '{}'?",
+                    word.value, suggestion
+                ),
+                None => format!("Column '{}' not found", word.value),
+            },
+        };
+        issues.push(SqlIssue {
+            message,
+            line: Some(token.location.line),
+            column: Some(token.location.column),
+            token: Some(word.value.clone()),
+        });
+    }
+    Ok(issues)
+}
+
+/// Check a filter against the file's schema without running it: that every
+/// column exists and every value can be compared with its column, with a
+/// suggestion for misspelled names. What passes those checks is planned by
+/// the query engine to catch anything else.
+pub async fn validate_filter(
+    cache: &ParquetCache,
+    path: &str,
+    filter: &FilterInput,
+) -> Result<SqlValidation, String> {
+    let ctx = cache.get_or_create_session(path).await?;
+    let columns = ColumnResolver::for_session(&ctx).await?;
+
+    let prefix = "SELECT * FROM t WHERE ";
+    let (condition, mut issues) = match filter {
+        FilterInput::Expr(expr) => {
+            let mut issues = Vec::new();
+            check_filter(expr, &columns, &mut issues);
+            let condition = compile_filter(expr, &columns);
+            (condition.unwrap_or_default(), issues)
+        }
+        FilterInput::Text(text) => {
+            if text.trim().is_empty() {
+                return Err("Filter must not be empty".to_string());
+            }
+            (text.clone(), check_condition_text(text, &columns)?)
+        }
+    };
+
+    if issues.is_empty() {
+        let query = format!("{}{}", prefix, condition);
+        match DFParser::parse_sql(&query) {
+            Err(e) => {
+                let mut parse_issue = parse_error_issue(&query, e.to_string());
+                // Report positions within the condition rather than the query
+                if parse_issue.line == Some(1) {
+                    parse_issue.column = parse_issue
+                        .column
+                        .map(|c| c.saturating_sub(prefix.len() as u64).max(1));
+                }
+                issues.push(parse_issue);
+            }
+            Ok(statements) => {
+                let state = ctx.state();
+                for statement in statements {
+                    if let Err(e) = state.statement_to_plan(statement).await {
+                        issues.push(issue(e.to_string(), None));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(SqlValidation {
+        valid: issues.is_empty(),
+        issues,
+    })
+}
@@ -0,0 +1,59 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Held across every load, modify and store of the JSON stores in the app
+/// data directory, so two commands changing a store at once can't lose one
+/// another's update.
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Numbers temp files, so concurrent writes of one store never share one.
+static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+
+/// Run `update`, which loads a store, changes it and stores it again, while no
+/// other update of any store runs. `update` must not call `locked` itself.
+pub fn locked<T>(update: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    update()
+}
+
+/// The JSON value stored at `path`, or `None` when nothing was stored yet.
+/// `what` names the store in the error for a file that doesn't parse.
+pub fn read_json<T: DeserializeOwned>(path: &Path, what: &str) -> Result<Option<T>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| format!("Invalid {}: {}", what, e))
+}
+
+/// Replace `path` with `value` as pretty-printed JSON. The value goes to a temp
+/// file first, renamed over `path` once complete, so a crash never leaves a
+/// half-written store; the temp file is removed if that fails.
+pub fn atomic_write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let contents = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let tmp = path.with_file_name(format!(
+        "{}.{}-{}.tmp",
+        file_name,
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = fs::write(&tmp, contents).and_then(|_| fs::rename(&tmp, path));
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.to_string());
+    }
+    Ok(())
+}
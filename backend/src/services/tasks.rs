@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+
+use crate::models::TaskInfo;
+
+pub const TASK_STARTED: &str = "task-started";
+pub const TASK_PROGRESS: &str = "task-progress";
+pub const TASK_FINISHED: &str = "task-finished";
+
+/// Finished tasks kept around so the UI can still show their outcome.
+const MAX_FINISHED_TASKS: usize = 50;
+
+/// Called with an event name and the task's current state on every lifecycle change.
+pub type TaskListener = Arc<dyn Fn(&str, &TaskInfo) + Send + Sync>;
+
+struct Task {
+    info: Mutex<TaskInfo>,
+    cancelled: AtomicBool,
+    cancel_notify: Notify,
+    listener: Option<TaskListener>,
+}
+
+impl Task {
+    fn snapshot(&self) -> Option<TaskInfo> {
+        self.info.lock().ok().map(|info| info.clone())
+    }
+
+    fn update(&self, event: &str, f: impl FnOnce(&mut TaskInfo)) {
+        let info = match self.info.lock() {
+            Ok(mut info) => {
+                f(&mut info);
+                info.clone()
+            }
+            Err(_) => return,
+        };
+        if let Some(listener) = &self.listener {
+            listener(event, &info);
+        }
+    }
+}
+
+/// Handle given to running work for reporting progress and observing cancellation.
+#[derive(Clone)]
+pub struct TaskHandle {
+    task: Arc<Task>,
+}
+
+impl TaskHandle {
+    pub fn id(&self) -> String {
+        self.task.snapshot().map(|info| info.id).unwrap_or_default()
+    }
+
+    /// Report progress as a fraction in 0.0..=1.0. Updates that don't move the
+    /// whole percentage are dropped so tight loops don't flood the UI.
+    pub fn set_progress(&self, fraction: f64, message: Option<String>) {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let changed = self.task.snapshot().is_some_and(|info| {
+            let percent = |p: Option<f64>| p.map(|p| (p * 100.0) as u32);
+            percent(info.progress) != percent(Some(fraction)) || info.message != message
+        });
+        if changed {
+            self.task.update(TASK_PROGRESS, |info| {
+                info.progress = Some(fraction);
+                info.message = message;
+            });
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.task.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// For blocking loops: bail out with an error once the task is cancelled.
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err("Task cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct TaskManager {
+    tasks: Mutex<HashMap<String, Arc<Task>>>,
+    next_id: AtomicU64,
+    listener: Mutex<Option<TaskListener>>,
+}
+
+impl Default for TaskManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self {
+            tasks: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            listener: Mutex::new(None),
+        }
+    }
+
+    /// Install the callback that forwards lifecycle events, typically to the frontend.
+    pub fn set_listener(&self, listener: impl Fn(&str, &TaskInfo) + Send + Sync + 'static) {
+        if let Ok(mut current) = self.listener.lock() {
+            *current = Some(Arc::new(listener));
+        }
+    }
+
+    fn start(&self, kind: &str, label: &str) -> Result<TaskHandle, String> {
+        let id = format!("task-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let listener = self.listener.lock().map_err(|e| e.to_string())?.clone();
+        let task = Arc::new(Task {
+            info: Mutex::new(TaskInfo {
+                id: id.clone(),
+                kind: kind.to_string(),
+                label: label.to_string(),
+                state: "running".to_string(),
+                progress: None,
+                message: None,
+                error: None,
+                started_at: chrono::Utc::now().to_rfc3339(),
+                finished_at: None,
+            }),
+            cancelled: AtomicBool::new(false),
+            cancel_notify: Notify::new(),
+            listener,
+        });
+
+        {
+            let mut tasks = self.tasks.lock().map_err(|e| e.to_string())?;
+            tasks.insert(id, task.clone());
+        }
+        task.update(TASK_STARTED, |_| {});
+        self.prune_finished();
+        Ok(TaskHandle { task })
+    }
+
+    /// Run `work` as a tracked task. Cancelling the task drops the future at its
+    /// next await point; blocking work should poll `TaskHandle::check_cancelled`.
+    pub async fn run<T, F, Fut>(&self, kind: &str, label: &str, work: F) -> Result<T, String>
+    where
+        F: FnOnce(TaskHandle) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let handle = self.start(kind, label)?;
+        let task = handle.task.clone();
+
+        let result = tokio::select! {
+            result = work(handle) => result,
+            _ = task.cancel_notify.notified() => Err("Task cancelled".to_string()),
+        };
+
+        let cancelled = task.cancelled.load(Ordering::Relaxed);
+        task.update(TASK_FINISHED, |info| {
+            info.finished_at = Some(chrono::Utc::now().to_rfc3339());
+            match &result {
+                _ if cancelled => info.state = "cancelled".to_string(),
+                Ok(_) => {
+                    info.state = "completed".to_string();
+                    info.progress = Some(1.0);
+                }
+                Err(e) => {
+                    info.state = "failed".to_string();
+                    info.error = Some(e.clone());
+                }
+            }
+        });
+        result
+    }
+
+    /// All known tasks, oldest first.
+    pub fn list(&self) -> Result<Vec<TaskInfo>, String> {
+        let tasks = self.tasks.lock().map_err(|e| e.to_string())?;
+        let mut infos: Vec<TaskInfo> = tasks.values().filter_map(|t| t.snapshot()).collect();
+        infos.sort_by_key(|info| task_number(&info.id));
+        Ok(infos)
+    }
+
+    pub fn cancel(&self, id: &str) -> Result<(), String> {
+        let task = {
+            let tasks = self.tasks.lock().map_err(|e| e.to_string())?;
+            tasks
+                .get(id)
+                .cloned()
+                .ok_or_else(|| format!("Task not found: {}", id))?
+        };
+        if task.snapshot().is_some_and(|info| info.state != "running") {
+            return Err(format!("Task is not running: {}", id));
+        }
+        task.cancelled.store(true, Ordering::Relaxed);
+        // notify_one stores a permit, so this works even before `run` starts waiting
+        task.cancel_notify.notify_one();
+        Ok(())
+    }
+
+    fn prune_finished(&self) {
+        let Ok(mut tasks) = self.tasks.lock() else {
+            return;
+        };
+        let mut finished: Vec<(u64, String)> = tasks
+            .values()
+            .filter_map(|t| t.snapshot())
+            .filter(|info| info.state != "running")
+            .map(|info| (task_number(&info.id), info.id))
+            .collect();
+        if finished.len() <= MAX_FINISHED_TASKS {
+            return;
+        }
+        finished.sort();
+        let excess = finished.len() - MAX_FINISHED_TASKS;
+        for (_, id) in finished.into_iter().take(excess) {
+            tasks.remove(&id);
+        }
+    }
+}
+
+fn task_number(id: &str) -> u64 {
+    id.trim_start_matches("task-").parse().unwrap_or(0)
+}
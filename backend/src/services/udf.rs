@@ -0,0 +1,209 @@
+use arrow::array::{
+    ArrayRef, AsArray, BinaryArray, BooleanArray, StringArray, TimestampNanosecondArray,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Float64Type, Int64Type, TimeUnit};
+use datafusion::common::{DataFusionError, Result, ScalarValue};
+use datafusion::execution::context::SessionContext;
+use datafusion::logical_expr::{ColumnarValue, ScalarUDF, ScalarUDFImpl, Signature, Volatility};
+use std::any::Any;
+
+use crate::utils::wkb;
+use std::sync::Arc;
+
+type Invoke = fn(&[ColumnarValue]) -> Result<ColumnarValue>;
+
+/// Scalar function backed by a plain function pointer. Arguments are accepted
+/// as any type and cast to what the function needs inside `invoke`.
+#[derive(Debug)]
+struct SimpleUdf {
+    name: &'static str,
+    signature: Signature,
+    return_type: DataType,
+    invoke: Invoke,
+}
+
+impl SimpleUdf {
+    fn udf(name: &'static str, arity: usize, return_type: DataType, invoke: Invoke) -> ScalarUDF {
+        ScalarUDF::new_from_impl(SimpleUdf {
+            name,
+            signature: Signature::any(arity, Volatility::Immutable),
+            return_type,
+            invoke,
+        })
+    }
+}
+
+impl ScalarUDFImpl for SimpleUdf {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    fn return_type(&self, _arg_types: &[DataType]) -> Result<DataType> {
+        Ok(self.return_type.clone())
+    }
+
+    fn invoke(&self, args: &[ColumnarValue]) -> Result<ColumnarValue> {
+        (self.invoke)(args)
+    }
+}
+
+/// Register the inspection helpers (`try_parse_json`, `uuid_from_bytes`,
+/// `epoch_to_ts`, `hex`, `isfinite`) and WKB geometry helpers (`st_astext`,
+/// `st_envelope`) on a session.
+pub fn register_udfs(ctx: &SessionContext) {
+    ctx.register_udf(SimpleUdf::udf(
+        "try_parse_json",
+        1,
+        DataType::Utf8,
+        try_parse_json,
+    ));
+    ctx.register_udf(SimpleUdf::udf(
+        "uuid_from_bytes",
+        1,
+        DataType::Utf8,
+        uuid_from_bytes,
+    ));
+    ctx.register_udf(SimpleUdf::udf(
+        "epoch_to_ts",
+        2,
+        DataType::Timestamp(TimeUnit::Nanosecond, None),
+        epoch_to_ts,
+    ));
+    ctx.register_udf(SimpleUdf::udf("hex", 1, DataType::Utf8, hex));
+    ctx.register_udf(SimpleUdf::udf("isfinite", 1, DataType::Boolean, isfinite));
+    ctx.register_udf(SimpleUdf::udf("st_astext", 1, DataType::Utf8, st_astext));
+    ctx.register_udf(SimpleUdf::udf(
+        "st_envelope",
+        1,
+        DataType::Binary,
+        st_envelope,
+    ));
+}
+
+fn arg_as(args: &[ColumnarValue], index: usize, data_type: &DataType) -> Result<ArrayRef> {
+    let arrays = ColumnarValue::values_to_arrays(args)?;
+    Ok(cast(&arrays[index], data_type)?)
+}
+
+fn binary_arg(args: &[ColumnarValue]) -> Result<BinaryArray> {
+    let array = arg_as(args, 0, &DataType::Binary)?;
+    Ok(array.as_binary::<i32>().clone())
+}
+
+/// Normalized JSON text, or NULL when the string is not valid JSON.
+fn try_parse_json(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let array = arg_as(args, 0, &DataType::Utf8)?;
+    let strings = array.as_string::<i32>();
+
+    let result: StringArray = strings
+        .iter()
+        .map(|s| {
+            s.and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok())
+                .map(|v| v.to_string())
+        })
+        .collect();
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+/// Canonical 8-4-4-4-12 form of a 16-byte UUID; NULL for other lengths.
+fn uuid_from_bytes(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let bytes = binary_arg(args)?;
+
+    let result: StringArray = bytes
+        .iter()
+        .map(|b| {
+            b.filter(|b| b.len() == 16).map(|b| {
+                let hex = bytes_to_hex(b);
+                format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[0..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..32]
+                )
+            })
+        })
+        .collect();
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+fn hex(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let bytes = binary_arg(args)?;
+    let result: StringArray = bytes.iter().map(|b| b.map(bytes_to_hex)).collect();
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+/// Whether a number is neither NaN nor ±Infinity; NULL for NULL.
+fn isfinite(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let array = arg_as(args, 0, &DataType::Float64)?;
+    let result: BooleanArray = array
+        .as_primitive::<Float64Type>()
+        .iter()
+        .map(|v| v.map(f64::is_finite))
+        .collect();
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Interpret an integer as an epoch offset in `unit` ("s", "ms", "us" or "ns").
+fn epoch_to_ts(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let unit = match &args[1] {
+        ColumnarValue::Scalar(ScalarValue::Utf8(Some(unit))) => unit.to_lowercase(),
+        _ => {
+            return Err(DataFusionError::Execution(
+                "epoch_to_ts unit must be a string literal".to_string(),
+            ))
+        }
+    };
+    let factor: i64 = match unit.as_str() {
+        "s" => 1_000_000_000,
+        "ms" => 1_000_000,
+        "us" => 1_000,
+        "ns" => 1,
+        other => {
+            return Err(DataFusionError::Execution(format!(
+                "Unsupported epoch unit: {}",
+                other
+            )))
+        }
+    };
+
+    let array = arg_as(args, 0, &DataType::Int64)?;
+    let values = array.as_primitive::<Int64Type>();
+
+    let result: TimestampNanosecondArray = values
+        .iter()
+        .map(|v| v.and_then(|v| v.checked_mul(factor)))
+        .collect();
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+/// WKT of a WKB geometry; NULL when the bytes are not valid WKB.
+fn st_astext(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let bytes = binary_arg(args)?;
+    let result: StringArray = bytes.iter().map(|b| b.and_then(wkb::wkb_to_wkt)).collect();
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
+
+/// Bounding box of a WKB geometry as a WKB polygon.
+fn st_envelope(args: &[ColumnarValue]) -> Result<ColumnarValue> {
+    let bytes = binary_arg(args)?;
+    let result: BinaryArray = bytes
+        .iter()
+        .map(|b| b.and_then(wkb::wkb_envelope))
+        .collect();
+    Ok(ColumnarValue::Array(Arc::new(result)))
+}
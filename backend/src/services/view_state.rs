@@ -0,0 +1,52 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::ViewState;
+use crate::services::store;
+
+const VIEW_STATE_DIR: &str = "view_state";
+
+/// 64-bit FNV-1a of `path`. Unlike `DefaultHasher` its output never changes
+/// between builds, so it can name files that outlive the process.
+fn path_hash(path: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in path.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn state_path(data_dir: &Path, path: &str) -> PathBuf {
+    data_dir
+        .join(VIEW_STATE_DIR)
+        .join(format!("{}.json", path_hash(path)))
+}
+
+/// The view state saved for `path`, if any.
+pub fn get_view_state(data_dir: &Path, path: &str) -> Result<Option<ViewState>, String> {
+    let state: Option<ViewState> = store::read_json(&state_path(data_dir, path), "view state")?;
+    // A hash collision leaves another file's state behind; treat it as unsaved
+    Ok(state.filter(|state| state.path == path))
+}
+
+/// Save `state` for its file, replacing what was saved before.
+pub fn save_view_state(data_dir: &Path, mut state: ViewState) -> Result<ViewState, String> {
+    if state.path.is_empty() {
+        return Err("View state must name a file".to_string());
+    }
+    state.updated_at = chrono::Utc::now().to_rfc3339();
+
+    store::locked(|| store::atomic_write_json(&state_path(data_dir, &state.path), &state))?;
+    Ok(state)
+}
+
+/// Forget the view state of `path`. Does nothing if none was saved.
+pub fn clear_view_state(data_dir: &Path, path: &str) -> Result<(), String> {
+    store::locked(|| {
+        if get_view_state(data_dir, path)?.is_none() {
+            return Ok(());
+        }
+        fs::remove_file(state_path(data_dir, path)).map_err(|e| e.to_string())
+    })
+}
@@ -0,0 +1,401 @@
+use notify::event::{EventKind, ModifyKind};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::models::{DirectoryChangedEvent, FileChangedEvent};
+
+/// Called whenever a watched file's size or mtime differs from what was last seen.
+pub type FileChangeListener = Arc<dyn Fn(&FileChangedEvent) + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct FileStamp {
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        Some(Self {
+            size: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+}
+
+struct WatchedFile {
+    /// Path as the frontend opened it, which is also the cache key.
+    path: String,
+    stamp: Option<FileStamp>,
+}
+
+#[derive(Default)]
+struct WatchState {
+    /// Keyed by canonical path so events match however the file was opened.
+    files: HashMap<PathBuf, WatchedFile>,
+    /// Number of watched files per directory. Directories are watched instead of
+    /// the files themselves so atomic rewrites (write temp, rename over) are seen.
+    dirs: HashMap<PathBuf, usize>,
+}
+
+/// Watches opened files and reports when they change on disk.
+pub struct FileWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    state: Arc<Mutex<WatchState>>,
+    listener: Arc<Mutex<Option<FileChangeListener>>>,
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        Self {
+            watcher: Mutex::new(None),
+            state: Arc::new(Mutex::new(WatchState::default())),
+            listener: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn set_listener(&self, listener: impl Fn(&FileChangedEvent) + Send + Sync + 'static) {
+        if let Ok(mut current) = self.listener.lock() {
+            *current = Some(Arc::new(listener));
+        }
+    }
+
+    /// Start watching `path`. Watching an already watched file just refreshes
+    /// its stamp, so reopening after a reload doesn't report the reload itself.
+    pub fn watch_file(&self, path: &str) -> Result<(), String> {
+        let canonical = fs::canonicalize(path).map_err(|e| e.to_string())?;
+        let dir = canonical
+            .parent()
+            .ok_or_else(|| format!("File has no parent directory: {}", path))?
+            .to_path_buf();
+
+        let stamp = FileStamp::read(&canonical);
+        let first_in_dir = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            if let Some(file) = state.files.get_mut(&canonical) {
+                file.stamp = stamp;
+                return Ok(());
+            }
+            state.files.insert(
+                canonical,
+                WatchedFile {
+                    path: path.to_string(),
+                    stamp,
+                },
+            );
+            let count = state.dirs.entry(dir.clone()).or_insert(0);
+            *count += 1;
+            *count == 1
+        };
+
+        if first_in_dir {
+            let registered = with_watcher(
+                &self.watcher,
+                || self.create_watcher(),
+                |watcher| {
+                    watcher
+                        .watch(&dir, RecursiveMode::NonRecursive)
+                        .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))
+                },
+            );
+            if let Err(e) = registered {
+                let _ = self.unwatch_file(path);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn unwatch_file(&self, path: &str) -> Result<(), String> {
+        let unused_dir = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            let canonical = state
+                .files
+                .iter()
+                .find(|(_, file)| file.path == path)
+                .map(|(canonical, _)| canonical.clone())
+                .ok_or_else(|| format!("File is not watched: {}", path))?;
+            state.files.remove(&canonical);
+
+            let Some(dir) = canonical.parent().map(Path::to_path_buf) else {
+                return Ok(());
+            };
+            let remaining = state.dirs.get_mut(&dir).map(|count| {
+                *count -= 1;
+                *count
+            });
+            if remaining == Some(0) {
+                state.dirs.remove(&dir);
+                Some(dir)
+            } else {
+                None
+            }
+        };
+
+        if let Some(dir) = unused_dir {
+            // The directory may already be gone, which unwatches it anyway
+            let _ = with_watcher(
+                &self.watcher,
+                || self.create_watcher(),
+                |watcher| watcher.unwatch(&dir).map_err(|e| e.to_string()),
+            );
+        }
+        Ok(())
+    }
+
+    fn create_watcher(&self) -> Result<RecommendedWatcher, String> {
+        let state = self.state.clone();
+        let listener = self.listener.clone();
+        notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                handle_event(&state, &listener, &event);
+            }
+        })
+        .map_err(|e| format!("Failed to start file watcher: {}", e))
+    }
+}
+
+/// Run `op` on the notify watcher in `slot`, starting it with `create` on
+/// first use. Callers must not hold the lock their event handler takes: notify
+/// waits for its event thread while registering, so that would deadlock.
+pub(crate) fn with_watcher(
+    slot: &Mutex<Option<RecommendedWatcher>>,
+    create: impl FnOnce() -> Result<RecommendedWatcher, String>,
+    op: impl FnOnce(&mut RecommendedWatcher) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut watcher = slot.lock().map_err(|e| e.to_string())?;
+    if watcher.is_none() {
+        *watcher = Some(create()?);
+    }
+    match watcher.as_mut() {
+        Some(watcher) => op(watcher),
+        None => Ok(()),
+    }
+}
+
+fn handle_event(
+    state: &Mutex<WatchState>,
+    listener: &Mutex<Option<FileChangeListener>>,
+    event: &Event,
+) {
+    let changes: Vec<FileChangedEvent> = {
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        event
+            .paths
+            .iter()
+            .filter_map(|path| {
+                let file = state.files.get_mut(path)?;
+                let stamp = FileStamp::read(path);
+                // Editors and writers emit several events per save; only report real changes
+                if stamp == file.stamp {
+                    return None;
+                }
+                file.stamp = stamp.clone();
+                Some(FileChangedEvent {
+                    path: file.path.clone(),
+                    kind: if stamp.is_some() {
+                        "modified"
+                    } else {
+                        "removed"
+                    }
+                    .to_string(),
+                    size: stamp.as_ref().map(|s| s.size),
+                    modified: stamp
+                        .and_then(|s| s.modified)
+                        .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+                })
+            })
+            .collect()
+    };
+
+    let Some(listener) = listener.lock().ok().and_then(|l| l.clone()) else {
+        return;
+    };
+    for change in &changes {
+        listener(change);
+    }
+}
+
+/// Called for each parquet file added, removed or modified under a watched directory.
+pub type DirectoryChangeListener = Arc<dyn Fn(&DirectoryChangedEvent) + Send + Sync>;
+
+/// Repeated events for the same file and kind within this window are dropped,
+/// since a single write usually produces a burst of them.
+const DIRECTORY_EVENT_DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Default)]
+struct DirectoryState {
+    /// Canonical root -> path as the frontend passed it.
+    roots: HashMap<PathBuf, String>,
+    recent: HashMap<PathBuf, (&'static str, Instant)>,
+}
+
+/// Recursively watches directories shown in the file browser.
+pub struct DirectoryWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    state: Arc<Mutex<DirectoryState>>,
+    listener: Arc<Mutex<Option<DirectoryChangeListener>>>,
+}
+
+impl Default for DirectoryWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirectoryWatcher {
+    pub fn new() -> Self {
+        Self {
+            watcher: Mutex::new(None),
+            state: Arc::new(Mutex::new(DirectoryState::default())),
+            listener: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn set_listener(&self, listener: impl Fn(&DirectoryChangedEvent) + Send + Sync + 'static) {
+        if let Ok(mut current) = self.listener.lock() {
+            *current = Some(Arc::new(listener));
+        }
+    }
+
+    pub fn watch_directory(&self, path: &str) -> Result<(), String> {
+        let canonical = fs::canonicalize(path).map_err(|e| e.to_string())?;
+        if !canonical.is_dir() {
+            return Err("Path is not a directory".to_string());
+        }
+
+        {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            if state.roots.contains_key(&canonical) {
+                return Ok(());
+            }
+            state.roots.insert(canonical.clone(), path.to_string());
+        }
+
+        let registered = with_watcher(
+            &self.watcher,
+            || self.create_watcher(),
+            |watcher| {
+                watcher
+                    .watch(&canonical, RecursiveMode::Recursive)
+                    .map_err(|e| format!("Failed to watch {}: {}", path, e))
+            },
+        );
+        if registered.is_err() {
+            if let Ok(mut state) = self.state.lock() {
+                state.roots.remove(&canonical);
+            }
+        }
+        registered
+    }
+
+    pub fn unwatch_directory(&self, path: &str) -> Result<(), String> {
+        let canonical = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            let canonical = state
+                .roots
+                .iter()
+                .find(|(_, root)| root.as_str() == path)
+                .map(|(canonical, _)| canonical.clone())
+                .ok_or_else(|| format!("Directory is not watched: {}", path))?;
+            state.roots.remove(&canonical);
+            state.recent.retain(|p, _| !p.starts_with(&canonical));
+            canonical
+        };
+
+        // The directory may already be gone, which unwatches it anyway
+        let _ = with_watcher(
+            &self.watcher,
+            || self.create_watcher(),
+            |watcher| watcher.unwatch(&canonical).map_err(|e| e.to_string()),
+        );
+        Ok(())
+    }
+
+    fn create_watcher(&self) -> Result<RecommendedWatcher, String> {
+        let state = self.state.clone();
+        let listener = self.listener.clone();
+        notify::recommended_watcher(move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                handle_directory_event(&state, &listener, &event);
+            }
+        })
+        .map_err(|e| format!("Failed to start directory watcher: {}", e))
+    }
+}
+
+fn directory_change_kind(kind: &EventKind, path: &Path) -> Option<&'static str> {
+    match kind {
+        EventKind::Create(_) => Some("added"),
+        EventKind::Remove(_) => Some("removed"),
+        // Renames arrive as separate from/to events, so check which side this is
+        EventKind::Modify(ModifyKind::Name(_)) => {
+            Some(if path.exists() { "added" } else { "removed" })
+        }
+        EventKind::Modify(_) => Some("modified"),
+        _ => None,
+    }
+}
+
+fn handle_directory_event(
+    state: &Mutex<DirectoryState>,
+    listener: &Mutex<Option<DirectoryChangeListener>>,
+    event: &Event,
+) {
+    let changes: Vec<DirectoryChangedEvent> = {
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        state
+            .recent
+            .retain(|_, (_, at)| now.duration_since(*at) < DIRECTORY_EVENT_DEBOUNCE);
+
+        let mut changes = Vec::new();
+        for path in &event.paths {
+            if path.extension().and_then(|e| e.to_str()) != Some("parquet") {
+                continue;
+            }
+            let Some(kind) = directory_change_kind(&event.kind, path) else {
+                continue;
+            };
+            let Some(directory) = state
+                .roots
+                .iter()
+                .find(|(root, _)| path.starts_with(root))
+                .map(|(_, original)| original.clone())
+            else {
+                continue;
+            };
+            if state.recent.get(path).is_some_and(|(k, _)| *k == kind) {
+                continue;
+            }
+            state.recent.insert(path.clone(), (kind, now));
+            changes.push(DirectoryChangedEvent {
+                directory,
+                path: path.to_string_lossy().to_string(),
+                kind: kind.to_string(),
+            });
+        }
+        changes
+    };
+
+    let Some(listener) = listener.lock().ok().and_then(|l| l.clone()) else {
+        return;
+    };
+    for change in &changes {
+        listener(change);
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::models::{Workspace, WorkspaceInfo};
+use crate::services::parquet::ParquetCache;
+use crate::services::{session, store};
+
+/// Version of the workspace format written by `save_workspace`.
+const WORKSPACE_VERSION: u32 = 1;
+
+const WORKSPACE_DIR: &str = "workspaces";
+
+fn workspace_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(WORKSPACE_DIR)
+}
+
+/// File a workspace is stored in. Names differing only in punctuation (or,
+/// on some systems, case) share one, so the name stored inside decides which
+/// workspace it holds.
+fn workspace_path(data_dir: &Path, name: &str) -> PathBuf {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    workspace_dir(data_dir).join(format!("{}.json", stem))
+}
+
+fn read_workspace(path: &Path) -> Result<Option<Workspace>, String> {
+    store::read_json(path, "workspace")
+}
+
+/// File holding the workspace named `name`, if one was saved.
+fn find_workspace(data_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let path = workspace_path(data_dir, name);
+    match read_workspace(&path)? {
+        Some(workspace) if workspace.name == name.trim() => Ok(path),
+        _ => Err(format!("Workspace not found: {}", name)),
+    }
+}
+
+/// Sessions opened by loading each workspace, keyed by workspace name. Loading
+/// a workspace again releases the sessions of its previous load, whose tabs
+/// the new load replaces.
+pub struct WorkspaceSessions {
+    opened: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl Default for WorkspaceSessions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorkspaceSessions {
+    pub fn new() -> Self {
+        Self {
+            opened: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Destroy the sessions an earlier load of `name` opened.
+    fn release(&self, cache: &ParquetCache, name: &str) -> Result<(), String> {
+        let released = self
+            .opened
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(name)
+            .unwrap_or_default();
+        for id in released {
+            // Already gone if the frontend destroyed it itself
+            let _ = cache.destroy_workspace(&id);
+        }
+        Ok(())
+    }
+
+    fn record(&self, name: &str, ids: Vec<String>) -> Result<(), String> {
+        self.opened
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(name.to_string(), ids);
+        Ok(())
+    }
+}
+
+/// Save `workspace` under its name, replacing an earlier save. The tables,
+/// views and settings of each SQL tab's session are captured so loading can
+/// rebuild it. A tab whose session no longer exists keeps the session it was
+/// loaded with, if any, and says why in `restore_error` instead of failing
+/// the save.
+pub async fn save_workspace(
+    cache: &ParquetCache,
+    data_dir: &Path,
+    mut workspace: Workspace,
+) -> Result<Workspace, String> {
+    if workspace.name.trim().is_empty() {
+        return Err("Workspace name must not be empty".to_string());
+    }
+    workspace.name = workspace.name.trim().to_string();
+    workspace.version = WORKSPACE_VERSION;
+    workspace.saved_at = chrono::Utc::now().to_rfc3339();
+
+    for tab in &mut workspace.sql_tabs {
+        tab.restore_error = None;
+        if let Some(session_id) = &tab.session_id {
+            match session::snapshot(cache, session_id, 0).await {
+                Ok(spec) => tab.session = Some(spec),
+                Err(e) => {
+                    tab.restore_error = Some(format!("Session not saved: {}", e));
+                    tab.session_id = None;
+                }
+            }
+        }
+    }
+
+    store::locked(|| {
+        let path = workspace_path(data_dir, &workspace.name);
+        if let Some(existing) = read_workspace(&path)? {
+            if existing.name != workspace.name {
+                return Err(format!(
+                    "Workspace name '{}' is too similar to the saved workspace '{}'",
+                    workspace.name, existing.name
+                ));
+            }
+        }
+        store::atomic_write_json(&path, &workspace)
+    })?;
+
+    Ok(workspace)
+}
+
+/// Load a saved workspace, opening a fresh SQL session for every tab that had
+/// one and releasing those of its previous load. A tab whose session can't be
+/// rebuilt (e.g. a file was moved) keeps its query, with `restore_error`
+/// saying why and no session id.
+pub async fn load_workspace(
+    cache: &ParquetCache,
+    sessions: &WorkspaceSessions,
+    data_dir: &Path,
+    name: &str,
+) -> Result<Workspace, String> {
+    let path = find_workspace(data_dir, name)?;
+    let mut workspace =
+        read_workspace(&path)?.ok_or_else(|| format!("Workspace not found: {}", name))?;
+    if workspace.version > WORKSPACE_VERSION {
+        return Err(format!(
+            "Unsupported workspace version: {}",
+            workspace.version
+        ));
+    }
+
+    sessions.release(cache, &workspace.name)?;
+    let mut opened = Vec::new();
+    for tab in &mut workspace.sql_tabs {
+        tab.session_id = None;
+        let Some(spec) = &tab.session else {
+            continue;
+        };
+        let restored = async {
+            let id = cache.create_workspace(session::primary_path(spec)?).await?;
+            let ctx = cache.session_for(&id).await?;
+            if let Err(e) = session::apply_spec(cache, &ctx, spec).await {
+                let _ = cache.destroy_workspace(&id);
+                return Err(e);
+            }
+            Ok::<_, String>(id)
+        }
+        .await;
+        match restored {
+            Ok(id) => {
+                opened.push(id.clone());
+                tab.session_id = Some(id);
+            }
+            Err(e) => tab.restore_error = Some(e),
+        }
+    }
+    sessions.record(&workspace.name, opened)?;
+
+    Ok(workspace)
+}
+
+/// Saved workspaces, most recently saved first.
+pub fn list_workspaces(data_dir: &Path) -> Result<Vec<WorkspaceInfo>, String> {
+    let dir = workspace_dir(data_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut workspaces = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        // Skip files that aren't workspaces rather than failing the whole list
+        if let Ok(Some(workspace)) = read_workspace(&path) {
+            workspaces.push(WorkspaceInfo {
+                name: workspace.name,
+                saved_at: workspace.saved_at,
+                file_count: workspace.files.len(),
+                sql_tab_count: workspace.sql_tabs.len(),
+            });
+        }
+    }
+    workspaces.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+    Ok(workspaces)
+}
+
+pub fn delete_workspace(data_dir: &Path, name: &str) -> Result<(), String> {
+    store::locked(|| {
+        let path = find_workspace(data_dir, name)?;
+        fs::remove_file(path).map_err(|e| e.to_string())
+    })
+}
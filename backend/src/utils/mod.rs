@@ -1,7 +1,23 @@
+use arrow::array::{make_array, Array, ArrayRef, AsArray, StringArray};
+use arrow::buffer::NullBuffer;
+use arrow::datatypes::{
+    DataType, Field as ArrowField, Float32Type, Float64Type, Schema, TimeUnit,
+    TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+    TimestampSecondType,
+};
+use arrow::record_batch::RecordBatch;
 use base64::{engine::general_purpose, Engine as _};
-use chrono::{DateTime, NaiveDate};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+use parquet::basic::{ConvertedType, LogicalType, Repetition};
 use parquet::record::{Field, Row};
+use parquet::schema::types::Type;
 use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+
+pub mod wkb;
 
 fn format_date(days: i32) -> String {
     let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
@@ -9,27 +25,602 @@ fn format_date(days: i32) -> String {
     date.format("%Y-%m-%d").to_string()
 }
 
-fn format_timestamp_millis(v: i64) -> Option<String> {
+fn format_timestamp_millis(v: i64, format: TimestampFormat) -> Option<String> {
     let seconds = v / 1000;
     let nanos = ((v % 1000) * 1_000_000) as u32;
-    DateTime::from_timestamp(seconds, nanos).map(|dt| dt.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+    DateTime::from_timestamp(seconds, nanos).map(|dt| format.wall_clock(dt, 3))
 }
 
-fn format_timestamp_micros(v: i64) -> Option<String> {
+fn format_timestamp_micros(v: i64, format: TimestampFormat) -> Option<String> {
     let seconds = v / 1_000_000;
     let nanos = ((v % 1_000_000) * 1000) as u32;
-    DateTime::from_timestamp(seconds, nanos).map(|dt| dt.format("%Y-%m-%d %H:%M:%S%.6f").to_string())
+    DateTime::from_timestamp(seconds, nanos).map(|dt| format.wall_clock(dt, 6))
+}
+
+/// Pattern `TimestampFormat` uses unless the user picks another.
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// chrono `strftime` pattern for the date and time of day of rendered
+/// timestamps. Fractional seconds at the column's precision, and the offset
+/// of UTC instants, are appended to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimestampFormat(&'static str);
+
+impl Default for TimestampFormat {
+    fn default() -> Self {
+        TimestampFormat(DEFAULT_TIMESTAMP_FORMAT)
+    }
+}
+
+impl TimestampFormat {
+    /// Parse a pattern such as `"%d/%m/%Y %H:%M:%S"`.
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        if pattern.is_empty() || StrftimeItems::new(pattern).any(|item| item == Item::Error) {
+            return Err(format!("Invalid timestamp format: {}", pattern));
+        }
+        Ok(TimestampFormat(intern(pattern)))
+    }
+
+    pub fn pattern(&self) -> &'static str {
+        self.0
+    }
+
+    /// The pattern followed by `precision` fractional second digits.
+    fn with_fraction(&self, precision: usize) -> String {
+        match precision {
+            0 => self.0.to_string(),
+            3 => format!("{}%.3f", self.0),
+            6 => format!("{}%.6f", self.0),
+            _ => format!("{}%.9f", self.0),
+        }
+    }
+
+    /// `value`, a wall-clock timestamp, as stored: without conversion or offset.
+    fn wall_clock(&self, value: DateTime<Utc>, precision: usize) -> String {
+        value.format(&self.with_fraction(precision)).to_string()
+    }
+}
+
+/// `pattern` kept for the life of the app, so `TimestampFormat` (and with it
+/// `DisplayOptions`) stays `Copy`. Each distinct pattern is stored once.
+fn intern(pattern: &str) -> &'static str {
+    static PATTERNS: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut patterns = PATTERNS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = patterns.get(pattern) {
+        return *interned;
+    }
+    let interned: &'static str = Box::leak(pattern.to_string().into_boxed_str());
+    patterns.insert(interned);
+    interned
+}
+
+/// Timezone that timestamps stored as UTC instants (`isAdjustedToUTC`) are
+/// rendered in. Timestamps without that flag are wall-clock values and are
+/// always shown as stored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DisplayTimezone {
+    #[default]
+    Utc,
+    Local,
+    Named(Tz),
+}
+
+impl DisplayTimezone {
+    /// Parse `"utc"`, `"local"` or an IANA name such as `"Asia/Tokyo"`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim() {
+            n if n.eq_ignore_ascii_case("utc") => Ok(DisplayTimezone::Utc),
+            n if n.eq_ignore_ascii_case("local") => Ok(DisplayTimezone::Local),
+            n => n
+                .parse::<Tz>()
+                .map(DisplayTimezone::Named)
+                .map_err(|_| format!("Unknown timezone: {}", n)),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            DisplayTimezone::Utc => "utc".to_string(),
+            DisplayTimezone::Local => "local".to_string(),
+            DisplayTimezone::Named(tz) => tz.name().to_string(),
+        }
+    }
+
+    /// `instant` in this timezone under `format` with its offset, e.g.
+    /// `2024-01-02 12:00:00.000+09:00`. `precision` is the number of
+    /// fractional second digits.
+    fn format(&self, instant: DateTime<Utc>, precision: usize, format: TimestampFormat) -> String {
+        let pattern = format!("{}%:z", format.with_fraction(precision));
+        match self {
+            DisplayTimezone::Utc => instant.format(&pattern).to_string(),
+            DisplayTimezone::Local => instant.with_timezone(&Local).format(&pattern).to_string(),
+            DisplayTimezone::Named(tz) => instant.with_timezone(tz).format(&pattern).to_string(),
+        }
+    }
+}
+
+/// Bytes of a binary value shown before the hex rendering is cut off.
+pub const BINARY_PREVIEW_BYTES: usize = 32;
+
+/// How binary (non-UTF8 `BYTE_ARRAY`) values are rendered as text.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BinaryDisplay {
+    /// Hex digits of the first `BINARY_PREVIEW_BYTES` bytes.
+    Hex,
+    #[default]
+    Base64,
+    /// Only the length, e.g. `<128 bytes>`.
+    Size,
+}
+
+impl BinaryDisplay {
+    /// Parse `"hex"`, `"base64"` or `"size"`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "hex" => Ok(BinaryDisplay::Hex),
+            "base64" => Ok(BinaryDisplay::Base64),
+            "size" => Ok(BinaryDisplay::Size),
+            other => Err(format!("Unsupported binary display: {}", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BinaryDisplay::Hex => "hex",
+            BinaryDisplay::Base64 => "base64",
+            BinaryDisplay::Size => "size",
+        }
+    }
+
+    pub fn render(&self, bytes: &[u8]) -> String {
+        match self {
+            BinaryDisplay::Hex => {
+                let shown = &bytes[..bytes.len().min(BINARY_PREVIEW_BYTES)];
+                let mut hex: String = shown.iter().map(|b| format!("{:02x}", b)).collect();
+                if shown.len() < bytes.len() {
+                    hex.push_str(&format!("… ({} bytes)", bytes.len()));
+                }
+                hex
+            }
+            BinaryDisplay::Base64 => general_purpose::STANDARD.encode(bytes),
+            BinaryDisplay::Size => format!("<{} bytes>", bytes.len()),
+        }
+    }
+}
+
+/// How NaN and ±Infinity floats are rendered; JSON has no literal for them.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NonFiniteDisplay {
+    /// The strings `"NaN"`, `"Infinity"` and `"-Infinity"`.
+    #[default]
+    String,
+    Null,
+}
+
+impl NonFiniteDisplay {
+    /// Parse `"string"` or `"null"`.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_lowercase().as_str() {
+            "string" => Ok(NonFiniteDisplay::String),
+            "null" => Ok(NonFiniteDisplay::Null),
+            other => Err(format!("Unsupported non-finite display: {}", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NonFiniteDisplay::String => "string",
+            NonFiniteDisplay::Null => "null",
+        }
+    }
+}
+
+/// User-chosen rendering of values whose text form is a matter of taste.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DisplayOptions {
+    pub timezone: DisplayTimezone,
+    pub timestamp_format: TimestampFormat,
+    pub binary: BinaryDisplay,
+    pub non_finite: NonFiniteDisplay,
+}
+
+fn non_finite_name(v: f64) -> Option<&'static str> {
+    if v.is_nan() {
+        Some("NaN")
+    } else if v == f64::INFINITY {
+        Some("Infinity")
+    } else if v == f64::NEG_INFINITY {
+        Some("-Infinity")
+    } else {
+        None
+    }
+}
+
+/// A narrower float as the f64 with the same shortest decimal form, so 0.1f32
+/// is written as 0.1 rather than 0.10000000149011612.
+fn widen(v: f32, shortest: String) -> f64 {
+    shortest.parse().unwrap_or(v as f64)
+}
+
+fn float_to_json(v: f64) -> Value {
+    match serde_json::Number::from_f64(v) {
+        Some(n) => Value::Number(n),
+        None => non_finite_name(v).map_or(Value::Null, |name| Value::String(name.to_string())),
+    }
+}
+
+fn float_to_string(v: f64, shortest: String) -> String {
+    non_finite_name(v).map_or(shortest, str::to_string)
+}
+
+fn is_non_finite(field: &Field) -> bool {
+    match field {
+        Field::Float(v) => !v.is_finite(),
+        Field::Double(v) => !v.is_finite(),
+        Field::Float16(v) => !v.is_finite(),
+        _ => false,
+    }
+}
+
+/// The arrow JSON writer turns NaN and ±Infinity into null; put them back into
+/// `rows` (one object per row of `batches`) when they are displayed as strings.
+pub fn restore_non_finite(batches: &[RecordBatch], rows: &mut [Value], display: NonFiniteDisplay) {
+    if display == NonFiniteDisplay::Null {
+        return;
+    }
+
+    let mut base = 0;
+    for batch in batches {
+        for (field, column) in batch.schema().fields().iter().zip(batch.columns()) {
+            let values: Vec<Option<f64>> = match field.data_type() {
+                DataType::Float64 => column.as_primitive::<Float64Type>().iter().collect(),
+                DataType::Float32 => column
+                    .as_primitive::<Float32Type>()
+                    .iter()
+                    .map(|v| v.map(f64::from))
+                    .collect(),
+                _ => continue,
+            };
+            for (i, v) in values.into_iter().enumerate() {
+                let Some(name) = v.and_then(non_finite_name) else {
+                    continue;
+                };
+                if let Some(Value::Object(row)) = rows.get_mut(base + i) {
+                    row.insert(field.name().clone(), Value::String(name.to_string()));
+                }
+            }
+        }
+        base += batch.num_rows();
+    }
+}
+
+/// Column-dependent rendering choices for `row_to_json_with` and `field_to_string_with`.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Columns whose WKB blobs are rendered as WKT.
+    pub geometry_columns: HashSet<String>,
+    /// Timestamp columns holding UTC instants, rendered in the display timezone.
+    pub utc_columns: HashSet<String>,
+    pub display: DisplayOptions,
+    /// Expand struct fields into dotted columns (`address.city`); lists and
+    /// maps are rendered as JSON.
+    pub flatten: bool,
+}
+
+/// Top-level timestamp columns of a Parquet schema that store UTC instants,
+/// including the legacy `TIMESTAMP_MILLIS`/`TIMESTAMP_MICROS` annotations.
+pub fn utc_timestamp_columns(schema: &Type) -> HashSet<String> {
+    schema
+        .get_fields()
+        .iter()
+        .filter(|field| {
+            let info = field.get_basic_info();
+            match info.logical_type() {
+                Some(LogicalType::Timestamp {
+                    is_adjusted_to_u_t_c,
+                    ..
+                }) => is_adjusted_to_u_t_c,
+                Some(_) => false,
+                None => matches!(
+                    info.converted_type(),
+                    ConvertedType::TIMESTAMP_MILLIS | ConvertedType::TIMESTAMP_MICROS
+                ),
+            }
+        })
+        .map(|field| field.name().to_string())
+        .collect()
+}
+
+fn is_rendered_for_display(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Timestamp(_, _)
+            | DataType::Binary
+            | DataType::LargeBinary
+            | DataType::FixedSizeBinary(_)
+    )
+}
+
+/// Replace timestamp and binary columns with their text rendering under
+/// `display`, so JSON output shows timestamps in the chosen format (with
+/// offsets for UTC instants) and binary values the way the user chose.
+pub fn render_for_display(
+    batch: &RecordBatch,
+    display: DisplayOptions,
+) -> Result<RecordBatch, String> {
+    let schema = batch.schema();
+    if !schema
+        .fields()
+        .iter()
+        .any(|f| is_rendered_for_display(f.data_type()))
+    {
+        return Ok(batch.clone());
+    }
+
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(schema.fields().len());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        match field.data_type() {
+            DataType::Timestamp(unit, timezone) => {
+                let rendered = render_timestamp_column(column, unit, timezone.is_some(), display);
+                fields.push(ArrowField::new(field.name(), DataType::Utf8, true));
+                columns.push(Arc::new(rendered));
+            }
+            data_type if is_rendered_for_display(data_type) => {
+                let rendered = render_binary_column(column, display.binary);
+                fields.push(ArrowField::new(field.name(), DataType::Utf8, true));
+                columns.push(Arc::new(rendered));
+            }
+            _ => {
+                fields.push(field.as_ref().clone());
+                columns.push(column.clone());
+            }
+        }
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(|e| e.to_string())
+}
+
+/// Replace struct columns by their fields, named `parent.child`, recursively.
+/// Lists and maps are kept as single columns.
+pub fn flatten_structs(batch: &RecordBatch) -> Result<RecordBatch, String> {
+    let schema = batch.schema();
+    if !schema
+        .fields()
+        .iter()
+        .any(|f| matches!(f.data_type(), DataType::Struct(_)))
+    {
+        return Ok(batch.clone());
+    }
+
+    let mut fields = Vec::new();
+    let mut columns = Vec::new();
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        push_flattened(
+            field.name(),
+            field,
+            column.clone(),
+            &mut fields,
+            &mut columns,
+        )?;
+    }
+
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(|e| e.to_string())
+}
+
+fn push_flattened(
+    name: &str,
+    field: &ArrowField,
+    column: ArrayRef,
+    fields: &mut Vec<ArrowField>,
+    columns: &mut Vec<ArrayRef>,
+) -> Result<(), String> {
+    let DataType::Struct(children) = field.data_type() else {
+        fields.push(field.clone().with_name(name));
+        columns.push(column);
+        return Ok(());
+    };
+
+    let parent = column.as_struct();
+    for (child, values) in children.iter().zip(parent.columns()) {
+        // A null struct makes all of its fields null
+        let nulls = NullBuffer::union(parent.nulls(), values.nulls());
+        let values = make_array(
+            values
+                .to_data()
+                .into_builder()
+                .nulls(nulls)
+                .build()
+                .map_err(|e| e.to_string())?,
+        );
+        let child_name = format!("{}.{}", name, child.name());
+        let child = child.as_ref().clone().with_nullable(true);
+        push_flattened(&child_name, &child, values, fields, columns)?;
+    }
+    Ok(())
+}
+
+/// Text of a timestamp column: UTC instants (`utc`) in the display timezone
+/// with their offset, wall-clock values as stored.
+fn render_timestamp_column(
+    column: &ArrayRef,
+    unit: &TimeUnit,
+    utc: bool,
+    display: DisplayOptions,
+) -> StringArray {
+    let (instants, precision): (Vec<Option<DateTime<Utc>>>, usize) = match unit {
+        TimeUnit::Second => (
+            column
+                .as_primitive::<TimestampSecondType>()
+                .iter()
+                .map(|v| v.and_then(|v| DateTime::from_timestamp(v, 0)))
+                .collect(),
+            0,
+        ),
+        TimeUnit::Millisecond => (
+            column
+                .as_primitive::<TimestampMillisecondType>()
+                .iter()
+                .map(|v| v.and_then(DateTime::from_timestamp_millis))
+                .collect(),
+            3,
+        ),
+        TimeUnit::Microsecond => (
+            column
+                .as_primitive::<TimestampMicrosecondType>()
+                .iter()
+                .map(|v| v.and_then(DateTime::from_timestamp_micros))
+                .collect(),
+            6,
+        ),
+        TimeUnit::Nanosecond => (
+            column
+                .as_primitive::<TimestampNanosecondType>()
+                .iter()
+                .map(|v| v.map(DateTime::from_timestamp_nanos))
+                .collect(),
+            9,
+        ),
+    };
+
+    instants
+        .into_iter()
+        .map(|instant| {
+            instant.map(|instant| {
+                if utc {
+                    display
+                        .timezone
+                        .format(instant, precision, display.timestamp_format)
+                } else {
+                    display.timestamp_format.wall_clock(instant, precision)
+                }
+            })
+        })
+        .collect()
+}
+
+fn render_binary_column(column: &ArrayRef, binary: BinaryDisplay) -> StringArray {
+    match column.data_type() {
+        DataType::Binary => column
+            .as_binary::<i32>()
+            .iter()
+            .map(|v| v.map(|bytes| binary.render(bytes)))
+            .collect(),
+        DataType::LargeBinary => column
+            .as_binary::<i64>()
+            .iter()
+            .map(|v| v.map(|bytes| binary.render(bytes)))
+            .collect(),
+        _ => column
+            .as_fixed_size_binary()
+            .iter()
+            .map(|v| v.map(|bytes| binary.render(bytes)))
+            .collect(),
+    }
+}
+
+fn utc_instant(field: &Field) -> Option<(DateTime<Utc>, usize)> {
+    match field {
+        Field::TimestampMillis(v) => DateTime::from_timestamp_millis(*v).map(|dt| (dt, 3)),
+        Field::TimestampMicros(v) => DateTime::from_timestamp_micros(*v).map(|dt| (dt, 6)),
+        _ => None,
+    }
 }
 
 pub fn row_to_json(row: &Row) -> Value {
+    row_to_json_with(row, &RenderOptions::default())
+}
+
+/// Like `row_to_json`, but renders the WKB blobs of geometry columns as WKT,
+/// other binary values and UTC timestamps as chosen in `options.display`.
+pub fn row_to_json_with(row: &Row, options: &RenderOptions) -> Value {
     let mut map = serde_json::Map::new();
+    insert_row_fields(&mut map, "", row, options);
+    Value::Object(map)
+}
 
+fn insert_row_fields(
+    map: &mut serde_json::Map<String, Value>,
+    prefix: &str,
+    row: &Row,
+    options: &RenderOptions,
+) {
     for (name, value) in row.get_column_iter() {
-        let json_value = field_to_json(value);
-        map.insert(name.clone(), json_value);
+        let key = format!("{}{}", prefix, name);
+        let json_value = match value {
+            Field::Group(group) if options.flatten => {
+                insert_row_fields(map, &format!("{}.", key), group, options);
+                continue;
+            }
+            Field::Bytes(bytes) if options.geometry_columns.contains(&key) => {
+                wkb::wkb_to_wkt(bytes.data())
+                    .map(Value::String)
+                    .unwrap_or_else(|| field_to_json(value))
+            }
+            Field::Bytes(bytes) => Value::String(options.display.binary.render(bytes.data())),
+            _ if options.display.non_finite == NonFiniteDisplay::Null && is_non_finite(value) => {
+                Value::Null
+            }
+            Field::TimestampMillis(_) | Field::TimestampMicros(_) => utc_instant(value)
+                .map(|(dt, precision)| Value::String(render_instant(&key, dt, precision, options)))
+                .unwrap_or_else(|| field_to_json(value)),
+            _ => field_to_json(value),
+        };
+        map.insert(key, json_value);
     }
+}
 
-    Value::Object(map)
+/// Leaf values of `row` keyed by dotted path, descending into struct groups.
+pub fn flatten_row_fields(row: &Row) -> Vec<(String, &Field)> {
+    let mut fields = Vec::new();
+    collect_row_fields(&mut fields, "", row);
+    fields
+}
+
+fn collect_row_fields<'a>(fields: &mut Vec<(String, &'a Field)>, prefix: &str, row: &'a Row) {
+    for (name, value) in row.get_column_iter() {
+        let key = format!("{}{}", prefix, name);
+        match value {
+            Field::Group(group) => collect_row_fields(fields, &format!("{}.", key), group),
+            _ => fields.push((key, value)),
+        }
+    }
+}
+
+/// Column names of a Parquet schema with struct groups expanded into dotted
+/// paths, matching the keys produced by `flatten_row_fields`.
+pub fn flattened_column_names(schema: &Type) -> Vec<String> {
+    let mut names = Vec::new();
+    collect_column_names(&mut names, "", schema);
+    names
+}
+
+fn collect_column_names(names: &mut Vec<String>, prefix: &str, group: &Type) {
+    for field in group.get_fields() {
+        let name = format!("{}{}", prefix, field.name());
+        if is_struct_group(field) {
+            collect_column_names(names, &format!("{}.", name), field);
+        } else {
+            names.push(name);
+        }
+    }
+}
+
+fn is_struct_group(field: &Type) -> bool {
+    let info = field.get_basic_info();
+    field.is_group()
+        && (!info.has_repetition() || info.repetition() != Repetition::REPEATED)
+        && !matches!(
+            info.logical_type(),
+            Some(LogicalType::List) | Some(LogicalType::Map)
+        )
+        && !matches!(
+            info.converted_type(),
+            ConvertedType::LIST | ConvertedType::MAP | ConvertedType::MAP_KEY_VALUE
+        )
 }
 
 pub fn field_to_json(field: &Field) -> Value {
@@ -43,33 +634,27 @@ pub fn field_to_json(field: &Field) -> Value {
         Field::UShort(v) => Value::Number((*v).into()),
         Field::UInt(v) => Value::Number((*v).into()),
         Field::ULong(v) => Value::Number((*v).into()),
-        Field::Float(v) => Value::Number(
-            serde_json::Number::from_f64(*v as f64).unwrap_or(serde_json::Number::from(0)),
-        ),
-        Field::Double(v) => {
-            Value::Number(serde_json::Number::from_f64(*v).unwrap_or(serde_json::Number::from(0)))
-        }
+        Field::Float(v) => float_to_json(widen(*v, v.to_string())),
+        Field::Double(v) => float_to_json(*v),
         Field::Decimal(d) => Value::String(format!("{:?}", d)),
         Field::Str(v) => Value::String(v.clone()),
         Field::Bytes(v) => Value::String(general_purpose::STANDARD.encode(v.data())),
         Field::Date(v) => Value::String(format_date(*v)),
         Field::TimestampMillis(v) => {
-            if let Some(s) = format_timestamp_millis(*v) {
+            if let Some(s) = format_timestamp_millis(*v, TimestampFormat::default()) {
                 Value::String(s)
             } else {
                 Value::Number((*v).into())
             }
         }
         Field::TimestampMicros(v) => {
-            if let Some(s) = format_timestamp_micros(*v) {
+            if let Some(s) = format_timestamp_micros(*v, TimestampFormat::default()) {
                 Value::String(s)
             } else {
                 Value::Number((*v).into())
             }
         }
-        Field::Float16(v) => Value::Number(
-            serde_json::Number::from_f64(v.to_f64()).unwrap_or(serde_json::Number::from(0)),
-        ),
+        Field::Float16(v) => float_to_json(widen(v.to_f32(), v.to_string())),
 
         Field::Group(g) => row_to_json(g),
         Field::ListInternal(list) => {
@@ -87,6 +672,38 @@ pub fn field_to_json(field: &Field) -> Value {
     }
 }
 
+/// `field_to_string` for the value of column `name`, rendering binary values
+/// and UTC timestamps as chosen in `options.display`.
+pub fn field_to_string_with(name: &str, field: &Field, options: &RenderOptions) -> String {
+    match field {
+        Field::Bytes(bytes) => return options.display.binary.render(bytes.data()),
+        _ if options.display.non_finite == NonFiniteDisplay::Null && is_non_finite(field) => {
+            return String::new()
+        }
+        _ => {}
+    }
+    if let Some((dt, precision)) = utc_instant(field) {
+        return render_instant(name, dt, precision, options);
+    }
+    field_to_string(field)
+}
+
+/// Timestamp value of column `name` under `options.display`: in the display
+/// timezone for UTC columns, as stored otherwise.
+fn render_instant(
+    name: &str,
+    instant: DateTime<Utc>,
+    precision: usize,
+    options: &RenderOptions,
+) -> String {
+    let format = options.display.timestamp_format;
+    if options.utc_columns.contains(name) {
+        options.display.timezone.format(instant, precision, format)
+    } else {
+        format.wall_clock(instant, precision)
+    }
+}
+
 pub fn field_to_string(field: &Field) -> String {
     match field {
         Field::Bool(v) => v.to_string(),
@@ -98,23 +715,24 @@ pub fn field_to_string(field: &Field) -> String {
         Field::UShort(v) => v.to_string(),
         Field::UInt(v) => v.to_string(),
         Field::ULong(v) => v.to_string(),
-        Field::Float(v) => v.to_string(),
-        Field::Double(v) => v.to_string(),
+        Field::Float(v) => float_to_string(*v as f64, v.to_string()),
+        Field::Double(v) => float_to_string(*v, v.to_string()),
         Field::Decimal(d) => format!("{:?}", d),
         Field::Str(v) => v.clone(),
         Field::Bytes(v) => general_purpose::STANDARD.encode(v.data()),
         Field::Date(v) => format_date(*v),
         Field::TimestampMillis(v) => {
-            format_timestamp_millis(*v).unwrap_or_else(|| v.to_string())
+            format_timestamp_millis(*v, TimestampFormat::default()).unwrap_or_else(|| v.to_string())
         }
         Field::TimestampMicros(v) => {
-            format_timestamp_micros(*v).unwrap_or_else(|| v.to_string())
+            format_timestamp_micros(*v, TimestampFormat::default()).unwrap_or_else(|| v.to_string())
         }
-        Field::Float16(v) => v.to_f64().to_string(),
+        Field::Float16(v) => float_to_string(v.to_f64(), v.to_string()),
 
-        Field::Group(_) => "[GROUP]".to_string(),
-        Field::ListInternal(_) => "[LIST]".to_string(),
-        Field::MapInternal(_) => "[MAP]".to_string(),
+        // Nested values as compact JSON so text exports keep their contents
+        Field::Group(_) | Field::ListInternal(_) | Field::MapInternal(_) => {
+            field_to_json(field).to_string()
+        }
         Field::Null => "".to_string(),
     }
 }
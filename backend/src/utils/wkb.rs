@@ -0,0 +1,274 @@
+use parquet::format::KeyValue;
+use std::collections::HashSet;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take<const N: usize>(&mut self, little_endian: bool) -> Option<[u8; N]> {
+        let slice = self.bytes.get(self.pos..self.pos + N)?;
+        self.pos += N;
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(slice);
+        if !little_endian {
+            buf.reverse();
+        }
+        Some(buf)
+    }
+
+    fn u32(&mut self, little_endian: bool) -> Option<u32> {
+        self.take::<4>(little_endian).map(u32::from_le_bytes)
+    }
+
+    fn f64(&mut self, little_endian: bool) -> Option<f64> {
+        self.take::<8>(little_endian).map(f64::from_le_bytes)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Header {
+    little_endian: bool,
+    kind: u32,
+    has_z: bool,
+    has_m: bool,
+}
+
+impl Header {
+    fn dimensions(&self) -> usize {
+        2 + self.has_z as usize + self.has_m as usize
+    }
+
+    fn tag(&self) -> &'static str {
+        match (self.has_z, self.has_m) {
+            (true, true) => " ZM",
+            (true, false) => " Z",
+            (false, true) => " M",
+            (false, false) => "",
+        }
+    }
+}
+
+fn read_header(reader: &mut Reader) -> Option<Header> {
+    let little_endian = match reader.take::<1>(true)?[0] {
+        0 => false,
+        1 => true,
+        _ => return None,
+    };
+    let code = reader.u32(little_endian)?;
+
+    // EWKB flags in the high bits; ISO encodes dimensions as +1000/+2000/+3000
+    let ewkb_z = code & 0x8000_0000 != 0;
+    let ewkb_m = code & 0x4000_0000 != 0;
+    if code & 0x2000_0000 != 0 {
+        reader.u32(little_endian)?; // SRID
+    }
+    let iso = code & 0x0FFF_FFFF;
+    let (kind, iso_z, iso_m) = match iso / 1000 {
+        0 => (iso, false, false),
+        1 => (iso - 1000, true, false),
+        2 => (iso - 2000, false, true),
+        3 => (iso - 3000, true, true),
+        _ => return None,
+    };
+
+    Some(Header {
+        little_endian,
+        kind,
+        has_z: ewkb_z || iso_z,
+        has_m: ewkb_m || iso_m,
+    })
+}
+
+type Coord = Vec<f64>;
+
+fn read_coord(reader: &mut Reader, header: Header) -> Option<Coord> {
+    (0..header.dimensions())
+        .map(|_| reader.f64(header.little_endian))
+        .collect()
+}
+
+fn read_coords(reader: &mut Reader, header: Header) -> Option<Vec<Coord>> {
+    let count = reader.u32(header.little_endian)?;
+    (0..count).map(|_| read_coord(reader, header)).collect()
+}
+
+fn read_rings(reader: &mut Reader, header: Header) -> Option<Vec<Vec<Coord>>> {
+    let count = reader.u32(header.little_endian)?;
+    (0..count).map(|_| read_coords(reader, header)).collect()
+}
+
+enum Geometry {
+    Point(Option<Coord>),
+    LineString(Vec<Coord>),
+    Polygon(Vec<Vec<Coord>>),
+    Multi(&'static str, Vec<Geometry>),
+}
+
+fn read_geometry(reader: &mut Reader) -> Option<(Header, Geometry)> {
+    let header = read_header(reader)?;
+    let geometry = match header.kind {
+        1 => {
+            let coord = read_coord(reader, header)?;
+            // Empty points are encoded with NaN coordinates
+            Geometry::Point((!coord.iter().all(|v| v.is_nan())).then_some(coord))
+        }
+        2 => Geometry::LineString(read_coords(reader, header)?),
+        3 => Geometry::Polygon(read_rings(reader, header)?),
+        4..=7 => {
+            let name = match header.kind {
+                4 => "MULTIPOINT",
+                5 => "MULTILINESTRING",
+                6 => "MULTIPOLYGON",
+                _ => "GEOMETRYCOLLECTION",
+            };
+            let count = reader.u32(header.little_endian)?;
+            let parts = (0..count)
+                .map(|_| read_geometry(reader).map(|(_, g)| g))
+                .collect::<Option<Vec<_>>>()?;
+            Geometry::Multi(name, parts)
+        }
+        _ => return None,
+    };
+    Some((header, geometry))
+}
+
+fn coord_text(coord: &Coord) -> String {
+    coord
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn coords_text(coords: &[Coord]) -> String {
+    if coords.is_empty() {
+        return "EMPTY".to_string();
+    }
+    let parts: Vec<String> = coords.iter().map(coord_text).collect();
+    format!("({})", parts.join(", "))
+}
+
+fn rings_text(rings: &[Vec<Coord>]) -> String {
+    if rings.is_empty() {
+        return "EMPTY".to_string();
+    }
+    let parts: Vec<String> = rings.iter().map(|ring| coords_text(ring)).collect();
+    format!("({})", parts.join(", "))
+}
+
+/// Body of a geometry inside a multi-geometry, without the type keyword.
+fn body_text(geometry: &Geometry) -> String {
+    match geometry {
+        Geometry::Point(Some(coord)) => format!("({})", coord_text(coord)),
+        Geometry::Point(None) => "EMPTY".to_string(),
+        Geometry::LineString(coords) => coords_text(coords),
+        Geometry::Polygon(rings) => rings_text(rings),
+        Geometry::Multi(_, parts) if parts.is_empty() => "EMPTY".to_string(),
+        Geometry::Multi(_, parts) => {
+            let parts: Vec<String> = parts.iter().map(body_text).collect();
+            format!("({})", parts.join(", "))
+        }
+    }
+}
+
+fn geometry_text(geometry: &Geometry, tag: &str) -> String {
+    match geometry {
+        Geometry::Point(_) => format!("POINT{} {}", tag, body_text(geometry)),
+        Geometry::LineString(_) => format!("LINESTRING{} {}", tag, body_text(geometry)),
+        Geometry::Polygon(_) => format!("POLYGON{} {}", tag, body_text(geometry)),
+        Geometry::Multi("GEOMETRYCOLLECTION", parts) if !parts.is_empty() => {
+            // Collection members keep their own type keywords
+            let parts: Vec<String> = parts.iter().map(|g| geometry_text(g, tag)).collect();
+            format!("GEOMETRYCOLLECTION{} ({})", tag, parts.join(", "))
+        }
+        Geometry::Multi(name, _) => format!("{}{} {}", name, tag, body_text(geometry)),
+    }
+}
+
+/// Render WKB (ISO or EWKB, with Z/M dimensions) as WKT, or `None` when the
+/// bytes are not valid WKB.
+pub fn wkb_to_wkt(bytes: &[u8]) -> Option<String> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let (header, geometry) = read_geometry(&mut reader)?;
+    Some(geometry_text(&geometry, header.tag()))
+}
+
+fn collect_xy(geometry: &Geometry, out: &mut Vec<(f64, f64)>) {
+    let mut push = |coords: &[Coord]| {
+        out.extend(coords.iter().map(|c| (c[0], c[1])));
+    };
+    match geometry {
+        Geometry::Point(Some(coord)) => push(std::slice::from_ref(coord)),
+        Geometry::Point(None) => {}
+        Geometry::LineString(coords) => push(coords),
+        Geometry::Polygon(rings) => rings.iter().for_each(|ring| push(ring)),
+        Geometry::Multi(_, parts) => parts.iter().for_each(|part| collect_xy(part, out)),
+    }
+}
+
+/// Bounding box of a WKB geometry as a little-endian WKB polygon, or `None`
+/// for invalid or empty geometries.
+pub fn wkb_envelope(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let (_, geometry) = read_geometry(&mut reader)?;
+
+    let mut points = Vec::new();
+    collect_xy(&geometry, &mut points);
+    let (min_x, min_y, max_x, max_y) = points.iter().fold(
+        (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ),
+        |(min_x, min_y, max_x, max_y), &(x, y)| {
+            (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+        },
+    );
+    if points.is_empty() || !min_x.is_finite() || !min_y.is_finite() {
+        return None;
+    }
+
+    let ring = [
+        (min_x, min_y),
+        (max_x, min_y),
+        (max_x, max_y),
+        (min_x, max_y),
+        (min_x, min_y),
+    ];
+    let mut wkb = vec![1u8];
+    wkb.extend_from_slice(&3u32.to_le_bytes());
+    wkb.extend_from_slice(&1u32.to_le_bytes());
+    wkb.extend_from_slice(&(ring.len() as u32).to_le_bytes());
+    for (x, y) in ring {
+        wkb.extend_from_slice(&x.to_le_bytes());
+        wkb.extend_from_slice(&y.to_le_bytes());
+    }
+    Some(wkb)
+}
+
+/// WKB-encoded geometry columns declared in GeoParquet `geo` file metadata.
+pub fn geoparquet_columns(key_value_metadata: Option<&Vec<KeyValue>>) -> HashSet<String> {
+    let geo = key_value_metadata
+        .and_then(|kvs| kvs.iter().find(|kv| kv.key == "geo"))
+        .and_then(|kv| kv.value.as_deref())
+        .and_then(|value| serde_json::from_str::<serde_json::Value>(value).ok());
+
+    geo.as_ref()
+        .and_then(|geo| geo.get("columns"))
+        .and_then(|columns| columns.as_object())
+        .map(|columns| {
+            columns
+                .iter()
+                .filter(|(_, spec)| {
+                    spec.get("encoding")
+                        .and_then(|e| e.as_str())
+                        .is_none_or(|e| e.eq_ignore_ascii_case("wkb"))
+                })
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
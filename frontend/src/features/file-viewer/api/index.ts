@@ -13,6 +13,28 @@ export interface ParquetMetadata {
     columns: ColumnInfo[];
 }
 
+export type FilterOperator =
+    | 'eq'
+    | 'not_eq'
+    | 'gt'
+    | 'gt_eq'
+    | 'lt'
+    | 'lt_eq'
+    | 'like'
+    | 'not_like'
+    | 'in'
+    | 'not_in'
+    | 'is_null'
+    | 'is_not_null';
+
+export type FilterValue = string | number | boolean | null;
+
+export type FilterExpr =
+    | { type: 'condition'; column: string; operator: FilterOperator; value?: FilterValue | FilterValue[] }
+    | { type: 'and'; filters: FilterExpr[] }
+    | { type: 'or'; filters: FilterExpr[] }
+    | { type: 'not'; filter: FilterExpr };
+
 export interface FileInfo {
     path: string;
     name: string;
@@ -39,11 +61,11 @@ export const getFileInfo = async (path: string): Promise<FileInfo> => {
     return await invoke('get_file_info', { path });
 };
 
-export const readParquetData = async (path: string, offset: number, limit: number, filter?: string): Promise<any[]> => {
+export const readParquetData = async (path: string, offset: number, limit: number, filter?: FilterExpr | null): Promise<any[]> => {
     return await invoke('read_parquet_data', { path, offset, limit, filter });
 };
 
-export const countParquetData = async (path: string, filter?: string): Promise<number> => {
+export const countParquetData = async (path: string, filter?: FilterExpr | null): Promise<number> => {
     return await invoke('count_parquet_data', { path, filter });
 };
 
@@ -4,7 +4,7 @@ import { useSettings } from "../../../contexts/SettingsContext";
 import { SearchBar } from "./search-bar";
 import { FilterBar } from "./filter-bar";
 import { ExportModal } from "./export-modal";
-import { openParquetFile, readParquetData, countParquetData, evictCache, ParquetMetadata } from "../api";
+import { openParquetFile, readParquetData, countParquetData, evictCache, ParquetMetadata, FilterExpr } from "../api";
 import { TabState } from "../routes/tab-content";
 import { getFileName } from "../../../lib/path";
 
@@ -42,7 +42,7 @@ function DataViewerComponent({ filePath, onClose, initialState, onStateChange }:
   const [isExportModalOpen, setIsExportModalOpen] = useState(false);
 
   // Filter state
-  const [activeFilter, setActiveFilter] = useState(initialState?.activeFilter || "");
+  const [activeFilter, setActiveFilter] = useState<FilterExpr | null>(initialState?.activeFilter ?? null);
 
   // Local state for page input (Enter key / blur to confirm)
   const [pageInput, setPageInput] = useState(String(currentPage));
@@ -109,7 +109,7 @@ function DataViewerComponent({ filePath, onClose, initialState, onStateChange }:
       const meta = await openParquetFile(filePath);
       setMetadata(meta);
       setTotalRows(meta.num_rows);
-      setActiveFilter("");
+      setActiveFilter(null);
     } catch (err) {
       setError(err as string);
       setLoading(false);
@@ -148,7 +148,7 @@ function DataViewerComponent({ filePath, onClose, initialState, onStateChange }:
     await loadFile();
   };
 
-  const handleFilterChange = useCallback((filter: string) => {
+  const handleFilterChange = useCallback((filter: FilterExpr | null) => {
     setActiveFilter(filter);
   }, []);
 
@@ -1,15 +1,27 @@
 import React, { useState, useEffect } from "react";
 import { Filter, X, Plus, Minus, Play } from "lucide-react";
 import { useTranslation } from "react-i18next";
-import { ColumnInfo } from "../api";
+import { ColumnInfo, FilterExpr, FilterOperator, FilterValue } from "../api";
 import { useSettings } from "../../../contexts/SettingsContext";
 
 interface FilterBarProps {
     columns: ColumnInfo[];
-    onFilterChange: (filter: string) => void;
-    activeFilter: string;
+    onFilterChange: (filter: FilterExpr | null) => void;
+    activeFilter: FilterExpr | null;
 }
 
+const operatorMap: Record<string, FilterOperator> = {
+    "=": "eq",
+    "!=": "not_eq",
+    ">": "gt",
+    "<": "lt",
+    ">=": "gt_eq",
+    "<=": "lt_eq",
+    "LIKE": "like",
+    "IS NULL": "is_null",
+    "IS NOT NULL": "is_not_null",
+};
+
 interface FilterRow {
     id: number;
     column: string;
@@ -51,7 +63,7 @@ export function FilterBar({ columns, onFilterChange, activeFilter }: FilterBarPr
         if (newFilters.length === 0) {
             setFilters([{ id: Date.now(), column: columns[0]?.name || "", operator: "=", value: "" }]);
             // Also clear the filter
-            onFilterChange("");
+            onFilterChange(null);
         } else {
             setFilters(newFilters);
             // We don't automatically submit on remove; user must press Apply
@@ -65,13 +77,13 @@ export function FilterBar({ columns, onFilterChange, activeFilter }: FilterBarPr
 
     const handleClear = () => {
         setFilters([{ id: Date.now(), column: columns[0]?.name || "", operator: "=", value: "" }]);
-        onFilterChange("");
+        onFilterChange(null);
     };
 
     const handleSubmit = (e: React.FormEvent) => {
         e.preventDefault();
 
-        const validConditions: string[] = [];
+        const validConditions: FilterExpr[] = [];
 
         for (const filter of filters) {
             if (!filter.column) continue;
@@ -80,35 +92,36 @@ export function FilterBar({ columns, onFilterChange, activeFilter }: FilterBarPr
             const needsValue = filter.operator !== "IS NULL" && filter.operator !== "IS NOT NULL";
             if (needsValue && !filter.value.trim()) continue;
 
-            let filterString = "";
+            const operator = operatorMap[filter.operator];
+            if (!operator) continue;
 
             if (!needsValue) {
-                filterString = `${filter.column} ${filter.operator}`;
-            } else {
-                // Find column type
-                const col = columns.find(c => c.name === filter.column);
-                const isString = col?.column_type === "STRING" || col?.column_type === "UTF8";
-
-                let formattedValue = filter.value;
-                if (isString || filter.operator === "LIKE") {
-                    formattedValue = `'${filter.value.replace(/'/g, "''")}'`;
-                }
-
-                let columnExpr = filter.column;
-                if (filter.operator === "LIKE" && !isString) {
-                    columnExpr = `CAST(${filter.column} AS TEXT)`;
-                }
+                validConditions.push({ type: "condition", column: filter.column, operator });
+                continue;
+            }
 
-                filterString = `${columnExpr} ${filter.operator} ${formattedValue}`;
+            // Find column type
+            const col = columns.find(c => c.name === filter.column);
+            const isString = col?.column_type === "STRING" || col?.column_type === "UTF8";
+            const isBoolean = col?.column_type === "BOOLEAN";
+
+            // Send numbers and booleans as typed values; the backend escapes everything else
+            let value: FilterValue = filter.value;
+            if (isBoolean && /^(true|false)$/i.test(filter.value.trim())) {
+                value = filter.value.trim().toLowerCase() === "true";
+            } else if (!isString && operator !== "like" && filter.value.trim() !== "" && !isNaN(Number(filter.value))) {
+                value = Number(filter.value);
             }
 
-            validConditions.push(filterString);
+            validConditions.push({ type: "condition", column: filter.column, operator, value });
         }
 
         if (validConditions.length === 0) {
-            onFilterChange("");
+            onFilterChange(null);
+        } else if (validConditions.length === 1) {
+            onFilterChange(validConditions[0]);
         } else {
-            onFilterChange(validConditions.join(" AND "));
+            onFilterChange({ type: "and", filters: validConditions });
         }
     };
 
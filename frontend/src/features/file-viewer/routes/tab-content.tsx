@@ -4,6 +4,7 @@ import { Table, Database } from 'lucide-react';
 import { useSettings } from '../../../contexts/SettingsContext';
 import { DataViewer } from '../components/data-viewer';
 import { QueryView } from '../../query/routes/query-view';
+import { FilterExpr } from '../api';
 
 interface TabContentProps {
   tab: {
@@ -22,7 +23,7 @@ export interface TabState {
   currentPage?: number;
   searchTerm?: string;
   viewMode?: 'browse' | 'query';
-  activeFilter?: string;
+  activeFilter?: FilterExpr | null;
   selectedRow?: number | null;
   isSearchOpen?: boolean;
 }